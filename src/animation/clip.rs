@@ -0,0 +1,151 @@
+use super::track::*;
+use crate::base::quaternion::Quaternion;
+use crate::base::material::PropertyValue;
+use rmu::raw::Vec3f;
+
+/// animated value and the track of it
+#[derive(Debug,Clone)]
+pub enum Channel {
+    /// object location
+    Location(String, Track<Vec3f>),
+    /// object euler rotation
+    Rotation(String, Track<Vec3f>),
+    /// object rotation by quaternion, written to the euler rotation of transform
+    Orientation(String, Track<Quaternion>),
+    /// object scale
+    Scale(String, Track<Vec3f>),
+    MaterialProperty {
+        material: String,
+        property: String,
+        track: Track<PropertyValue>,
+    },
+    /// weight of a morph target of the object mesh
+    MorphWeight {
+        object: String,
+        target: String,
+        track: Track<f32>,
+    },
+    CameraLookFrom(String, Track<Vec3f>),
+    CameraLookAt(String, Track<Vec3f>),
+    CameraFov(String, Track<f32>),
+}
+
+impl Channel {
+    pub fn duration(&self) -> f32 {
+        match self {
+            Channel::Location(_, track) => track.duration(),
+            Channel::Rotation(_, track) => track.duration(),
+            Channel::Orientation(_, track) => track.duration(),
+            Channel::Scale(_, track) => track.duration(),
+            Channel::MaterialProperty { track, .. } => track.duration(),
+            Channel::MorphWeight { track, .. } => track.duration(),
+            Channel::CameraLookFrom(_, track) => track.duration(),
+            Channel::CameraLookAt(_, track) => track.duration(),
+            Channel::CameraFov(_, track) => track.duration(),
+        }
+    }
+}
+
+#[derive(Debug,Copy,Clone,PartialEq)]
+pub enum WrapMode {
+    /// hold the last frame after the end
+    Once,
+    Loop,
+    /// play forward then backward
+    PingPong,
+}
+
+#[derive(Debug,Clone)]
+pub struct Clip {
+    pub name: String,
+    pub duration: f32,
+    pub wrap_mode: WrapMode,
+    pub channels: Vec<Channel>,
+}
+
+impl Clip {
+    pub fn new(name: &str, wrap_mode: WrapMode) -> Self {
+        Self {
+            name: name.to_string(),
+            duration: 0.0,
+            wrap_mode,
+            channels: Vec::new(),
+        }
+    }
+
+    /// add a channel, the duration is extended to the end of the channel
+    pub fn with_channel(mut self, channel: Channel) -> Self {
+        self.duration = self.duration.max(channel.duration());
+        self.channels.push(channel);
+        self
+    }
+
+    /// map the play time to the time of tracks
+    pub fn local_time(&self, t: f32) -> f32 {
+        if self.duration <= 0.0 || t <= 0.0 {
+            return t.max(0.0).min(self.duration.max(0.0));
+        }
+
+        match self.wrap_mode {
+            WrapMode::Once => t.min(self.duration),
+            WrapMode::Loop => t % self.duration,
+            WrapMode::PingPong => {
+                let t = t % (2.0 * self.duration);
+                if t > self.duration {
+                    2.0 * self.duration - t
+                } else {
+                    t
+                }
+            },
+        }
+    }
+
+    /// true if a `Once` clip has reached its end
+    pub fn is_finished(&self, t: f32) -> bool {
+        self.wrap_mode == WrapMode::Once && t >= self.duration
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn clip(wrap_mode: WrapMode) -> Clip {
+        Clip::new("clip", wrap_mode)
+            .with_channel(Channel::Location("a".to_string(), Track::new(Interpolation::Linear).key(0.0, [0.0; 3]).key(1.0, [1.0; 3])))
+            .with_channel(Channel::CameraFov("camera".to_string(), Track::new(Interpolation::Linear).key(0.0, 1.0).key(2.0, 0.5)))
+    }
+
+    #[test]
+    fn duration() {
+        assert_eq!(clip(WrapMode::Once).duration, 2.0);
+        assert_eq!(Clip::new("empty", WrapMode::Loop).local_time(3.0), 0.0);
+    }
+
+    #[test]
+    fn once() {
+        let clip = clip(WrapMode::Once);
+
+        assert_eq!(clip.local_time(-1.0), 0.0);
+        assert_eq!(clip.local_time(1.5), 1.5);
+        assert_eq!(clip.local_time(3.0), 2.0);
+        assert!(!clip.is_finished(1.5));
+        assert!(clip.is_finished(2.0));
+    }
+
+    #[test]
+    fn wrap() {
+        let looping = clip(WrapMode::Loop);
+        assert_eq!(looping.local_time(1.5), 1.5);
+        assert_eq!(looping.local_time(2.5), 0.5);
+        assert_eq!(looping.local_time(6.25), 0.25);
+        assert!(!looping.is_finished(10.0));
+
+        let ping_pong = clip(WrapMode::PingPong);
+        assert_eq!(ping_pong.local_time(1.0), 1.0);
+        assert_eq!(ping_pong.local_time(2.5), 1.5);
+        assert_eq!(ping_pong.local_time(3.75), 0.25);
+        assert_eq!(ping_pong.local_time(4.5), 0.5);
+        assert!(!ping_pong.is_finished(10.0));
+    }
+}
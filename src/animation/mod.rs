@@ -0,0 +1,10 @@
+/// keyframe animation of object transform, material property and camera
+mod track;
+mod clip;
+mod player;
+mod skeleton;
+
+pub use track::*;
+pub use clip::*;
+pub use player::*;
+pub use skeleton::*;
@@ -0,0 +1,235 @@
+use super::clip::*;
+use crate::scene::Scene;
+use crate::scene::object::{SubObject, PrimitiveObject};
+use crate::base::camera::Camera;
+use crate::base::material::PropertyValue;
+
+/// a clip playing in a player
+pub struct Playing {
+    pub clip: Clip,
+    /// player time when the clip start
+    pub start_time: f32,
+    pub speed: f32,
+}
+
+/// # Animation Player
+/// evaluate playing clips at time `t` and write the result to the scene,
+/// a later clip overrides the value written by an earlier clip
+pub struct AnimationPlayer {
+    pub playing: Vec<Playing>,
+}
+
+impl AnimationPlayer {
+    pub fn new() -> Self {
+        Self {
+            playing: Vec::new(),
+        }
+    }
+
+    pub fn play(&mut self, clip: Clip, start_time: f32) {
+        self.stop(&clip.name);
+        self.playing.push(Playing {
+            clip,
+            start_time,
+            speed: 1.0,
+        });
+    }
+
+    pub fn stop(&mut self, name: &str) {
+        self.playing.retain(|x| x.clip.name != name);
+    }
+
+    pub fn set_speed(&mut self, name: &str, speed: f32) {
+        for playing in self.playing.iter_mut().filter(|x| x.clip.name == name) {
+            playing.speed = speed;
+        }
+    }
+
+    pub fn is_playing(&self, name: &str) -> bool {
+        self.playing.iter().any(|x| x.clip.name == name)
+    }
+
+    /// write values of all playing clips at time `t` into the scene
+    pub fn evaluate(&self, t: f32, scene: &mut Scene) {
+        for playing in self.playing.iter() {
+            let time = playing.clip.local_time((t - playing.start_time) * playing.speed);
+
+            for channel in playing.clip.channels.iter() {
+                apply(channel, time, scene);
+            }
+        }
+    }
+
+    /// write camera channels of the camera name into a camera outside of scene
+    pub fn evaluate_camera(&self, t: f32, name: &str, camera: &mut Camera) {
+        for playing in self.playing.iter() {
+            let time = playing.clip.local_time((t - playing.start_time) * playing.speed);
+
+            for channel in playing.clip.channels.iter() {
+                match channel {
+                    Channel::CameraLookFrom(camera_name, track) if camera_name == name =>
+                        if let Some(look_from) = track.sample(time) { camera.set_look_from(look_from) },
+                    Channel::CameraLookAt(camera_name, track) if camera_name == name =>
+                        if let Some(look_at) = track.sample(time) { camera.set_look_at(look_at) },
+                    Channel::CameraFov(camera_name, track) if camera_name == name =>
+                        if let Some(fov) = track.sample(time) { camera.fov = fov },
+                    _ => (),
+                }
+            }
+        }
+    }
+
+    /// remove `Once` clips which have finished at time `t`
+    pub fn remove_finished(&mut self, t: f32) {
+        self.playing.retain(|x| !x.clip.is_finished((t - x.start_time) * x.speed));
+    }
+}
+
+fn apply(channel: &Channel, t: f32, scene: &mut Scene) {
+    match channel {
+        Channel::Location(name, track) => if let Some(location) = track.sample(t) {
+            update_transform(scene, name, |transform| transform.location = location);
+        },
+        Channel::Rotation(name, track) => if let Some(rotation) = track.sample(t) {
+            update_transform(scene, name, |transform| transform.rotation = rotation);
+        },
+        Channel::Orientation(name, track) => if let Some(orientation) = track.sample(t) {
+            update_transform(scene, name, |transform| transform.rotation = orientation.to_euler());
+        },
+        Channel::Scale(name, track) => if let Some(scale) = track.sample(t) {
+            update_transform(scene, name, |transform| transform.scale = scale);
+        },
+        Channel::MaterialProperty { material, property, track } => if let Some(value) = track.sample(t) {
+            if let Some(material_object) = scene.materials.iter_mut().find(|x| x.name == *material) {
+                set_property(&mut material_object.material.property, property, value);
+            }
+        },
+        Channel::MorphWeight { object, target, track } => if let Some(weight) = track.sample(t) {
+            if let Some(object) = scene.data.get_mut(object) {
+                if let SubObject::Atomic(PrimitiveObject::Data(mesh)) = &mut object.sub_objects {
+                    mesh.set_morph_weight(target, weight);
+                }
+            }
+        },
+        Channel::CameraLookFrom(name, track) => if let Some(look_from) = track.sample(t) {
+            update_camera(scene, name, |camera| camera.set_look_from(look_from));
+        },
+        Channel::CameraLookAt(name, track) => if let Some(look_at) = track.sample(t) {
+            update_camera(scene, name, |camera| camera.set_look_at(look_at));
+        },
+        Channel::CameraFov(name, track) => if let Some(fov) = track.sample(t) {
+            update_camera(scene, name, |camera| camera.fov = fov);
+        },
+    }
+}
+
+fn update_transform<F: FnOnce(&mut crate::base::transform::Transform)>(scene: &mut Scene, name: &str, f: F) {
+    if let Some(object) = scene.data.get_mut(name) {
+        f(&mut object.transform);
+        object.dirty = true;
+    }
+}
+
+fn update_camera<F: FnOnce(&mut Camera)>(scene: &mut Scene, name: &str, f: F) {
+    if let Some(camera_object) = scene.cameras.iter_mut().find(|x| x.name == name) {
+        f(&mut camera_object.camera);
+    }
+}
+
+fn set_property(property: &mut Vec<(String,PropertyValue)>, name: &str, value: PropertyValue) {
+    match property.iter_mut().find(|(x, _)| x == name) {
+        Some((_, old_value)) => *old_value = value,
+        None => property.push((name.to_string(), value)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::animation::{Track, Interpolation};
+    use crate::base::material::pure_color_material;
+    use crate::scene::object::Object;
+
+    fn scene() -> Scene {
+        let mut scene = Scene::new("scene".to_string());
+        scene.insert_object(Object::new("box".to_string())).unwrap();
+        scene.add_camera("camera".to_string(), Camera::new([0.0, 0.0, 5.0], [0.0, 0.0, 0.0], [0.0, 1.0, 0.0], 1.0));
+        scene.add_material("red".to_string(), pure_color_material([1.0, 0.0, 0.0]));
+        scene.update_world_transforms();
+        scene
+    }
+
+    fn moving(name: &str, to: f32) -> Clip {
+        Clip::new(name, WrapMode::Once)
+            .with_channel(Channel::Location("box".to_string(), Track::new(Interpolation::Linear).key(0.0, [0.0; 3]).key(2.0, [to, 0.0, 0.0])))
+    }
+
+    #[test]
+    fn write_scene() {
+        let mut scene = scene();
+        let clip = moving("move", 2.0)
+            .with_channel(Channel::CameraFov("camera".to_string(), Track::new(Interpolation::Linear).key(0.0, 1.0).key(2.0, 0.5)))
+            .with_channel(Channel::CameraLookFrom("camera".to_string(), Track::new(Interpolation::Step).key(0.0, [0.0, 1.0, 5.0])))
+            .with_channel(Channel::MaterialProperty {
+                material: "red".to_string(),
+                property: "opacity".to_string(),
+                track: Track::new(Interpolation::Linear).key(0.0, PropertyValue::Float(0.0)).key(2.0, PropertyValue::Float(1.0)),
+            });
+
+        let mut player = AnimationPlayer::new();
+        player.play(clip, 1.0);
+        player.evaluate(2.0, &mut scene);
+
+        let object = scene.get_object("box").unwrap();
+        assert_eq!(object.transform.location, [1.0, 0.0, 0.0]);
+        assert!(object.dirty);
+
+        let camera = scene.get_camera("camera".to_string()).unwrap();
+        assert_eq!(camera.fov, 0.75);
+        assert_eq!(camera.look_from(), [0.0, 1.0, 5.0]);
+
+        let material = &scene.materials.iter().find(|x| x.name == "red").unwrap().material;
+        assert!(material.property.contains(&("opacity".to_string(), PropertyValue::Float(0.5))));
+
+        /* a camera outside of the scene */
+        let mut camera = Camera::new([0.0, 0.0, 5.0], [0.0, 0.0, 0.0], [0.0, 1.0, 0.0], 1.0);
+        player.evaluate_camera(3.0, "camera", &mut camera);
+        assert_eq!(camera.fov, 0.5);
+        assert_eq!(camera.look_from(), [0.0, 1.0, 5.0]);
+    }
+
+    #[test]
+    fn later_clip_overrides() {
+        let mut scene = scene();
+        let mut player = AnimationPlayer::new();
+        player.play(moving("a", 2.0), 0.0);
+        player.play(moving("b", 4.0), 0.0);
+        player.set_speed("b", 2.0);
+
+        player.evaluate(0.5, &mut scene);
+        assert_eq!(scene.get_object("box").unwrap().transform.location, [2.0, 0.0, 0.0]);
+
+        /* playing a clip with the same name replaces it */
+        player.play(moving("b", 8.0), 0.0);
+        assert_eq!(player.playing.len(), 2);
+        player.evaluate(1.0, &mut scene);
+        assert_eq!(scene.get_object("box").unwrap().transform.location, [4.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn remove_finished() {
+        let mut player = AnimationPlayer::new();
+        player.play(moving("once", 1.0), 1.0);
+        player.play(Clip { wrap_mode: WrapMode::Loop, ..moving("loop", 1.0) }, 0.0);
+
+        player.remove_finished(2.5);
+        assert!(player.is_playing("once"));
+
+        player.remove_finished(3.0);
+        assert!(!player.is_playing("once"));
+        assert!(player.is_playing("loop"));
+
+        player.stop("loop");
+        assert!(player.playing.is_empty());
+    }
+}
@@ -0,0 +1,237 @@
+use crate::base::transform::{Transform, IDENTITY};
+use crate::base::{Vertex, SkinnedVertex};
+use rmu::matrix::Matrix4x4;
+use rmu::raw::{Mat4f, Vec3f};
+
+/// max number of joints of the skin uniform block in shader
+pub const JOINTS_MAX_NUMBER: usize = 64;
+
+#[derive(Clone)]
+pub struct Joint {
+    pub name: String,
+    /// index of parent joint, the parent is before its children in `Skeleton::joints`
+    pub parent: Option<usize>,
+    /// local pose relative to the parent joint
+    pub transform: Transform,
+    /// inverse of the joint world matrix in bind pose
+    pub inverse_bind: Mat4f,
+}
+
+/// # Skeleton
+/// joint hierarchy for linear blend skinning
+#[derive(Clone)]
+pub struct Skeleton {
+    pub joints: Vec<Joint>,
+}
+
+impl Skeleton {
+    pub fn new() -> Self {
+        Self {
+            joints: Vec::new(),
+        }
+    }
+
+    /// add a joint and return its index, `parent` must be added before
+    pub fn add_joint(&mut self, name: &str, parent: Option<usize>, transform: Transform, inverse_bind: Mat4f) -> usize {
+        self.joints.push(Joint {
+            name: name.to_string(),
+            parent,
+            transform,
+            inverse_bind,
+        });
+        self.joints.len() - 1
+    }
+
+    pub fn find_joint(&self, name: &str) -> Option<usize> {
+        self.joints.iter().position(|x| x.name == name)
+    }
+
+    /// world matrices of joints in current pose
+    pub fn world_matrices(&self) -> Vec<Mat4f> {
+        let mut world_matrices: Vec<Mat4f> = Vec::with_capacity(self.joints.len());
+
+        for joint in self.joints.iter() {
+            let parent_world = match joint.parent {
+                Some(parent) if parent < world_matrices.len() => world_matrices[parent],
+                _ => IDENTITY,
+            };
+            world_matrices.push((Matrix4x4::from(parent_world) * joint.transform.transform()).into());
+        }
+
+        world_matrices
+    }
+
+    /// set the inverse bind matrices from the current pose
+    pub fn bind(&mut self) {
+        let world_matrices = self.world_matrices();
+
+        for (joint, world) in self.joints.iter_mut().zip(world_matrices.into_iter()) {
+            joint.inverse_bind = crate::base::transform::inverse(world).unwrap_or(IDENTITY);
+        }
+    }
+
+    /// skinning matrices of joints in current pose, upload them to the skin uniform block
+    pub fn joint_matrices(&self) -> Vec<Mat4f> {
+        self.world_matrices()
+            .into_iter()
+            .zip(self.joints.iter())
+            .map(|(world, joint)| (Matrix4x4::from(world) * Matrix4x4::from(joint.inverse_bind)).into())
+            .collect()
+    }
+}
+
+/// CPU linear blend skinning, the fallback of `skin_matrix` in the vertex shader library.
+/// matrices are column major like the GPU, joints out of range or beyond `JOINTS_MAX_NUMBER` are ignored,
+/// the weights are normalized and a vertex without weight keeps its bind pose
+pub fn skin_vertices(vertices: &[SkinnedVertex], joint_matrices: &[Mat4f]) -> Vec<Vertex> {
+    vertices
+        .iter()
+        .map(|vertex| {
+            let mut skin = [[0.0f32; 4]; 4];
+            let mut total_weight = 0.0;
+
+            for (joint, weight) in vertex.joints.iter().zip(vertex.weights.iter()) {
+                if *weight == 0.0 || *joint as usize >= JOINTS_MAX_NUMBER {
+                    continue;
+                }
+
+                if let Some(matrix) = joint_matrices.get(*joint as usize) {
+                    for c in 0..4 {
+                        for r in 0..4 {
+                            skin[c][r] += matrix[c][r] * weight;
+                        }
+                    }
+                    total_weight += weight;
+                }
+            }
+
+            if total_weight == 0.0 {
+                return vertex.vertex();
+            }
+
+            let position = transform_point(&skin, vertex.position);
+            let normal = normalize(transform_direction(&skin, vertex.normal));
+
+            Vertex::new(position, normal, vertex.tex_coordinate)
+        })
+        .collect()
+}
+
+fn transform_point(m: &Mat4f, p: Vec3f) -> Vec3f {
+    let w = m[0][3] * p[0] + m[1][3] * p[1] + m[2][3] * p[2] + m[3][3];
+    let w = if w == 0.0 { 1.0 } else { w };
+
+    [(m[0][0] * p[0] + m[1][0] * p[1] + m[2][0] * p[2] + m[3][0]) / w
+    ,(m[0][1] * p[0] + m[1][1] * p[1] + m[2][1] * p[2] + m[3][1]) / w
+    ,(m[0][2] * p[0] + m[1][2] * p[1] + m[2][2] * p[2] + m[3][2]) / w]
+}
+
+fn transform_direction(m: &Mat4f, d: Vec3f) -> Vec3f {
+    [m[0][0] * d[0] + m[1][0] * d[1] + m[2][0] * d[2]
+    ,m[0][1] * d[0] + m[1][1] * d[1] + m[2][1] * d[2]
+    ,m[0][2] * d[0] + m[1][2] * d[1] + m[2][2] * d[2]]
+}
+
+fn normalize(v: Vec3f) -> Vec3f {
+    let length = (v[0] * v[0] + v[1] * v[1] + v[2] * v[2]).sqrt();
+    if length == 0.0 {
+        v
+    } else {
+        [v[0] / length, v[1] / length, v[2] / length]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: Vec3f, b: Vec3f) -> bool {
+        a.iter().zip(b.iter()).all(|(x, y)| (x - y).abs() < 1e-4)
+    }
+
+    fn vertex(position: Vec3f, joints: [u32;4], weights: [f32;4]) -> SkinnedVertex {
+        SkinnedVertex::new(Vertex::new(position, [1.0, 0.0, 0.0], [0.0, 0.0]), joints, weights)
+    }
+
+    /* a bone from the origin to (0,1,0) and a bone from (0,1,0) bent by 90 degrees around z */
+    fn bent_arm() -> Skeleton {
+        let mut skeleton = Skeleton::new();
+        let root = skeleton.add_joint("root", None, Transform::new(), IDENTITY);
+
+        let mut elbow = Transform::new();
+        elbow.translate(0.0, 1.0, 0.0);
+        skeleton.add_joint("elbow", Some(root), elbow, IDENTITY);
+        skeleton.bind();
+
+        skeleton.joints[1].transform.add_rotate(0.0, 0.0, std::f32::consts::FRAC_PI_2);
+        skeleton
+    }
+
+    #[test]
+    fn two_joint_bend() {
+        let joint_matrices = bent_arm().joint_matrices();
+        let vertices = [
+            vertex([0.0, 0.5, 0.0], [0, 1, 0, 0], [1.0, 0.0, 0.0, 0.0]),
+            vertex([0.0, 1.0, 0.0], [0, 1, 0, 0], [0.5, 0.5, 0.0, 0.0]),
+            vertex([0.0, 2.0, 0.0], [0, 1, 0, 0], [0.0, 1.0, 0.0, 0.0]),
+            vertex([0.0, 1.5, 0.0], [0, 1, 0, 0], [0.5, 0.5, 0.0, 0.0]),
+        ];
+        let skinned = skin_vertices(&vertices, &joint_matrices);
+
+        /* the rotation around z turns +y to +x, the elbow bends the forearm to +x */
+        let side = 1.0;
+
+        assert!(close(skinned[0].position, [0.0, 0.5, 0.0]));
+        assert!(close(skinned[1].position, [0.0, 1.0, 0.0]));
+        assert!(close(skinned[2].position, [side, 1.0, 0.0]));
+        assert!(close(skinned[3].position, [side * 0.25, 1.25, 0.0]));
+
+        /* normals turn with the joint and stay unit length */
+        assert!(close(skinned[0].normal, [1.0, 0.0, 0.0]));
+        assert!(close(skinned[2].normal, [0.0, -side, 0.0]));
+        assert!(skinned.iter().all(|x| (x.normal.iter().map(|n| n * n).sum::<f32>() - 1.0).abs() < 1e-4));
+    }
+
+    #[test]
+    fn weights_are_normalized() {
+        let joint_matrices = bent_arm().joint_matrices();
+        let half = skin_vertices(&[vertex([0.0, 2.0, 0.0], [1, 0, 0, 0], [0.5, 0.0, 0.0, 0.0])], &joint_matrices);
+        let full = skin_vertices(&[vertex([0.0, 2.0, 0.0], [1, 0, 0, 0], [1.0, 0.0, 0.0, 0.0])], &joint_matrices);
+        assert!(close(half[0].position, full[0].position), "{:?}", half[0].position);
+
+        let blend = skin_vertices(&[vertex([0.0, 1.5, 0.0], [0, 1, 0, 0], [0.25, 0.25, 0.0, 0.0])], &joint_matrices);
+        let normalized = skin_vertices(&[vertex([0.0, 1.5, 0.0], [0, 1, 0, 0], [0.5, 0.5, 0.0, 0.0])], &joint_matrices);
+        assert!(close(blend[0].position, normalized[0].position), "{:?}", blend[0].position);
+    }
+
+    #[test]
+    fn joints_out_of_range() {
+        let joint_matrices = bent_arm().joint_matrices();
+
+        /* the weight of the missing joint is dropped and the rest is normalized */
+        let vertices = [
+            vertex([0.0, 2.0, 0.0], [1, 7, 0, 0], [0.5, 0.5, 0.0, 0.0]),
+            vertex([0.0, 2.0, 0.0], [1, 0, 0, 0], [1.0, 0.0, 0.0, 0.0]),
+            vertex([3.0, 4.0, 5.0], [9, 0, 0, 0], [1.0, 0.0, 0.0, 0.0]),
+        ];
+        let skinned = skin_vertices(&vertices, &joint_matrices);
+
+        assert!(close(skinned[0].position, skinned[1].position), "{:?}", skinned[0].position);
+
+        /* no joint in range keeps the bind pose */
+        assert!(close(skinned[2].position, [3.0, 4.0, 5.0]));
+        assert!(close(skinned[2].normal, [1.0, 0.0, 0.0]));
+    }
+
+    #[test]
+    fn joints_beyond_skin_block() {
+        /* the skin uniform block holds `JOINTS_MAX_NUMBER` matrices, the shader ignores the rest */
+        let mut joint_matrices = vec![IDENTITY; JOINTS_MAX_NUMBER + 1];
+        joint_matrices[JOINTS_MAX_NUMBER] = Transform::position(1.0, 0.0, 0.0).into();
+
+        let vertices = [vertex([0.0, 1.0, 0.0], [JOINTS_MAX_NUMBER as u32, 0, 0, 0], [1.0, 0.0, 0.0, 0.0])];
+        let skinned = skin_vertices(&vertices, &joint_matrices);
+
+        assert!(close(skinned[0].position, [0.0, 1.0, 0.0]), "{:?}", skinned[0].position);
+    }
+}
@@ -0,0 +1,324 @@
+use rmu::raw::{Vec2f,Vec3f,Vec4f};
+use crate::base::quaternion::Quaternion;
+use crate::base::material::PropertyValue;
+
+#[derive(Debug,Copy,Clone,PartialEq)]
+pub enum Interpolation {
+    /// hold the value of the previous keyframe
+    Step,
+    Linear,
+    /// cubic hermite spline with the tangents of keyframes
+    CubicHermite,
+}
+
+/// value can be interpolated by keyframe track
+pub trait Interpolate: Clone {
+    fn lerp(a: &Self, b: &Self, t: f32) -> Self;
+    /// `m0` is the out tangent of `p0` and `m1` is the in tangent of `p1`, scaled by the keyframe interval
+    fn hermite(p0: &Self, m0: &Self, p1: &Self, m1: &Self, t: f32) -> Self;
+    /// multiply a tangent by a scalar
+    fn scale(&self, s: f32) -> Self;
+}
+
+/// hermite basis functions
+fn hermite_basis(t: f32) -> [f32;4] {
+    let t2 = t * t;
+    let t3 = t2 * t;
+    [2.0 * t3 - 3.0 * t2 + 1.0, t3 - 2.0 * t2 + t, -2.0 * t3 + 3.0 * t2, t3 - t2]
+}
+
+impl Interpolate for f32 {
+    fn lerp(a: &Self, b: &Self, t: f32) -> Self {
+        a + (b - a) * t
+    }
+
+    fn hermite(p0: &Self, m0: &Self, p1: &Self, m1: &Self, t: f32) -> Self {
+        let [h00, h10, h01, h11] = hermite_basis(t);
+        h00 * p0 + h10 * m0 + h01 * p1 + h11 * m1
+    }
+
+    fn scale(&self, s: f32) -> Self {
+        self * s
+    }
+}
+
+macro_rules! impl_interpolate_array {
+    ($t:ty, $n:expr) => {
+        impl Interpolate for $t {
+            fn lerp(a: &Self, b: &Self, t: f32) -> Self {
+                let mut result = [0.0; $n];
+                for i in 0..$n {
+                    result[i] = f32::lerp(&a[i], &b[i], t);
+                }
+                result
+            }
+
+            fn hermite(p0: &Self, m0: &Self, p1: &Self, m1: &Self, t: f32) -> Self {
+                let mut result = [0.0; $n];
+                for i in 0..$n {
+                    result[i] = f32::hermite(&p0[i], &m0[i], &p1[i], &m1[i], t);
+                }
+                result
+            }
+
+            fn scale(&self, s: f32) -> Self {
+                let mut result = *self;
+                for i in 0..$n {
+                    result[i] *= s;
+                }
+                result
+            }
+        }
+    };
+}
+
+impl_interpolate_array!(Vec2f, 2);
+impl_interpolate_array!(Vec3f, 3);
+impl_interpolate_array!(Vec4f, 4);
+
+impl Interpolate for Quaternion {
+    fn lerp(a: &Self, b: &Self, t: f32) -> Self {
+        Quaternion::slerp(*a, *b, t)
+    }
+
+    /// hermite on components then normalized, tangents are component derivatives
+    fn hermite(p0: &Self, m0: &Self, p1: &Self, m1: &Self, t: f32) -> Self {
+        let p1 = if Quaternion::dot(*p0, *p1) < 0.0 { -*p1 } else { *p1 };
+        let result: Vec4f = Vec4f::hermite(&(*p0).into(), &(*m0).into(), &p1.into(), &(*m1).into(), t);
+        Quaternion::from(result).normalized()
+    }
+
+    fn scale(&self, s: f32) -> Self {
+        Quaternion::new(self.x * s, self.y * s, self.z * s, self.w * s)
+    }
+}
+
+/// float and vector property are interpolated, other property step at the next keyframe
+impl Interpolate for PropertyValue {
+    fn lerp(a: &Self, b: &Self, t: f32) -> Self {
+        match (a, b) {
+            (PropertyValue::Float(a), PropertyValue::Float(b)) => PropertyValue::Float(f32::lerp(a, b, t)),
+            (PropertyValue::Vec2(a), PropertyValue::Vec2(b)) => PropertyValue::Vec2(Vec2f::lerp(a, b, t)),
+            (PropertyValue::Vec3(a), PropertyValue::Vec3(b)) => PropertyValue::Vec3(Vec3f::lerp(a, b, t)),
+            (PropertyValue::Vec4(a), PropertyValue::Vec4(b)) => PropertyValue::Vec4(Vec4f::lerp(a, b, t)),
+            _ => if t < 1.0 { a.clone() } else { b.clone() },
+        }
+    }
+
+    fn hermite(p0: &Self, m0: &Self, p1: &Self, m1: &Self, t: f32) -> Self {
+        match (p0, m0, p1, m1) {
+            (PropertyValue::Float(p0), PropertyValue::Float(m0), PropertyValue::Float(p1), PropertyValue::Float(m1)) =>
+                PropertyValue::Float(f32::hermite(p0, m0, p1, m1, t)),
+            (PropertyValue::Vec2(p0), PropertyValue::Vec2(m0), PropertyValue::Vec2(p1), PropertyValue::Vec2(m1)) =>
+                PropertyValue::Vec2(Vec2f::hermite(p0, m0, p1, m1, t)),
+            (PropertyValue::Vec3(p0), PropertyValue::Vec3(m0), PropertyValue::Vec3(p1), PropertyValue::Vec3(m1)) =>
+                PropertyValue::Vec3(Vec3f::hermite(p0, m0, p1, m1, t)),
+            (PropertyValue::Vec4(p0), PropertyValue::Vec4(m0), PropertyValue::Vec4(p1), PropertyValue::Vec4(m1)) =>
+                PropertyValue::Vec4(Vec4f::hermite(p0, m0, p1, m1, t)),
+            _ => Self::lerp(p0, p1, t),
+        }
+    }
+
+    fn scale(&self, s: f32) -> Self {
+        match self {
+            PropertyValue::Float(value) => PropertyValue::Float(value.scale(s)),
+            PropertyValue::Vec2(value) => PropertyValue::Vec2(value.scale(s)),
+            PropertyValue::Vec3(value) => PropertyValue::Vec3(value.scale(s)),
+            PropertyValue::Vec4(value) => PropertyValue::Vec4(value.scale(s)),
+            _ => self.clone(),
+        }
+    }
+}
+
+#[derive(Debug,Clone)]
+pub struct Keyframe<T> {
+    pub time: f32,
+    pub value: T,
+    /// tangents for cubic hermite, derivative of value by time
+    pub in_tangent: T,
+    pub out_tangent: T,
+}
+
+impl<T: Clone> Keyframe<T> {
+    /// keyframe with tangents
+    pub fn new(time: f32, value: T, in_tangent: T, out_tangent: T) -> Self {
+        Self {
+            time,
+            value,
+            in_tangent,
+            out_tangent,
+        }
+    }
+}
+
+/// keyframes of a value, sorted by time
+#[derive(Debug,Clone)]
+pub struct Track<T> {
+    pub keyframes: Vec<Keyframe<T>>,
+    pub interpolation: Interpolation,
+}
+
+impl<T: Interpolate> Track<T> {
+    pub fn new(interpolation: Interpolation) -> Self {
+        Self {
+            keyframes: Vec::new(),
+            interpolation,
+        }
+    }
+
+    /// insert a keyframe with zero tangents
+    pub fn key(self, time: f32, value: T) -> Self {
+        let tangent = value.scale(0.0);
+        self.key_with_tangent(time, value, tangent.clone(), tangent)
+    }
+
+    pub fn key_with_tangent(mut self, time: f32, value: T, in_tangent: T, out_tangent: T) -> Self {
+        self.insert(Keyframe::new(time, value, in_tangent, out_tangent));
+        self
+    }
+
+    /// insert a keyframe keeping the keyframes sorted, replace the keyframe at the same time
+    pub fn insert(&mut self, keyframe: Keyframe<T>) {
+        match self.keyframes.iter().position(|x| x.time >= keyframe.time) {
+            Some(index) if self.keyframes[index].time == keyframe.time => self.keyframes[index] = keyframe,
+            Some(index) => self.keyframes.insert(index, keyframe),
+            None => self.keyframes.push(keyframe),
+        }
+    }
+
+    /// time of the last keyframe
+    pub fn duration(&self) -> f32 {
+        self.keyframes.last().map(|x| x.time).unwrap_or(0.0)
+    }
+
+    /// value at time `t`, clamped to the first and last keyframe
+    pub fn sample(&self, t: f32) -> Option<T> {
+        let first = self.keyframes.first()?;
+        let last = self.keyframes.last()?;
+
+        if t <= first.time {
+            return Some(first.value.clone());
+        }
+
+        if t >= last.time {
+            return Some(last.value.clone());
+        }
+
+        let index = self.keyframes.iter().position(|x| x.time > t)?;
+        let k0 = &self.keyframes[index - 1];
+        let k1 = &self.keyframes[index];
+
+        let interval = k1.time - k0.time;
+        let s = (t - k0.time) / interval;
+
+        Some(match self.interpolation {
+            Interpolation::Step => k0.value.clone(),
+            Interpolation::Linear => T::lerp(&k0.value, &k1.value, s),
+            Interpolation::CubicHermite => T::hermite(
+                &k0.value,
+                &k0.out_tangent.scale(interval),
+                &k1.value,
+                &k1.in_tangent.scale(interval),
+                s
+            ),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1e-5
+    }
+
+    fn close_quaternion(a: Quaternion, b: Quaternion) -> bool {
+        close(a.x, b.x) && close(a.y, b.y) && close(a.z, b.z) && close(a.w, b.w)
+    }
+
+    #[test]
+    fn insert_sorted() {
+        let track: Track<f32> = Track::new(Interpolation::Linear)
+            .key(1.0, 1.0)
+            .key(0.0, 0.0)
+            .key(2.0, 2.0)
+            .key(1.0, 5.0);
+
+        assert_eq!(track.keyframes.iter().map(|x| x.time).collect::<Vec<f32>>(), vec![0.0, 1.0, 2.0]);
+        assert_eq!(track.keyframes[1].value, 5.0);
+        assert_eq!(track.duration(), 2.0);
+        assert!(Track::<f32>::new(Interpolation::Linear).sample(0.0).is_none());
+    }
+
+    #[test]
+    fn step() {
+        let track: Track<f32> = Track::new(Interpolation::Step).key(0.0, 0.0).key(1.0, 10.0);
+
+        assert_eq!(track.sample(-1.0), Some(0.0));
+        assert_eq!(track.sample(0.5), Some(0.0));
+        assert_eq!(track.sample(0.999), Some(0.0));
+        assert_eq!(track.sample(1.0), Some(10.0));
+        assert_eq!(track.sample(2.0), Some(10.0));
+    }
+
+    #[test]
+    fn linear() {
+        let track = Track::new(Interpolation::Linear).key(0.0, 0.0).key(2.0, 10.0).key(3.0, 0.0);
+
+        assert!(close(track.sample(0.5).unwrap(), 2.5));
+        assert!(close(track.sample(2.0).unwrap(), 10.0));
+        assert!(close(track.sample(2.5).unwrap(), 5.0));
+
+        let vector = Track::new(Interpolation::Linear).key(0.0, [0.0, 0.0, 0.0]).key(1.0, [1.0, 2.0, -4.0]);
+        assert_eq!(vector.sample(0.25), Some([0.25, 0.5, -1.0]));
+    }
+
+    #[test]
+    fn cubic_hermite() {
+        /* zero tangents ease in and out */
+        let ease = Track::new(Interpolation::CubicHermite).key(0.0, 0.0).key(2.0, 1.0);
+        assert!(close(ease.sample(1.0).unwrap(), 0.5));
+        assert!(close(ease.sample(0.5).unwrap(), 0.15625));
+
+        /* tangents are derivatives by time, a straight line is reproduced on any interval */
+        let line = Track::new(Interpolation::CubicHermite)
+            .key_with_tangent(0.0, 0.0, 1.0, 1.0)
+            .key_with_tangent(2.0, 2.0, 1.0, 1.0);
+        assert!(close(line.sample(0.5).unwrap(), 0.5));
+        assert!(close(line.sample(1.3).unwrap(), 1.3));
+    }
+
+    #[test]
+    fn slerp() {
+        let a = Quaternion::identity();
+        let b = Quaternion::from_axis_angle([0.0, 0.0, 1.0], std::f32::consts::FRAC_PI_2);
+        let half = Quaternion::from_axis_angle([0.0, 0.0, 1.0], std::f32::consts::FRAC_PI_4);
+
+        let track = Track::new(Interpolation::Linear).key(0.0, a).key(1.0, b);
+        assert!(close_quaternion(track.sample(0.5).unwrap(), half));
+
+        /* -b is the same rotation, the shortest path is taken */
+        let track = Track::new(Interpolation::Linear).key(0.0, a).key(1.0, -b);
+        assert!(close_quaternion(track.sample(0.5).unwrap(), half));
+
+        let track = Track::new(Interpolation::CubicHermite).key(0.0, a).key(1.0, b);
+        let q = track.sample(0.3).unwrap();
+        assert!(close(Quaternion::dot(q, q), 1.0));
+    }
+
+    #[test]
+    fn property() {
+        let track = Track::new(Interpolation::Linear)
+            .key(0.0, PropertyValue::Float(0.0))
+            .key(1.0, PropertyValue::Float(4.0));
+        assert_eq!(track.sample(0.25), Some(PropertyValue::Float(1.0)));
+
+        /* bool can't be interpolated, it steps at the next keyframe */
+        let track = Track::new(Interpolation::Linear)
+            .key(0.0, PropertyValue::Bool(false))
+            .key(1.0, PropertyValue::Bool(true));
+        assert_eq!(track.sample(0.9), Some(PropertyValue::Bool(false)));
+        assert_eq!(track.sample(1.0), Some(PropertyValue::Bool(true)));
+    }
+}
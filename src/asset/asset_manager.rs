@@ -1,7 +1,8 @@
-use std::fs::{File,read_to_string};
+use std::fs::{File,read,read_to_string};
 use std::io::Write;
 use serde_derive::{Serialize,Deserialize};
 use std::collections::HashMap;
+use crate::base::mesh::Mesh;
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct AssetManager {
@@ -35,6 +36,30 @@ impl AssetManager {
         self.meshes.insert(name, path);
     }
 
+    pub fn mesh_path(&self, name: &str) -> Option<&String> {
+        self.meshes.get(name)
+    }
+
+    /// read a mesh file of the mesh asset
+    pub fn load_mesh(&self, name: &str) -> Result<Mesh,AssetManagerFileErr> {
+        let path = self.meshes.get(name).ok_or(AssetManagerFileErr::PathErr)?;
+
+        match read(path) {
+            Ok(data) => Mesh::from_bytes(&data).ok_or(AssetManagerFileErr::SrcErr),
+            Err(_) => Err(AssetManagerFileErr::PathErr),
+        }
+    }
+
+    /// write the mesh to the path of the mesh asset
+    pub fn save_mesh(&self, name: &str, mesh: &Mesh) -> Result<(),AssetManagerFileErr> {
+        let path = self.meshes.get(name).ok_or(AssetManagerFileErr::PathErr)?;
+
+        match File::create(path) {
+            Ok(mut file) => file.write_all(&mesh.as_bytes()).map_err(|_| AssetManagerFileErr::PathErr),
+            Err(_) => Err(AssetManagerFileErr::PathErr),
+        }
+    }
+
     pub fn save(&self, path: &str) {
         let src = toml::to_string(&self).unwrap();
         let mut file = File::create(path).unwrap();
@@ -9,7 +9,10 @@ pub use event::*;
 pub use renderer::*;
 
 use glium::implement_vertex;
-use crate::base::vertex::{Vertex, Position};
+use crate::base::vertex::{Vertex, SkinnedVertex, Position};
+use renderer::buffer::InstanceAttribute;
 
 implement_vertex!(Vertex, position, normal, tex_coordinate);
+implement_vertex!(SkinnedVertex, position, normal, tex_coordinate, joints, weights);
 implement_vertex!(Position, position, tex_coordinate);
+implement_vertex!(InstanceAttribute, instance_transform, instance_tint);
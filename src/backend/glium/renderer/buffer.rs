@@ -3,28 +3,33 @@ use rmu::raw::{Vec4f,Mat4f};
 use glium::vertex::VertexBuffer;
 use glium::index::IndexBuffer;
 use glium::texture::texture2d::Texture2d;
+use glium::texture::cubemap::Cubemap;
 use glium::texture::depth_texture2d::DepthTexture2d;
-use std::collections::HashMap;
-use crate::base::{Vertex, material::Material, camera::Camera};
-use crate::renderer::Light;
-use glium::Display;
+use glium::texture::depth_texture2d_array::DepthTexture2dArray;
+use glium::texture::depth_cubemap_array::DepthCubemapArray;
+use std::collections::{HashMap, BTreeMap};
+use crate::base::{Vertex, SkinnedVertex, material::Material, camera::Camera};
+use crate::renderer::{Light, Instance, ShadowData};
+use super::display::GLDisplay;
 use std::rc::Rc;
 
 pub struct DataBuffer {
     pub scene_buffer    : Rc<SceneBuffer>,
     pub light_buffer  : Rc<LightBuffer>,
     pub texture_buffer: HashMap<String,Texture2d>,
+    pub cubemap_buffer: HashMap<String,Cubemap>,
     pub depth_texture : Option<DepthTexture2d>,
     pub camera        : Camera,
     pub bg_color      : Vec4f,
 }
 
 impl DataBuffer {
-    pub fn new(display: &Display) -> Self {
+    pub fn new(display: &GLDisplay) -> Self {
         Self {
             scene_buffer: Rc::new(Default::default()),
             light_buffer: Rc::new(LightBuffer::new(display)),
             texture_buffer: HashMap::new(),
+            cubemap_buffer: HashMap::new(),
             depth_texture: None,
             camera: Default::default(),
             bg_color: [1.0,1.0,1.0,1.0],
@@ -35,25 +40,40 @@ impl DataBuffer {
 use glium::buffer::*;
 use glium::implement_uniform_block;
 
-implement_uniform_block!(Light,color_flux,position,direction_type,cut_off,outer_cut_off,linear,quadratic);
+implement_uniform_block!(Light,color_intensity,position_range,direction_type,inner_angle,outer_angle,unit,padding);
+implement_uniform_block!(ShadowData,light_space_0,light_space_1,light_space_2,light_space_3,layer_count_bias,cube_far);
 
 pub struct LightBuffer {
     pub lights: HashMap<String,Light>,
-    pub shadow_maps: HashMap<String,DepthTexture2d>,
-    pub shadow_map_size: u32,
+    /// layers of spot lights and cascades of parallel lights, drawn by `shadow_map`
+    pub shadow_maps: DepthTexture2dArray,
+    /// one cube map for each point light
+    pub point_shadow_maps: DepthCubemapArray,
+    /// shadow data in the order of lights in uniform buffer
+    pub shadow_buffer: Buffer<[ShadowData]>,
     pub buffer: Buffer<[Light]>,
 }
 
 impl LightBuffer {
-    pub fn new(display: &Display) -> Self {
+    pub fn new(display: &GLDisplay) -> Self {
         Self {
             lights: HashMap::new(),
-            shadow_maps: HashMap::new(),
-            shadow_map_size: 512,
+            shadow_maps: DepthTexture2dArray::empty(display, 1, 1, 1).unwrap(),
+            point_shadow_maps: DepthCubemapArray::empty(display, 1, 1).unwrap(),
+            shadow_buffer: Buffer::new(display, vec![ShadowData::none()].as_slice(), BufferType::UniformBuffer, BufferMode::default()).unwrap(),
             buffer: Buffer::new(display, vec![Light::new()].as_slice(), BufferType::UniformBuffer, BufferMode::default()).unwrap(),
         }
     }
 
+    /// upload lights and clear shadows until the next shadow map
+    pub fn rebuild(&mut self, display: &GLDisplay) {
+        let lights: Vec<Light> = self.sorted_lights();
+        self.buffer = Buffer::new(display, lights.as_slice(), BufferType::UniformBuffer, BufferMode::default()).unwrap();
+
+        let shadows = vec![ShadowData::none(); lights.len().max(1)];
+        self.shadow_buffer = Buffer::new(display, shadows.as_slice(), BufferType::UniformBuffer, BufferMode::default()).unwrap();
+    }
+
     pub fn unifrom_buffer(&self) -> &Buffer<[Light]> {
         &self.buffer
     }
@@ -61,12 +81,31 @@ impl LightBuffer {
     pub fn light_number(&self) -> usize{
         self.lights.len()
     }
+
+    /// lights ordered by name, the order of lights in uniform buffer
+    pub fn sorted_lights(&self) -> Vec<Light> {
+        let mut lights: Vec<(&String,&Light)> = self.lights.iter().collect();
+        lights.sort_by(|a, b| a.0.cmp(b.0));
+        lights.into_iter().map(|(_, light)| *light).collect()
+    }
 }
 
 pub struct SceneBuffer {
     pub objects: HashMap<String,RenderObject>,
     pub meshes: HashMap<String,RenderMesh>,
+    pub skinned_meshes: HashMap<String,RenderSkinnedMesh>,
+    /// joint matrices uniform block of objects
+    pub skins: HashMap<String,Buffer<[Mat4f]>>,
+    /// instances of objects from `update_instances`
+    pub instances: HashMap<String,VertexBuffer<InstanceAttribute>>,
+    /// objects with same mesh and material drawn by one call, the key is material name
+    pub batches: HashMap<String,Vec<InstanceBatch>>,
+    pub batched_objects: HashMap<String,BatchSlot>,
+    /// batches need rebuild before rendering
+    pub batches_dirty: bool,
     pub materials: HashMap<String,Material>,
+    /// parent of material instances, batches are grouped by parent
+    pub material_parents: HashMap<String,String>,
     pub same_material_objects: HashMap<String,HashMap<String,()>>,
 }
 
@@ -75,12 +114,138 @@ impl Default for SceneBuffer {
         Self {
             objects: HashMap::new(),
             meshes: HashMap::new(),
+            skinned_meshes: HashMap::new(),
+            skins: HashMap::new(),
+            instances: HashMap::new(),
+            batches: HashMap::new(),
+            batched_objects: HashMap::new(),
+            batches_dirty: false,
             materials: HashMap::new(),
+            material_parents: HashMap::new(),
             same_material_objects: HashMap::new(),
         }
     }
 }
 
+impl SceneBuffer {
+    pub fn add_same_material_object(&mut self, material_name: &String, object_name: &String) {
+        if let Some(same_material_objects) = self.same_material_objects.get_mut(material_name) {
+            same_material_objects.insert(object_name.clone(),());
+        } else {
+            let mut same_material_objects = HashMap::new();
+            same_material_objects.insert(object_name.clone(),());
+            self.same_material_objects.insert(material_name.clone(),same_material_objects);
+        }
+    }
+
+    /// group objects with same mesh and material, instances of a parent equal to a material of the parent
+    /// are grouped with it and drawn with the first of them by name. skinned meshes and objects with
+    /// instances are not grouped
+    pub fn build_batches(&mut self, display: &GLDisplay) {
+        self.batches.clear();
+        self.batched_objects.clear();
+
+        let objects = &self.objects;
+        let materials = &self.materials;
+
+        /* transparent objects are sorted one by one */
+        let mut same_parent_materials: BTreeMap<&String,Vec<&String>> = BTreeMap::new();
+        for material_name in self.same_material_objects.keys() {
+            match materials.get(material_name) {
+                Some(material) if !material.is_transparent() => (),
+                _ => continue,
+            }
+
+            let parent_name = self.material_parents.get(material_name).unwrap_or(material_name);
+            same_parent_materials.entry(parent_name).or_insert(Vec::new()).push(material_name);
+        }
+
+        for material_names in same_parent_materials.values_mut() {
+            material_names.sort();
+
+            let mut same_mesh_objects: BTreeMap<(&String,&String),Vec<&String>> = BTreeMap::new();
+
+            for (i, material_name) in material_names.iter().enumerate() {
+                let material = &materials[*material_name];
+                let drawn_with = material_names[..i]
+                    .iter()
+                    .find(|x| materials[**x] == *material)
+                    .unwrap_or(material_name);
+
+                for object_name in self.same_material_objects[*material_name].keys() {
+                    if self.instances.contains_key(object_name) {
+                        continue;
+                    }
+
+                    if let Some(object) = objects.get(object_name) {
+                        if self.meshes.contains_key(&object.mesh_name) {
+                            same_mesh_objects.entry((*drawn_with, &object.mesh_name)).or_insert(Vec::new()).push(object_name);
+                        }
+                    }
+                }
+            }
+
+            for ((material_name, mesh_name), mut object_names) in same_mesh_objects.into_iter() {
+                if object_names.len() < 2 {
+                    continue;
+                }
+
+                object_names.sort();
+
+                let instances: Vec<InstanceAttribute> = object_names
+                    .iter()
+                    .map(|x| InstanceAttribute::from(Instance::new(objects[*x].transform)))
+                    .collect();
+
+                let batches = self.batches.entry(material_name.clone()).or_insert(Vec::new());
+
+                for (slot, object_name) in object_names.into_iter().enumerate() {
+                    self.batched_objects.insert(object_name.clone(), BatchSlot {
+                        material_name: material_name.clone(),
+                        batch: batches.len(),
+                        slot,
+                    });
+                }
+
+                batches.push(InstanceBatch {
+                    mesh_name: mesh_name.clone(),
+                    instance_buffer: VertexBuffer::dynamic(display, &instances).unwrap(),
+                });
+            }
+        }
+
+        self.batches_dirty = false;
+    }
+
+    /// write the transform of a batched object to its instance slot, batches to rebuild are not written
+    pub fn update_batched_transform(&self, object_name: &String) {
+        if self.batches_dirty {
+            return;
+        }
+
+        if let (Some(batch_slot), Some(object)) = (self.batched_objects.get(object_name), self.objects.get(object_name)) {
+            let slot = batch_slot.slot;
+            let slice = self.batches
+                .get(&batch_slot.material_name)
+                .and_then(|x| x.get(batch_slot.batch))
+                .and_then(|x| x.instance_buffer.slice(slot..slot + 1));
+
+            if let Some(slice) = slice {
+                slice.write(&[InstanceAttribute::from(Instance::new(object.transform))]);
+            }
+        }
+    }
+
+    pub fn remove_same_material_object(&mut self, material_name: &String, object_name: &String) {
+        if let Some(same_material_objects) = self.same_material_objects.get_mut(material_name) {
+            same_material_objects.remove(object_name);
+            if same_material_objects.is_empty() {
+                self.same_material_objects.remove(material_name);
+            }
+        }
+    }
+}
+
 pub struct RenderObject {
     pub mesh_name: String,
     pub material_name: String,
@@ -111,4 +276,48 @@ impl RenderMesh {
             index_buffer,
         }
     }
+}
+
+pub struct RenderSkinnedMesh {
+    pub vertex_buffer: VertexBuffer<SkinnedVertex>,
+    pub index_buffer: IndexBuffer<u32>,
+}
+
+impl RenderSkinnedMesh {
+    #[inline]
+    pub fn new(vertex_buffer: VertexBuffer<SkinnedVertex>, index_buffer: IndexBuffer<u32>) -> Self {
+        Self {
+            vertex_buffer,
+            index_buffer,
+        }
+    }
+}
+
+/// per instance vertex attributes of instanced draw call
+#[derive(Copy,Clone)]
+pub struct InstanceAttribute {
+    pub instance_transform: Mat4f,
+    pub instance_tint: Vec4f,
+}
+
+impl From<Instance> for InstanceAttribute {
+    fn from(instance: Instance) -> Self {
+        Self {
+            instance_transform: instance.transform,
+            instance_tint: instance.tint,
+        }
+    }
+}
+
+pub struct InstanceBatch {
+    pub mesh_name: String,
+    pub instance_buffer: VertexBuffer<InstanceAttribute>,
+}
+
+/// the batch of a batched object and its instance slot
+pub struct BatchSlot {
+    /// material the batch is drawn with
+    pub material_name: String,
+    pub batch: usize,
+    pub slot: usize,
 }
\ No newline at end of file
@@ -0,0 +1,255 @@
+use super::renderer::GLRenderer;
+use super::display::GLDisplay;
+use super::pipeline::*;
+use super::render_pass::draw_objects;
+use crate::renderer::{RenderPassRenderer, RenderPhase, pipeline::*};
+use crate::renderer::pipeline::shader::glsl::*;
+use glium::program::Program;
+use glium::{draw_parameters::*, Surface};
+use glium::framebuffer::MultiOutputFrameBuffer;
+use glium::texture::{Texture2d, DepthTexture2d, DepthFormat, MipmapsOption};
+use std::rc::Rc;
+
+/// targets of the g-buffer pass, they are in the texture buffer by these names
+pub const GBUFFER_TARGETS: [(&str, OutputFormat); 4] = [
+    ("g_albedo", OutputFormat::Vec4U8),
+    ("g_normal", OutputFormat::Vec4F16),
+    ("g_material", OutputFormat::Vec4U8),
+    ("g_depth", OutputFormat::F32),
+];
+
+/// the depth target is cleared to the far plane, the lighting and occlusion passes skip texels
+/// with depth 1.0 as background, other targets are cleared to zero
+pub fn gbuffer_clear_color(name: &str) -> (f32, f32, f32, f32) {
+    match name {
+        "g_depth" => (1.0, 1.0, 1.0, 1.0),
+        _ => (0.0, 0.0, 0.0, 0.0),
+    }
+}
+
+#[derive(Debug,Copy,Clone,PartialEq)]
+pub enum RenderPath {
+    Forward,
+    /// opaque bulidin materials write the g-buffer and are lit by one full screen pass,
+    /// alpha test and other opaque materials are drawn forward after the lighting
+    Deferred,
+}
+
+impl Default for RenderPath {
+    fn default() -> Self {
+        RenderPath::Forward
+    }
+}
+
+pub struct DeferredData {
+    pub gbuffer_shaders: Rc<GLShaderBuffer>,
+    pub lighting: Program,
+    pub depth_texture: Option<DepthTexture2d>,
+}
+
+impl DeferredData {
+    pub fn new(display: &GLDisplay) -> Self {
+        let mut gbuffer_shaders = GLShaderBuffer::new();
+        gbuffer_shaders.load_gbuffer(display);
+
+        let version = glsl_version(4, 60);
+        let vertex_shader = glsl(version.clone(), vert_lib(), deferred_vert());
+        let fragment_shader = glsl(version, light_storage_lib(), deferred_lighting());
+
+        let lighting = Program::from_source(
+            display, vertex_shader.as_str(), fragment_shader.as_str(), None
+        ).unwrap();
+
+        Self {
+            gbuffer_shaders: Rc::new(gbuffer_shaders),
+            lighting,
+            depth_texture: None,
+        }
+    }
+}
+
+impl GLRenderer {
+    /// the deferred programs are compiled on first use
+    pub fn set_render_path(&mut self, render_path: RenderPath) {
+        if render_path == RenderPath::Deferred && self.deferred_data.is_none() {
+            self.deferred_data = Some(DeferredData::new(&self.display));
+        }
+        self.render_path = render_path;
+    }
+
+    pub fn render_path(&self) -> RenderPath {
+        self.render_path
+    }
+
+    /* g-buffer textures are allocated again when the frame is resized */
+    fn allocate_gbuffer(&mut self, dimensions: (u32,u32)) {
+        let (w, h) = dimensions;
+
+        for (name, format) in GBUFFER_TARGETS.iter() {
+            match self.data_buffer.texture_buffer.get(*name) {
+                Some(tex) if tex.dimensions() == dimensions => continue,
+                _ => (),
+            }
+
+            let tex = Texture2d::empty_with_format(
+                &self.display,
+                From::from(*format),
+                MipmapsOption::NoMipmap,
+                w,
+                h
+            ).unwrap();
+            self.data_buffer.texture_buffer.insert(name.to_string(), tex);
+        }
+
+        let deferred_data = self.deferred_data.as_mut().unwrap();
+
+        match &deferred_data.depth_texture {
+            Some(depth) if depth.dimensions() == dimensions => (),
+            _ => {
+                let depth = DepthTexture2d::empty_with_format(
+                    &self.display,
+                    DepthFormat::I24,
+                    MipmapsOption::NoMipmap,
+                    w,
+                    h
+                ).unwrap();
+                deferred_data.depth_texture = Some(depth);
+            },
+        }
+    }
+
+    /* objects of bulidin materials write the g-buffer in the queue order, alpha test objects discard
+       against the cutoff of their material. it is also the depth and normal pre-pass of ambient occlusion */
+    pub(super) fn render_gbuffer(&mut self, uniform_data: &SceneUniformData, material_names: &[String]) {
+        if self.deferred_data.is_none() {
+            self.deferred_data = Some(DeferredData::new(&self.display));
+        }
+
+        let dimensions = self.display.get_framebuffer_dimensions();
+        self.allocate_gbuffer(dimensions);
+
+        let scene_data = self.data_buffer.scene_buffer.clone();
+        let shader_buffer = self.shader_buffer.clone();
+        let gbuffer_shaders = self.deferred_data.as_ref().unwrap().gbuffer_shaders.clone();
+
+        /* the frame buffer of multiple outputs clears every target to one color */
+        for (name, _) in GBUFFER_TARGETS.iter() {
+            let (r, g, b, a) = gbuffer_clear_color(name);
+            self.data_buffer.texture_buffer[*name].as_surface().clear_color(r, g, b, a);
+        }
+
+        let output: Vec<(&str,&Texture2d)> = GBUFFER_TARGETS
+            .iter()
+            .map(|(name, _)| (*name, &self.data_buffer.texture_buffer[*name]))
+            .collect();
+        let depth = self.deferred_data.as_ref().unwrap().depth_texture.as_ref().unwrap();
+
+        let mut frame = MultiOutputFrameBuffer::with_depth_buffer(&self.display, output, depth).unwrap();
+        frame.clear_depth(1.0);
+
+        let mut parameters = DrawParameters {
+            depth: Depth {
+                test: DepthTest::IfLessOrEqual,
+                write: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        for material_name in material_names.iter() {
+            if let Some(material) = scene_data.materials.get(material_name) {
+                if let (Some(render_pass), Some(forward_pass)) = (gbuffer_shaders.shader(&material.name), shader_buffer.shader(&material.name)) {
+                    parameters.load(&forward_pass.pass_option);
+                    draw_objects(&mut frame, &self.data_buffer, &gbuffer_shaders, &self.render_queue, material_name, uniform_data, render_pass, &parameters);
+                }
+            }
+        }
+    }
+
+    /* the lighting pass writes the scene depth to the frame so that forward objects are depth tested */
+    pub(super) fn render_deferred(&mut self, uniform_data: &mut SceneUniformData) {
+        let scene_data = self.data_buffer.scene_buffer.clone();
+        let shader_buffer = self.shader_buffer.clone();
+
+        let materials: Vec<(RenderPhase,String)> = self.render_queue
+            .materials()
+            .into_iter()
+            .map(|(phase, name)| (phase, name.clone()))
+            .collect();
+
+        /* alpha test materials are drawn forward after the lighting with the discard of their own shader,
+           the g-buffer of the lighting pass only holds opaque materials */
+        let opaque_names: Vec<String> = materials
+            .iter()
+            .filter(|(phase, _)| *phase == RenderPhase::Opaque)
+            .map(|(_, name)| name.clone())
+            .collect();
+
+        self.render_gbuffer(uniform_data, &opaque_names);
+        self.render_ssao(uniform_data);
+
+        let gbuffer_shaders = self.deferred_data.as_ref().unwrap().gbuffer_shaders.clone();
+
+        scene_frame!(self, frame => {
+            uniform_data.multiple_render_target = GBUFFER_TARGETS.iter().map(|(name, _)| name.to_string()).collect();
+
+            let uniforms = SceneUniform::new(
+                uniform_data,
+                Default::default(),
+                Vec::new(),
+                true,
+                &self.data_buffer.texture_buffer,
+                &self.data_buffer.cubemap_buffer
+            );
+
+            let parameters = DrawParameters {
+                depth: Depth {
+                    test: DepthTest::Overwrite,
+                    write: true,
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+
+            frame.draw(
+                &self.screen_data.vertex_buffer,
+                &self.screen_data.index_buffer,
+                &self.deferred_data.as_ref().unwrap().lighting,
+                &uniforms,
+                &parameters
+            ).unwrap();
+
+            uniform_data.multiple_render_target = Vec::new();
+        });
+
+        /* alpha test materials and materials without g-buffer program */
+        for (phase, material_name) in materials.iter() {
+            if let Some(material) = scene_data.materials.get(material_name) {
+                if *phase == RenderPhase::Opaque && gbuffer_shaders.shader(&material.name).is_some() {
+                    continue;
+                }
+
+                if let Some(render_pass) = shader_buffer.shader(&material.name) {
+                    self.render_pass(material_name, uniform_data, render_pass);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn background_keeps_clear_color() {
+        /* the lighting pass discards the background and the occlusion pass leaves it unoccluded */
+        assert_eq!(gbuffer_clear_color("g_depth"), (1.0, 1.0, 1.0, 1.0));
+        assert!(deferred_lighting().contains("if (depth >= 1.0) {\n        discard;"));
+        assert!(ssao("SSAO", 64).contains("if (depth >= 1.0) {\n        occlusion_out = 1.0;"));
+
+        for (name, _) in GBUFFER_TARGETS.iter().filter(|(name, _)| *name != "g_depth") {
+            assert_eq!(gbuffer_clear_color(name), (0.0, 0.0, 0.0, 0.0));
+        }
+    }
+}
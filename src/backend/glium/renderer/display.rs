@@ -0,0 +1,106 @@
+use glium::{Display, Frame, HeadlessRenderer};
+use glium::backend::{Context, Facade};
+use glium::glutin::{self, ContextBuilder, NotCurrent, dpi::PhysicalSize};
+use std::ops::Deref;
+use std::rc::Rc;
+
+/// # GL Display
+/// window display or headless context for rendering without window,
+/// both are glium facade and draw to their default framebuffer
+pub enum GLDisplay {
+    Window(Display),
+    Headless(HeadlessRenderer),
+}
+
+#[derive(Debug)]
+pub enum HeadlessErr {
+    CreationErr(glutin::CreationError),
+    IncompatibleOpenGl(glium::IncompatibleOpenGl),
+}
+
+impl std::fmt::Display for HeadlessErr {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            HeadlessErr::CreationErr(err) => write!(f, "can't create headless context: {}", err),
+            HeadlessErr::IncompatibleOpenGl(err) => write!(f, "incompatible opengl: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for HeadlessErr {}
+
+impl GLDisplay {
+    /// headless context with a default framebuffer of `width` x `height`,
+    /// on linux it use OSMesa, e.g. Mesa llvmpipe in CI
+    pub fn headless(width: u32, height: u32) -> Result<Self, HeadlessErr> {
+        let size = PhysicalSize::new(width, height);
+        let context = headless_context(ContextBuilder::new().with_depth_buffer(24), size)
+            .map_err(|err| HeadlessErr::CreationErr(err))?;
+
+        Self::from_headless_context(context)
+    }
+
+    pub fn from_headless_context(context: glutin::Context<NotCurrent>) -> Result<Self, HeadlessErr> {
+        let renderer = HeadlessRenderer::new(context)
+            .map_err(|err| HeadlessErr::IncompatibleOpenGl(err))?;
+
+        Ok(GLDisplay::Headless(renderer))
+    }
+
+    pub fn draw(&self) -> Frame {
+        match self {
+            GLDisplay::Window(display) => display.draw(),
+            GLDisplay::Headless(renderer) => renderer.draw(),
+        }
+    }
+
+    pub fn is_headless(&self) -> bool {
+        match self {
+            GLDisplay::Window(_) => false,
+            GLDisplay::Headless(_) => true,
+        }
+    }
+
+    /// scale factor of window, 1.0 for headless
+    pub fn scale_factor(&self) -> f64 {
+        match self {
+            GLDisplay::Window(display) => display.gl_window().window().scale_factor(),
+            GLDisplay::Headless(_) => 1.0,
+        }
+    }
+}
+
+#[cfg(all(unix, not(target_os = "macos"), not(target_os = "ios")))]
+fn headless_context(context_builder: ContextBuilder<NotCurrent>, size: PhysicalSize<u32>) -> Result<glutin::Context<NotCurrent>, glutin::CreationError> {
+    use glium::glutin::platform::unix::HeadlessContextExt;
+    context_builder.build_osmesa(size)
+}
+
+#[cfg(not(all(unix, not(target_os = "macos"), not(target_os = "ios"))))]
+fn headless_context(context_builder: ContextBuilder<NotCurrent>, size: PhysicalSize<u32>) -> Result<glutin::Context<NotCurrent>, glutin::CreationError> {
+    let event_loop = glutin::event_loop::EventLoop::new();
+    context_builder.build_headless(&event_loop, size)
+}
+
+impl Facade for GLDisplay {
+    fn get_context(&self) -> &Rc<Context> {
+        match self {
+            GLDisplay::Window(display) => display.get_context(),
+            GLDisplay::Headless(renderer) => renderer.get_context(),
+        }
+    }
+}
+
+impl Deref for GLDisplay {
+    type Target = Context;
+
+    fn deref(&self) -> &Context {
+        self.get_context()
+    }
+}
+
+impl From<Display> for GLDisplay {
+    fn from(display: Display) -> Self {
+        GLDisplay::Window(display)
+    }
+}
@@ -0,0 +1,261 @@
+use super::renderer::GLRenderer;
+use super::display::GLDisplay;
+use super::screen_data::ScreenData;
+use super::pipeline::uniforms::SceneEnvironment;
+use crate::renderer::{Image, ImageType};
+use crate::renderer::pipeline::shader::glsl::*;
+use crate::base::transform::{inverse, IDENTITY};
+use glium::program::Program;
+use glium::{draw_parameters::*, Surface};
+use glium::framebuffer::SimpleFrameBuffer;
+use glium::texture::{Texture2d, Cubemap, CubeLayer, RawImage2d, ClientFormat, UncompressedFloatFormat, MipmapsOption};
+use glium::uniforms::{Sampler, Uniforms, SamplerWrapFunction, MagnifySamplerFilter, MinifySamplerFilter};
+use rmu::raw::{Vec3f, Mat4f};
+use rmu::matrix::Matrix4x4;
+use std::rc::Rc;
+
+pub const ENVIRONMENT_SIZE: u32 = 512;
+pub const IRRADIANCE_SIZE: u32 = 32;
+pub const PREFILTERED_SIZE: u32 = 128;
+/// mipmap levels of the prefiltered map, roughness from 0.0 to 1.0
+pub const PREFILTERED_LEVELS: u32 = 5;
+pub const BRDF_LUT_SIZE: u32 = 512;
+
+/* forward, right and up of the faces in the orientation cube maps are sampled by direction */
+const CUBE_FACES: [(CubeLayer, Vec3f, Vec3f, Vec3f); 6] = [
+    (CubeLayer::PositiveX, [ 1.0, 0.0, 0.0], [ 0.0, 0.0,-1.0], [0.0,-1.0, 0.0]),
+    (CubeLayer::NegativeX, [-1.0, 0.0, 0.0], [ 0.0, 0.0, 1.0], [0.0,-1.0, 0.0]),
+    (CubeLayer::PositiveY, [ 0.0, 1.0, 0.0], [ 1.0, 0.0, 0.0], [0.0, 0.0, 1.0]),
+    (CubeLayer::NegativeY, [ 0.0,-1.0, 0.0], [ 1.0, 0.0, 0.0], [0.0, 0.0,-1.0]),
+    (CubeLayer::PositiveZ, [ 0.0, 0.0, 1.0], [ 1.0, 0.0, 0.0], [0.0,-1.0, 0.0]),
+    (CubeLayer::NegativeZ, [ 0.0, 0.0,-1.0], [-1.0, 0.0, 0.0], [0.0,-1.0, 0.0]),
+];
+
+/// # Environment Data
+/// cube map of the environment and the maps of image based lighting computed from it,
+/// the maps are 1 x 1 and disabled until an environment is set
+pub struct EnvironmentData {
+    pub enable: bool,
+    pub environment: Cubemap,
+    /// cosine weighted irradiance by normal
+    pub irradiance: Cubemap,
+    /// radiance filtered by ggx, roughness increases with the mipmap level
+    pub prefiltered: Cubemap,
+    /// scale and bias of fresnel by view angle and roughness
+    pub brdf_lut: Texture2d,
+    /// created with the first environment
+    pub skybox: Option<Program>,
+}
+
+impl EnvironmentData {
+    pub fn new(display: &GLDisplay) -> Self {
+        Self {
+            enable: false,
+            environment: cubemap(display, 1, MipmapsOption::NoMipmap),
+            irradiance: cubemap(display, 1, MipmapsOption::NoMipmap),
+            prefiltered: cubemap(display, 1, MipmapsOption::NoMipmap),
+            brdf_lut: Texture2d::empty_with_format(
+                display,
+                UncompressedFloatFormat::F16F16,
+                MipmapsOption::NoMipmap,
+                1,
+                1
+            ).unwrap(),
+            skybox: None,
+        }
+    }
+
+    pub fn uniforms(&self) -> SceneEnvironment<'_> {
+        SceneEnvironment {
+            enable: self.enable,
+            irradiance_map: cube_sampler(&self.irradiance),
+            prefiltered_map: cube_sampler(&self.prefiltered)
+                .minify_filter(MinifySamplerFilter::LinearMipmapLinear),
+            brdf_lut: Sampler::new(&self.brdf_lut)
+                .wrap_function(SamplerWrapFunction::Clamp)
+                .magnify_filter(MagnifySamplerFilter::Linear)
+                .minify_filter(MinifySamplerFilter::Linear),
+            prefiltered_lod: (PREFILTERED_LEVELS - 1) as f32,
+        }
+    }
+}
+
+impl GLRenderer {
+    /* the maps are computed once by full screen passes for each cube face */
+    pub(super) fn load_environment(&mut self, image: &Image) {
+        let display = &self.display;
+        let screen_data = &self.screen_data;
+
+        let version = glsl_version(4, 60);
+        let vertex_shader = glsl(version.clone(), String::new(), environment_vert());
+        let program = |pass: &str| {
+            let fragment_shader = glsl(version.clone(), String::new(), environment(pass));
+            Program::from_source(display, vertex_shader.as_str(), fragment_shader.as_str(), None).unwrap()
+        };
+
+        use glium::uniform;
+
+        let equirectangular = equirectangular_texture(display, image, self.gamma);
+        let environment = cubemap(display, ENVIRONMENT_SIZE, MipmapsOption::NoMipmap);
+        let equirectangular_program = program("EQUIRECTANGULAR");
+
+        for (layer, forward, right, up) in CUBE_FACES.iter() {
+            let mut frame = SimpleFrameBuffer::new(display, environment.main_level().image(*layer)).unwrap();
+            draw_screen(&mut frame, screen_data, &equirectangular_program, &uniform! {
+                face_forward: *forward,
+                face_right: *right,
+                face_up: *up,
+                equirectangular: Sampler::new(&equirectangular)
+                    .magnify_filter(MagnifySamplerFilter::Linear)
+                    .minify_filter(MinifySamplerFilter::Linear),
+            });
+        }
+
+        let irradiance = cubemap(display, IRRADIANCE_SIZE, MipmapsOption::NoMipmap);
+        let irradiance_program = program("IRRADIANCE");
+
+        for (layer, forward, right, up) in CUBE_FACES.iter() {
+            let mut frame = SimpleFrameBuffer::new(display, irradiance.main_level().image(*layer)).unwrap();
+            draw_screen(&mut frame, screen_data, &irradiance_program, &uniform! {
+                face_forward: *forward,
+                face_right: *right,
+                face_up: *up,
+                environment: cube_sampler(&environment),
+            });
+        }
+
+        let prefiltered = cubemap(display, PREFILTERED_SIZE, MipmapsOption::EmptyMipmapsMax(PREFILTERED_LEVELS - 1));
+        let prefilter_program = program("PREFILTER");
+
+        for level in 0..PREFILTERED_LEVELS {
+            let roughness = level as f32 / (PREFILTERED_LEVELS - 1) as f32;
+            let mipmap = match prefiltered.mipmap(level) {
+                Some(mipmap) => mipmap,
+                None => break,
+            };
+
+            for (layer, forward, right, up) in CUBE_FACES.iter() {
+                let mut frame = SimpleFrameBuffer::new(display, mipmap.image(*layer)).unwrap();
+                draw_screen(&mut frame, screen_data, &prefilter_program, &uniform! {
+                    face_forward: *forward,
+                    face_right: *right,
+                    face_up: *up,
+                    environment: cube_sampler(&environment),
+                    roughness: roughness,
+                });
+            }
+        }
+
+        let brdf_lut = Texture2d::empty_with_format(
+            display,
+            UncompressedFloatFormat::F16F16,
+            MipmapsOption::NoMipmap,
+            BRDF_LUT_SIZE,
+            BRDF_LUT_SIZE
+        ).unwrap();
+        draw_screen(&mut brdf_lut.as_surface(), screen_data, &program("BRDF_LUT"), &glium::uniforms::EmptyUniforms);
+
+        let skybox = Rc::get_mut(&mut self.environment_data)
+            .and_then(|environment_data| environment_data.skybox.take())
+            .unwrap_or_else(|| program("SKYBOX"));
+
+        self.environment_data = Rc::new(EnvironmentData {
+            enable: true,
+            environment,
+            irradiance,
+            prefiltered,
+            brdf_lut,
+            skybox: Some(skybox),
+        });
+    }
+
+    /* after opaque objects, only the background passes the depth test on the far plane */
+    pub(super) fn render_skybox(&mut self) {
+        let environment_data = self.environment_data.clone();
+        let skybox = match &environment_data.skybox {
+            Some(skybox) if environment_data.enable => skybox,
+            _ => return,
+        };
+
+        let camera = &self.data_buffer.camera;
+        let mut view = camera.view();
+        view[3][0] = 0.0;
+        view[3][1] = 0.0;
+        view[3][2] = 0.0;
+        let view_project: Mat4f = (Matrix4x4::from(camera.project()) * Matrix4x4::from(view)).into();
+
+        use glium::uniform;
+
+        let uniforms = uniform! {
+            inverse_view_project: inverse(view_project).unwrap_or(IDENTITY),
+            environment: cube_sampler(&environment_data.environment),
+            hdr_enable: self.hdr_enable && !self.post_process.has_tone_mapping(),
+            gamma: self.gamma,
+        };
+
+        let parameters = DrawParameters {
+            depth: Depth {
+                test: DepthTest::IfLessOrEqual,
+                write: false,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        scene_frame!(self, frame => {
+            frame.draw(
+                &self.screen_data.vertex_buffer,
+                &self.screen_data.index_buffer,
+                skybox,
+                &uniforms,
+                &parameters
+            ).unwrap();
+        });
+    }
+}
+
+fn cubemap(display: &GLDisplay, size: u32, mipmaps: MipmapsOption) -> Cubemap {
+    Cubemap::empty_with_format(display, UncompressedFloatFormat::F16F16F16F16, mipmaps, size).unwrap()
+}
+
+fn cube_sampler(cubemap: &Cubemap) -> Sampler<'_,Cubemap> {
+    Sampler::new(cubemap)
+        .wrap_function(SamplerWrapFunction::Clamp)
+        .magnify_filter(MagnifySamplerFilter::Linear)
+        .minify_filter(MinifySamplerFilter::Linear)
+}
+
+/* 8 bit images are converted to linear by the gamma of renderer */
+fn equirectangular_texture(display: &GLDisplay, image: &Image, gamma: f32) -> Texture2d {
+    use std::borrow::Cow;
+
+    let data: Vec<f32> = image.rgb_f32()
+        .iter()
+        .flat_map(|x| x.iter().map(|x| match image.image_type {
+            ImageType::F32F32F32 => *x,
+            _ => x.powf(gamma),
+        }).collect::<Vec<f32>>())
+        .collect();
+
+    Texture2d::with_format(
+        display,
+        RawImage2d {
+            data: Cow::Owned(data),
+            width: image.dimensions.0,
+            height: image.dimensions.1,
+            format: ClientFormat::F32F32F32,
+        },
+        UncompressedFloatFormat::F32F32F32,
+        MipmapsOption::NoMipmap,
+    ).unwrap()
+}
+
+fn draw_screen<S: Surface, U: Uniforms>(frame: &mut S, screen_data: &ScreenData, program: &Program, uniforms: &U) {
+    frame.draw(
+        &screen_data.vertex_buffer,
+        &screen_data.index_buffer,
+        program,
+        uniforms,
+        &DrawParameters::default()
+    ).unwrap();
+}
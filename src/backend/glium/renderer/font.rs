@@ -1,53 +1,18 @@
 use glium::texture::Texture2d;
 use rusttype::gpu_cache::Cache;
-use rusttype::{point, vector, Font, PositionedGlyph, Rect, Scale};
+use rusttype::{point, vector, Font, Rect, Scale};
+use crate::base::font::layout_paragraph;
 use crate::base::Position;
 use rmu::raw::Vec2f;
 
 use std::borrow::Cow;
 
-fn layout_paragraph<'a>(font: &'a Font, scale: Scale, width: u32, text: &str) -> Vec<PositionedGlyph<'a>> {
-    let mut result = Vec::new();
-
-    let v_metrics = font.v_metrics(scale);
-    let advance_height = v_metrics.ascent - v_metrics.descent + v_metrics.line_gap;
-    let mut caret = point(0.0, v_metrics.ascent);
-    let mut last_glyph_id = None;
-    for c in text.chars() {
-        if c.is_control() {
-            match c {
-                '\r' => {
-                    caret = point(0.0, caret.y + advance_height);
-                },
-                '\n' => {},
-                _ => {},
-            }
-            continue;
-        }
-        let base_glyph = font.glyph(c);
-        if let Some(id) = last_glyph_id.take() {
-            caret.x += font.pair_kerning(scale, id, base_glyph.id());
-        }
-        last_glyph_id = Some(base_glyph.id());
-        let mut glyph = base_glyph.scaled(scale).positioned(caret);
-        if let Some(bb) = glyph.pixel_bounding_box() {
-            if bb.max.x > width as i32 {
-                caret = point(0.0, caret.y + advance_height);
-                glyph.set_position(caret);
-                last_glyph_id = None;
-            }
-        }
-        caret.x += glyph.unpositioned().h_metrics().advance_width;
-        result.push(glyph);
-    }
-    result
-}
-
 use glium::{VertexBuffer, IndexBuffer};
+use super::display::GLDisplay;
 use crate::base::utils::Size;
 
-pub fn load_text (text: &str, size: Size, width: f32, position: Vec2f,font: &Font, display: &glium::Display) ->  (VertexBuffer<Position>,IndexBuffer<u32>,Texture2d) {
-    let scale = display.gl_window().window().scale_factor();
+pub fn load_text (text: &str, size: Size, width: f32, position: Vec2f,font: &Font, display: &GLDisplay) ->  (VertexBuffer<Position>,IndexBuffer<u32>,Texture2d) {
+    let scale = display.scale_factor();
     let (w, h) = ((512.0 * scale) as u32, (512.0 * scale) as u32);
     let mut cache = Cache::builder().dimensions(w, h).build();
 
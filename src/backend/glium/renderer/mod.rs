@@ -1,7 +1,33 @@
+/// draw the scene to the hdr target of post processing if it is used, else to the frame
+macro_rules! scene_frame {
+    ($renderer:ident, $frame:ident => $body:block) => {{
+        let scene_target = match &$renderer.post_process_data {
+            Some(post_process_data) if !$renderer.post_process.is_empty() => post_process_data.scene_target(),
+            _ => None,
+        };
+
+        match scene_target {
+            Some((color, depth)) => {
+                let mut target = glium::framebuffer::SimpleFrameBuffer::with_depth_buffer(&$renderer.display, color, depth).unwrap();
+                let $frame = &mut target;
+                $body
+            },
+            None => if let Some($frame) = &mut $renderer.frame $body,
+        }
+    }};
+}
+
 pub mod renderer;
+pub mod display;
 pub mod render_produre;
 pub mod renderer_manager;
 pub mod render_pass;
+pub mod render_graph;
+pub mod deferred;
+pub mod post_process;
+pub mod environment;
+pub mod ssao;
+pub mod shadow;
 pub mod pipeline;
 pub mod buffer;
 pub mod screen_data;
@@ -12,5 +38,7 @@ pub mod text_paint;
 pub mod font;
 
 pub use renderer::*;
+pub use display::*;
 pub use canvas::*;
-pub use render_pass::*;
\ No newline at end of file
+pub use render_pass::*;
+pub use deferred::{RenderPath, GBUFFER_TARGETS, gbuffer_clear_color};
\ No newline at end of file
@@ -1,46 +1,108 @@
 use glium::program::{Program, Binary};
-use glium::Display;
+use super::super::display::GLDisplay;
 
 use crate::renderer::pipeline::*;
 use crate::renderer::pipeline::shader::glsl::*;
 
+use crate::animation::JOINTS_MAX_NUMBER;
 use std::collections::HashMap;
 
 
 pub struct GLShaderBuffer {
     pub shaders: HashMap<String,RenderPass<Program>>,
+    /// programs of shaders with skinning vertex shader, for skinned meshes
+    pub skinned_shaders: HashMap<String,Program>,
+    /// programs of shaders with per instance transform and tint attributes
+    pub instanced_shaders: HashMap<String,Program>,
+    /// programs of shaders output weighted blended transparency targets
+    pub oit_shaders: HashMap<String,Program>,
 }
 
 impl GLShaderBuffer {
     pub fn new() -> Self {
         Self {
             shaders: HashMap::new(),
+            skinned_shaders: HashMap::new(),
+            instanced_shaders: HashMap::new(),
+            oit_shaders: HashMap::new(),
         }
     }
 
-    pub fn load_bulidin(&mut self,display: &Display) {
+    pub fn load_bulidin(&mut self,display: &GLDisplay) {
         let version = glsl_version(4, 60);
 
         let vertex_shader = glsl(version.clone(), vert_lib(), base_vert());
-        let pure_color_code = glsl(version.clone(), String::new(), pure_color());
-        let blinn_phong_brdf_code = glsl(version.clone(), light_lib(10), blinn_phong_brdf());
-        let cook_torrance_brdf_code = glsl(version.clone(), light_lib(10), cook_torrance_brdf());
-        
-        let blinn_phong_brdf = Program::from_source(
-            display, vertex_shader.as_str(), blinn_phong_brdf_code.as_str(), None
-        ).unwrap();
-
-        let cook_torrance_brdf = Program::from_source(
-            display, vertex_shader.as_str(), cook_torrance_brdf_code.as_str(), None
-        ).unwrap();
-        
-        let pure_color = Program::from_source(
-            display, vertex_shader.as_str(), pure_color_code.as_str(), None
-        ).unwrap();
-        
-        self.shaders.insert("Blinn Phong BRDF".into(), RenderPass::pass(blinn_phong_brdf, None));
-        self.shaders.insert("Cook Torrance BRDF".into(), RenderPass::pass(cook_torrance_brdf, None));
-        self.shaders.insert("Pure Color Material".into(), RenderPass::pass(pure_color, None));
+        let skinned_vertex_shader = glsl(version.clone(), skinned_vert_lib(JOINTS_MAX_NUMBER as u16), base_vert());
+        let instanced_vertex_shader = glsl(version.clone(), instanced_vert_lib(), base_vert());
+
+        let bulidin = [
+            ("Blinn Phong BRDF", light_lib(10), blinn_phong_brdf()),
+            ("Cook Torrance BRDF", light_lib(10), cook_torrance_brdf()),
+            ("Pure Color Material", String::new(), pure_color()),
+        ];
+
+        for (name, include, source_code) in bulidin.iter() {
+            let fragment_shader = glsl(version.clone(), format!("{}\n{}", include, frag_lib(false)), source_code.clone());
+            let oit_fragment_shader = glsl(version.clone(), format!("{}\n{}", include, frag_lib(true)), source_code.clone());
+
+            let program = Program::from_source(
+                display, vertex_shader.as_str(), fragment_shader.as_str(), None
+            ).unwrap();
+
+            let skinned_program = Program::from_source(
+                display, skinned_vertex_shader.as_str(), fragment_shader.as_str(), None
+            ).unwrap();
+
+            let instanced_program = Program::from_source(
+                display, instanced_vertex_shader.as_str(), fragment_shader.as_str(), None
+            ).unwrap();
+
+            let oit_program = Program::from_source(
+                display, vertex_shader.as_str(), oit_fragment_shader.as_str(), None
+            ).unwrap();
+
+            self.shaders.insert(name.to_string(), RenderPass::pass(program, None));
+            self.skinned_shaders.insert(name.to_string(), skinned_program);
+            self.instanced_shaders.insert(name.to_string(), instanced_program);
+            self.oit_shaders.insert(name.to_string(), oit_program);
+        }
+    }
+}
+
+impl GLShaderBuffer {
+    /// programs of bulidin materials writing the g-buffer, for deferred shading
+    pub fn load_gbuffer(&mut self, display: &GLDisplay) {
+        let version = glsl_version(4, 60);
+
+        let vertex_shader = glsl(version.clone(), vert_lib(), base_vert());
+        let skinned_vertex_shader = glsl(version.clone(), skinned_vert_lib(JOINTS_MAX_NUMBER as u16), base_vert());
+        let instanced_vertex_shader = glsl(version.clone(), instanced_vert_lib(), base_vert());
+
+        let bulidin = [
+            ("Blinn Phong BRDF", "BLINN_PHONG_BRDF"),
+            ("Cook Torrance BRDF", "COOK_TORRANCE_BRDF"),
+            ("Pure Color Material", "PURE_COLOR"),
+        ];
+
+        for (name, shading_model) in bulidin.iter() {
+            let fragment_shader = glsl(version.clone(), String::new(), gbuffer(shading_model));
+
+            let program = Program::from_source(
+                display, vertex_shader.as_str(), fragment_shader.as_str(), None
+            ).unwrap();
+
+            let skinned_program = Program::from_source(
+                display, skinned_vertex_shader.as_str(), fragment_shader.as_str(), None
+            ).unwrap();
+
+            let instanced_program = Program::from_source(
+                display, instanced_vertex_shader.as_str(), fragment_shader.as_str(), None
+            ).unwrap();
+
+            self.shaders.insert(name.to_string(), RenderPass::pass(program, None));
+            self.skinned_shaders.insert(name.to_string(), skinned_program);
+            self.instanced_shaders.insert(name.to_string(), instanced_program);
+        }
     }
 }
 
@@ -49,4 +111,18 @@ impl ShaderBuffer<Program> for GLShaderBuffer {
     fn shader(&self, shader_name: &String) -> Option<&RenderPass<Program>> {
         self.shaders.get(shader_name)
     }
+}
+
+impl GLShaderBuffer {
+    pub fn skinned_shader(&self, shader_name: &String) -> Option<&Program> {
+        self.skinned_shaders.get(shader_name)
+    }
+
+    pub fn instanced_shader(&self, shader_name: &String) -> Option<&Program> {
+        self.instanced_shaders.get(shader_name)
+    }
+
+    pub fn oit_shader(&self, shader_name: &String) -> Option<&Program> {
+        self.oit_shaders.get(shader_name)
+    }
 }
\ No newline at end of file
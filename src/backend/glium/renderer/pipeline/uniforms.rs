@@ -5,6 +5,7 @@ use glium::buffer::*;
 use glium::texture::*;
 use glium::implement_uniform_block;
 use crate::renderer::light::Light;
+use crate::renderer::shadow::{ShadowData, ShadowFilter};
 use crate::base::material::*;
 use std::collections::HashMap;
 
@@ -26,6 +27,36 @@ pub struct SceneUniformData<'a> {
     pub gamma: f32,
     pub render_target: Option<String>,
     pub multiple_render_target: Vec<String>,
+    pub shadows: Option<SceneShadows<'a>>,
+    pub environment: Option<SceneEnvironment<'a>>,
+    pub ssao: Option<SceneSsao<'a>>,
+}
+
+/// shadow data of lights and the shadow map array sampled with depth comparison
+pub struct SceneShadows<'a> {
+    pub buffer: &'a Buffer<[ShadowData]>,
+    pub shadow_map: Sampler<'a,DepthTexture2dArray>,
+    pub point_shadow_map: Sampler<'a,DepthCubemapArray>,
+    /// 0 is hard, 1 is pcf and 2 is poisson
+    pub filter: i32,
+    /// pcf radius or poisson spread in texels
+    pub filter_size: f32,
+    pub samples: i32,
+}
+
+/// image based lighting maps, see `EnvironmentData`
+pub struct SceneEnvironment<'a> {
+    pub enable: bool,
+    pub irradiance_map: Sampler<'a,Cubemap>,
+    pub prefiltered_map: Sampler<'a,Cubemap>,
+    pub brdf_lut: Sampler<'a,Texture2d>,
+    pub prefiltered_lod: f32,
+}
+
+/// blurred ambient occlusion of the frame, see `SsaoData`
+pub struct SceneSsao<'a> {
+    pub enable: bool,
+    pub ssao_map: Sampler<'a,Texture2d>,
 }
 
 impl<'a> SceneUniformData<'a> {
@@ -47,8 +78,38 @@ impl<'a> SceneUniformData<'a> {
             gamma,
             render_target: None,
             multiple_render_target: Vec::new(),
+            shadows: None,
+            environment: None,
+            ssao: None,
         }
     }
+
+    pub fn with_environment(mut self, environment: SceneEnvironment<'a>) -> Self {
+        self.environment = Some(environment);
+        self
+    }
+
+    pub fn with_ssao(mut self, ssao: SceneSsao<'a>) -> Self {
+        self.ssao = Some(ssao);
+        self
+    }
+
+    pub fn with_shadows(
+        mut self,
+        buffer    : &'a Buffer<[ShadowData]>,
+        shadow_map: Sampler<'a,DepthTexture2dArray>,
+        point_shadow_map: Sampler<'a,DepthCubemapArray>,
+        filter    : ShadowFilter
+    ) -> Self {
+        let (filter, filter_size, samples) = match filter {
+            ShadowFilter::Hard => (0, 0.0, 1),
+            ShadowFilter::Pcf { radius } => (1, radius as f32, ((2 * radius + 1) * (2 * radius + 1)) as i32),
+            ShadowFilter::Poisson { samples, spread } => (2, spread, samples.max(1).min(16) as i32),
+        };
+
+        self.shadows = Some(SceneShadows { buffer, shadow_map, point_shadow_map, filter, filter_size, samples });
+        self
+    }
 }
 
 /* Because of scene rendering is draw call by same material , material property 
@@ -58,6 +119,9 @@ pub struct SceneUniform<'a>{
     pub transform: Mat4f,
     pub material_property_mapped: Vec<(String,PropertyValueMapped<'a>)>,
     pub lighting: bool,
+    pub skin: Option<&'a Buffer<[Mat4f]>>,
+    pub opacity: f32,
+    pub alpha_cutoff: f32,
     pub render_target: Option<(String,&'a Texture2d)>,
     pub multiple_render_target: Vec<(String, &'a Texture2d)>
 }
@@ -69,21 +133,41 @@ impl<'a> SceneUniform<'a> {
         material_property: Vec<(String,PropertyValue)>, 
         lighting         : bool,
         texture_buffer   : &'a HashMap<String,Texture2d>,
+        cubemap_buffer   : &'a HashMap<String,Cubemap>,
     ) -> Self {
         let mut material_property_mapped = Vec::new();
 
-        for (name, value) in material_property.iter() {
+        for (name, value) in with_mip_biases(material_property).iter() {
             match value {
                 PropertyValue::Bool(value)  => material_property_mapped.push((name.clone(), PropertyValueMapped::Bool(*value))),
                 PropertyValue::Float(value) => material_property_mapped.push((name.clone(), PropertyValueMapped::Float(*value))),
                 PropertyValue::Vec2(value)  => material_property_mapped.push((name.clone(), PropertyValueMapped::Vec2(*value))),
                 PropertyValue::Vec3(value)  => material_property_mapped.push((name.clone(), PropertyValueMapped::Vec3(*value))),
                 PropertyValue::Vec4(value)  => material_property_mapped.push((name.clone(), PropertyValueMapped::Vec4(*value))),
+                PropertyValue::Int(value)   => material_property_mapped.push((name.clone(), PropertyValueMapped::Int(*value))),
+                PropertyValue::IVec2(value) => material_property_mapped.push((name.clone(), PropertyValueMapped::IVec2(*value))),
+                PropertyValue::IVec3(value) => material_property_mapped.push((name.clone(), PropertyValueMapped::IVec3(*value))),
+                PropertyValue::IVec4(value) => material_property_mapped.push((name.clone(), PropertyValueMapped::IVec4(*value))),
+                PropertyValue::Mat3(value)  => material_property_mapped.push((name.clone(), PropertyValueMapped::Mat3(*value))),
+                PropertyValue::Mat4(value)  => material_property_mapped.push((name.clone(), PropertyValueMapped::Mat4(*value))),
+                PropertyValue::FloatArray(value) => material_property_mapped.push((name.clone(), PropertyValueMapped::FloatArray(
+                    value.iter().enumerate().map(|(i, x)| (format!("{}[{}]", name, i), *x)).collect()
+                ))),
                 PropertyValue::Texture(value) => {
                     if let Some(tex) = texture_buffer.get(value) {
                         material_property_mapped.push((name.clone(), PropertyValueMapped::Texture(tex)));
                     }
                 },
+                PropertyValue::SampledTexture(value) => {
+                    if let Some(tex) = texture_buffer.get(&value.texture) {
+                        material_property_mapped.push((name.clone(), PropertyValueMapped::SampledTexture(sampler(tex, value))));
+                    }
+                },
+                PropertyValue::Cubemap(value) => {
+                    if let Some(cubemap) = cubemap_buffer.get(value) {
+                        material_property_mapped.push((name.clone(), PropertyValueMapped::Cubemap(cubemap)));
+                    }
+                },
             }
         }
 
@@ -110,16 +194,40 @@ impl<'a> SceneUniform<'a> {
             transform,
             material_property_mapped,
             lighting,
+            skin: None,
+            opacity: 1.0,
+            alpha_cutoff: 0.0,
             render_target,
             multiple_render_target,
         }
     }
+
+    pub fn with_blend_mode(mut self, blend_mode: BlendMode, opacity: f32) -> Self {
+        self.opacity = opacity;
+        self.alpha_cutoff = match blend_mode {
+            BlendMode::AlphaTest { cutoff } => cutoff,
+            _ => 0.0,
+        };
+        self
+    }
+
+    pub fn with_skin(mut self, skin: &'a Buffer<[Mat4f]>) -> Self {
+        self.skin = Some(skin);
+        self
+    }
 }
 
 impl<'n> Uniforms for SceneUniform<'n> {
     fn visit_values<'a, F: FnMut(&str, UniformValue<'a>)>(&'a self, mut output : F) {
         output("Camera",self.data.matrix.as_uniform_value());
         output("transform",self.transform.as_uniform_value());
+
+        output("opacity",self.opacity.as_uniform_value());
+        output("alpha_cutoff",self.alpha_cutoff.as_uniform_value());
+
+        if let Some(skin) = &self.skin {
+            output("Skin",skin.as_uniform_value());
+        }
         
         if self.lighting {
             output("view_position",self.data.view_position.as_uniform_value());
@@ -128,7 +236,27 @@ impl<'n> Uniforms for SceneUniform<'n> {
             output("Lights",self.data.lights.as_uniform_value());
             output("lights_count",self.data.lights_count.as_uniform_value());
 
-            /* issue: depth texture as uniform have some problem will make shadow mapping don't work*/
+            if let Some(shadows) = &self.data.shadows {
+                output("Shadows",shadows.buffer.as_uniform_value());
+                output("shadow_map",shadows.shadow_map.as_uniform_value());
+                output("point_shadow_map",shadows.point_shadow_map.as_uniform_value());
+                output("shadow_filter",shadows.filter.as_uniform_value());
+                output("shadow_filter_size",shadows.filter_size.as_uniform_value());
+                output("shadow_samples",shadows.samples.as_uniform_value());
+            }
+
+            if let Some(environment) = &self.data.environment {
+                output("environment_enable",environment.enable.as_uniform_value());
+                output("irradiance_map",environment.irradiance_map.as_uniform_value());
+                output("prefiltered_map",environment.prefiltered_map.as_uniform_value());
+                output("brdf_lut",environment.brdf_lut.as_uniform_value());
+                output("prefiltered_lod",environment.prefiltered_lod.as_uniform_value());
+            }
+
+            if let Some(ssao) = &self.data.ssao {
+                output("ssao_enable",ssao.enable.as_uniform_value());
+                output("ssao_map",ssao.ssao_map.as_uniform_value());
+            }
         }
 
         for (name, value) in self.material_property_mapped.iter() {
@@ -138,7 +266,20 @@ impl<'n> Uniforms for SceneUniform<'n> {
                 PropertyValueMapped::Vec2(value)  => output(name, value.as_uniform_value()),
                 PropertyValueMapped::Vec3(value)  => output(name, value.as_uniform_value()),
                 PropertyValueMapped::Vec4(value)  => output(name, value.as_uniform_value()),
+                PropertyValueMapped::Int(value)   => output(name, value.as_uniform_value()),
+                PropertyValueMapped::IVec2(value) => output(name, value.as_uniform_value()),
+                PropertyValueMapped::IVec3(value) => output(name, value.as_uniform_value()),
+                PropertyValueMapped::IVec4(value) => output(name, value.as_uniform_value()),
+                PropertyValueMapped::Mat3(value)  => output(name, value.as_uniform_value()),
+                PropertyValueMapped::Mat4(value)  => output(name, value.as_uniform_value()),
+                PropertyValueMapped::FloatArray(value) => {
+                    for (element, x) in value.iter() {
+                        output(element, x.as_uniform_value());
+                    }
+                },
                 PropertyValueMapped::Texture(value) => output(name, value.as_uniform_value()),
+                PropertyValueMapped::SampledTexture(value) => output(name, value.as_uniform_value()),
+                PropertyValueMapped::Cubemap(value) => output(name, value.as_uniform_value()),
             }
         }
 
@@ -158,5 +299,82 @@ pub enum PropertyValueMapped<'a> {
     Vec2(Vec2f),
     Vec3(Vec3f),
     Vec4(Vec4f),
+    Int(i32),
+    IVec2([i32;2]),
+    IVec3([i32;3]),
+    IVec4([i32;4]),
+    Mat3([[f32;3];3]),
+    Mat4(Mat4f),
+    /// elements with their uniform names `name[i]`
+    FloatArray(Vec<(String,f32)>),
     Texture(&'a Texture2d),
-}
\ No newline at end of file
+    /// texture with sampler state
+    SampledTexture(Sampler<'a,Texture2d>),
+    Cubemap(&'a Cubemap),
+}
+
+/* glium samplers have no lod bias, the mip bias of a sampled texture is set as the float `<name>_mip_bias` */
+fn with_mip_biases(material_property: Vec<(String,PropertyValue)>) -> Vec<(String,PropertyValue)> {
+    let mip_biases: Vec<(String,PropertyValue)> = material_property
+        .iter()
+        .filter_map(|(name, value)| match value {
+            PropertyValue::SampledTexture(value) => Some((format!("{}_mip_bias", name), PropertyValue::Float(value.mip_bias))),
+            _ => None,
+        })
+        .collect();
+
+    let mut material_property = material_property;
+    material_property.extend(mip_biases);
+    material_property
+}
+
+fn sampler<'a>(texture: &'a Texture2d, sampled: &SampledTexture) -> Sampler<'a,Texture2d> {
+    let wrap = match sampled.wrap {
+        WrapMode::Repeat => SamplerWrapFunction::Repeat,
+        WrapMode::MirroredRepeat => SamplerWrapFunction::Mirror,
+        WrapMode::Clamp => SamplerWrapFunction::Clamp,
+    };
+
+    let minify = match (sampled.min_filter, sampled.mipmap_filter) {
+        (FilterMode::Nearest, None) => MinifySamplerFilter::Nearest,
+        (FilterMode::Linear, None) => MinifySamplerFilter::Linear,
+        (FilterMode::Nearest, Some(FilterMode::Nearest)) => MinifySamplerFilter::NearestMipmapNearest,
+        (FilterMode::Nearest, Some(FilterMode::Linear)) => MinifySamplerFilter::NearestMipmapLinear,
+        (FilterMode::Linear, Some(FilterMode::Nearest)) => MinifySamplerFilter::LinearMipmapNearest,
+        (FilterMode::Linear, Some(FilterMode::Linear)) => MinifySamplerFilter::LinearMipmapLinear,
+    };
+
+    let magnify = match sampled.mag_filter {
+        FilterMode::Nearest => MagnifySamplerFilter::Nearest,
+        FilterMode::Linear => MagnifySamplerFilter::Linear,
+    };
+
+    texture
+        .sampled()
+        .wrap_function(wrap)
+        .minify_filter(minify)
+        .magnify_filter(magnify)
+        .anisotropy(sampled.anisotropy.max(1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mip_bias_uniform() {
+        let properties = vec![
+            ("material.color".to_string(), PropertyValue::Vec3([1.0, 1.0, 1.0])),
+            ("albedo_map".to_string(), PropertyValue::SampledTexture(SampledTexture::new("albedo").with_mip_bias(-0.5))),
+            ("normal_map".to_string(), PropertyValue::SampledTexture(SampledTexture::new("normal"))),
+        ];
+
+        let uniforms = with_mip_biases(properties.clone());
+
+        assert_eq!(&uniforms[..3], &properties[..]);
+        assert_eq!(&uniforms[3..], &[
+            ("albedo_map_mip_bias".to_string(), PropertyValue::Float(-0.5)),
+            ("normal_map_mip_bias".to_string(), PropertyValue::Float(0.0)),
+        ]);
+    }
+}
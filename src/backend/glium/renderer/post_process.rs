@@ -0,0 +1,328 @@
+use super::renderer::GLRenderer;
+use super::display::GLDisplay;
+use super::screen_data::ScreenData;
+use crate::renderer::{Image, PostEffect, PostProcessStack, ToneMapping, pipeline::OutputFormat};
+use crate::renderer::pipeline::shader::glsl::*;
+use glium::program::Program;
+use glium::{draw_parameters::DrawParameters, Surface};
+use glium::uniforms::{Sampler, Uniforms, SamplerWrapFunction, MagnifySamplerFilter, MinifySamplerFilter};
+use glium::texture::{Texture2d, DepthTexture2d, DepthFormat, MipmapsOption, RawImage2d};
+use std::collections::HashMap;
+
+const EFFECTS: [&str; 9] = [
+    "BLOOM_PREFILTER",
+    "BLOOM_DOWNSAMPLE",
+    "BLOOM_UPSAMPLE",
+    "BLOOM_COMPOSITE",
+    "TONE_MAPPING",
+    "FXAA",
+    "VIGNETTE",
+    "COLOR_GRADING",
+    "COPY",
+];
+
+/// # Post Process Data
+/// programs of effects, the hdr target the scene is drawn to, two textures effects are drawn to
+/// in turn and the half size textures of bloom
+pub struct PostProcessData {
+    pub programs: HashMap<&'static str,Program>,
+    pub dimensions: (u32,u32),
+    pub scene_target: Option<(Texture2d,DepthTexture2d)>,
+    pub swap_textures: Vec<Texture2d>,
+    pub bloom_textures: Vec<Texture2d>,
+    /// lookup tables of color grading effects by effect name
+    pub luts: HashMap<String,Texture2d>,
+    /// version of stack the lookup tables are uploaded from
+    pub version: Option<u64>,
+}
+
+impl PostProcessData {
+    pub fn new(display: &GLDisplay) -> Self {
+        let version = glsl_version(4, 60);
+        let vertex_shader = glsl(version.clone(), String::new(), position());
+
+        let mut programs = HashMap::new();
+
+        for effect in EFFECTS.iter() {
+            let fragment_shader = glsl(version.clone(), String::new(), post_process(effect));
+            let program = Program::from_source(
+                display, vertex_shader.as_str(), fragment_shader.as_str(), None
+            ).unwrap();
+            programs.insert(*effect, program);
+        }
+
+        Self {
+            programs,
+            dimensions: (0, 0),
+            scene_target: None,
+            swap_textures: Vec::new(),
+            bloom_textures: Vec::new(),
+            luts: HashMap::new(),
+            version: None,
+        }
+    }
+
+    pub fn scene_target(&self) -> Option<(&Texture2d,&DepthTexture2d)> {
+        self.scene_target.as_ref().map(|(color, depth)| (color, depth))
+    }
+
+    /* targets are allocated again when the frame is resized */
+    fn allocate(&mut self, display: &GLDisplay, dimensions: (u32,u32)) {
+        if self.dimensions == dimensions && self.scene_target.is_some() {
+            return;
+        }
+
+        let (w, h) = dimensions;
+
+        let depth = DepthTexture2d::empty_with_format(
+            display,
+            DepthFormat::I24,
+            MipmapsOption::NoMipmap,
+            w,
+            h
+        ).unwrap();
+
+        self.scene_target = Some((hdr_texture(display, w, h), depth));
+        self.swap_textures = vec![hdr_texture(display, w, h), hdr_texture(display, w, h)];
+        self.bloom_textures.clear();
+        self.dimensions = dimensions;
+    }
+
+    /* half size textures down to 1 x 1 */
+    fn allocate_bloom(&mut self, display: &GLDisplay, iterations: usize) {
+        while self.bloom_textures.len() < iterations {
+            let (w, h) = match self.bloom_textures.last() {
+                Some(tex) => tex.dimensions(),
+                None => self.dimensions,
+            };
+
+            if w == 1 && h == 1 && !self.bloom_textures.is_empty() {
+                break;
+            }
+
+            self.bloom_textures.push(hdr_texture(display, (w / 2).max(1), (h / 2).max(1)));
+        }
+    }
+
+    fn upload_luts(&mut self, display: &GLDisplay, stack: &PostProcessStack) {
+        if self.version == Some(stack.version()) {
+            return;
+        }
+
+        self.luts.clear();
+
+        for (name, effect) in stack.effects().iter() {
+            if let PostEffect::ColorGrading { lut, .. } = effect {
+                self.luts.insert(name.clone(), lut_texture(display, lut));
+            }
+        }
+
+        self.version = Some(stack.version());
+    }
+}
+
+impl GLRenderer {
+    pub fn set_post_process(&mut self, stack: PostProcessStack) {
+        self.post_process = stack;
+    }
+
+    /// effects can be added, removed and moved between frames
+    pub fn post_process_mut(&mut self) -> &mut PostProcessStack {
+        &mut self.post_process
+    }
+
+    /* the scene is drawn to the hdr target instead of the frame if the stack is not empty */
+    pub(super) fn begin_post_process(&mut self) {
+        if self.post_process.is_empty() {
+            return;
+        }
+
+        if self.post_process_data.is_none() {
+            self.post_process_data = Some(PostProcessData::new(&self.display));
+        }
+
+        let dimensions = self.display.get_framebuffer_dimensions();
+        let post_process_data = self.post_process_data.as_mut().unwrap();
+        post_process_data.allocate(&self.display, dimensions);
+
+        let [r,g,b,a] = self.data_buffer.bg_color;
+        let (color, depth) = post_process_data.scene_target().unwrap();
+        let mut frame = glium::framebuffer::SimpleFrameBuffer::with_depth_buffer(&self.display, color, depth).unwrap();
+        frame.clear_color_and_depth((r, g, b, a), 1.0);
+    }
+
+    /* effects in the stack order, the last effect is drawn to the frame */
+    pub(super) fn end_post_process(&mut self) {
+        if self.post_process.is_empty() {
+            return;
+        }
+
+        let post_process_data = match self.post_process_data.as_mut() {
+            Some(post_process_data) => post_process_data,
+            None => return,
+        };
+
+        post_process_data.upload_luts(&self.display, &self.post_process);
+
+        let iterations = self.post_process.effects()
+            .iter()
+            .map(|(_, effect)| match effect {
+                PostEffect::Bloom { iterations, .. } => (*iterations).max(1) as usize,
+                _ => 0,
+            })
+            .max()
+            .unwrap_or(0);
+        post_process_data.allocate_bloom(&self.display, iterations);
+
+        let post_process_data = self.post_process_data.as_ref().unwrap();
+        let effects = self.post_process.effects();
+
+        let mut source = &post_process_data.scene_target.as_ref().unwrap().0;
+        let mut next = 0;
+
+        for (i, (name, effect)) in effects.iter().enumerate() {
+            if i + 1 == effects.len() {
+                if let Some(frame) = &mut self.frame {
+                    apply_effect(frame, &self.screen_data, post_process_data, name, effect, source, self.gamma);
+                }
+            } else {
+                let destination = &post_process_data.swap_textures[next];
+                apply_effect(&mut destination.as_surface(), &self.screen_data, post_process_data, name, effect, source, self.gamma);
+                source = destination;
+                next = 1 - next;
+            }
+        }
+    }
+}
+
+fn hdr_texture(display: &GLDisplay, width: u32, height: u32) -> Texture2d {
+    Texture2d::empty_with_format(
+        display,
+        From::from(OutputFormat::Vec4F16),
+        MipmapsOption::NoMipmap,
+        width,
+        height
+    ).unwrap()
+}
+
+fn lut_texture(display: &GLDisplay, image: &Image) -> Texture2d {
+    use std::borrow::Cow;
+
+    Texture2d::with_format(
+        display,
+        RawImage2d {
+            data: Cow::Owned(image.data.clone()),
+            width: image.dimensions.0,
+            height: image.dimensions.1,
+            format: image.image_type.into(),
+        },
+        image.image_type.into(),
+        MipmapsOption::NoMipmap,
+    ).unwrap()
+}
+
+fn sampler(texture: &Texture2d) -> Sampler<'_,Texture2d> {
+    Sampler::new(texture)
+        .wrap_function(SamplerWrapFunction::Clamp)
+        .magnify_filter(MagnifySamplerFilter::Linear)
+        .minify_filter(MinifySamplerFilter::Linear)
+}
+
+fn texel_size(texture: &Texture2d) -> [f32;2] {
+    let (w, h) = texture.dimensions();
+    [1.0 / w as f32, 1.0 / h as f32]
+}
+
+fn draw_screen<S: Surface, U: Uniforms>(frame: &mut S, screen_data: &ScreenData, program: &Program, uniforms: &U) {
+    frame.draw(
+        &screen_data.vertex_buffer,
+        &screen_data.index_buffer,
+        program,
+        uniforms,
+        &DrawParameters::default()
+    ).unwrap();
+}
+
+fn apply_effect<S: Surface>(
+    frame      : &mut S,
+    screen_data: &ScreenData,
+    data       : &PostProcessData,
+    name       : &String,
+    effect     : &PostEffect,
+    source     : &Texture2d,
+    gamma      : f32,
+) {
+    use glium::uniform;
+
+    match effect {
+        PostEffect::Bloom { threshold, intensity, iterations } => {
+            let bloom_textures = &data.bloom_textures[..(*iterations as usize).max(1).min(data.bloom_textures.len())];
+
+            draw_screen(&mut bloom_textures[0].as_surface(), screen_data, &data.programs["BLOOM_PREFILTER"], &uniform! {
+                source: sampler(source),
+                texel_size: texel_size(source),
+                threshold: *threshold,
+            });
+
+            for i in 1..bloom_textures.len() {
+                draw_screen(&mut bloom_textures[i].as_surface(), screen_data, &data.programs["BLOOM_DOWNSAMPLE"], &uniform! {
+                    source: sampler(&bloom_textures[i - 1]),
+                    texel_size: texel_size(&bloom_textures[i - 1]),
+                });
+            }
+
+            for i in (1..bloom_textures.len()).rev() {
+                draw_screen(&mut bloom_textures[i - 1].as_surface(), screen_data, &data.programs["BLOOM_UPSAMPLE"], &uniform! {
+                    source: sampler(&bloom_textures[i]),
+                    texel_size: texel_size(&bloom_textures[i]),
+                });
+            }
+
+            draw_screen(frame, screen_data, &data.programs["BLOOM_COMPOSITE"], &uniform! {
+                source: sampler(source),
+                bloom: sampler(&bloom_textures[0]),
+                intensity: *intensity,
+            });
+        },
+        PostEffect::ToneMapping { operator, exposure } => {
+            let tone_mapping: i32 = match operator {
+                ToneMapping::Reinhard => 0,
+                ToneMapping::Aces => 1,
+                ToneMapping::Filmic => 2,
+            };
+
+            draw_screen(frame, screen_data, &data.programs["TONE_MAPPING"], &uniform! {
+                source: sampler(source),
+                tone_mapping: tone_mapping,
+                exposure: *exposure,
+                gamma: gamma,
+            });
+        },
+        PostEffect::Fxaa => {
+            draw_screen(frame, screen_data, &data.programs["FXAA"], &uniform! {
+                source: sampler(source),
+                texel_size: texel_size(source),
+            });
+        },
+        PostEffect::Vignette { intensity, smoothness } => {
+            draw_screen(frame, screen_data, &data.programs["VIGNETTE"], &uniform! {
+                source: sampler(source),
+                intensity: *intensity,
+                smoothness: smoothness.max(0.001),
+            });
+        },
+        PostEffect::ColorGrading { lut, contribution } => {
+            match data.luts.get(name) {
+                Some(lut_texture) => draw_screen(frame, screen_data, &data.programs["COLOR_GRADING"], &uniform! {
+                    source: sampler(source),
+                    lut: sampler(lut_texture),
+                    lut_size: lut.dimensions.1 as f32,
+                    contribution: *contribution,
+                }),
+                None => draw_screen(frame, screen_data, &data.programs["COPY"], &uniform! {
+                    source: sampler(source),
+                }),
+            }
+        },
+    }
+}
@@ -0,0 +1,248 @@
+use super::renderer::GLRenderer;
+use super::pipeline::uniforms::*;
+use super::render_pass::{Programs, draw_material_objects, material_uniforms};
+use crate::renderer::pipeline::*;
+use glium::program::Program;
+use glium::{draw_parameters::*, Surface};
+use glium::framebuffer::{SimpleFrameBuffer, MultiOutputFrameBuffer};
+use glium::texture::{Texture2d, DepthTexture2d, DepthFormat, MipmapsOption};
+use std::collections::HashMap;
+use std::rc::Rc;
+
+/// # Graph Targets
+/// physical textures of a compiled graph, each slot texture lives in the texture buffer
+/// under the name of the texture currently using it, so passes read it by name
+pub struct GraphTargets {
+    pub dimensions: (u32,u32),
+    /// current name of the slot textures
+    pub slot_names: Vec<String>,
+    /// depth buffers of passes drawing to textures, by size
+    pub depth_textures: HashMap<(u32,u32),DepthTexture2d>,
+}
+
+impl GraphTargets {
+    pub fn new() -> Self {
+        Self {
+            dimensions: (0, 0),
+            slot_names: Vec::new(),
+            depth_textures: HashMap::new(),
+        }
+    }
+}
+
+impl GLRenderer {
+    /// render the opaque scene with the graph instead of the render passes of material shaders,
+    /// transparent objects are still drawn after the graph
+    pub fn set_render_graph(&mut self, mut graph: RenderGraph<Program>) -> Result<(), RenderGraphErr> {
+        graph.compile()?;
+        self.release_graph_targets();
+        self.render_graph = Some(Rc::new(graph));
+        Ok(())
+    }
+
+    pub fn remove_render_graph(&mut self) {
+        self.release_graph_targets();
+        self.render_graph = None;
+    }
+
+    pub fn render_graph(&self) -> Option<&RenderGraph<Program>> {
+        self.render_graph.as_deref()
+    }
+
+    fn release_graph_targets(&mut self) {
+        for name in self.graph_targets.slot_names.drain(..) {
+            self.data_buffer.texture_buffer.remove(&name);
+        }
+        self.graph_targets.depth_textures.clear();
+        self.graph_targets.dimensions = (0, 0);
+    }
+
+    /* textures are allocated again when the frame is resized */
+    fn allocate_graph_targets(&mut self, compiled: &CompiledGraph, dimensions: (u32,u32)) {
+        if self.graph_targets.dimensions == dimensions && self.graph_targets.slot_names.len() == compiled.slots.len() {
+            return;
+        }
+
+        self.release_graph_targets();
+
+        for (i, ((format, _), (w, h))) in compiled.slots.iter().zip(compiled.slot_dimensions(dimensions)).enumerate() {
+            let tex = Texture2d::empty_with_format(
+                &self.display,
+                From::from(*format),
+                MipmapsOption::NoMipmap,
+                w,
+                h
+            ).unwrap();
+
+            let name = format!("render_graph_slot_{}", i);
+            self.data_buffer.texture_buffer.insert(name.clone(), tex);
+            self.graph_targets.slot_names.push(name);
+
+            if !self.graph_targets.depth_textures.contains_key(&(w, h)) {
+                let depth = DepthTexture2d::empty_with_format(
+                    &self.display,
+                    DepthFormat::I24,
+                    MipmapsOption::NoMipmap,
+                    w,
+                    h
+                ).unwrap();
+                self.graph_targets.depth_textures.insert((w, h), depth);
+            }
+        }
+
+        self.graph_targets.dimensions = dimensions;
+    }
+
+    /* move the slot texture to the name of the texture written by a pass */
+    fn bind_graph_target(&mut self, compiled: &CompiledGraph, name: &String) {
+        if let Some(slot) = compiled.slot(name) {
+            let current = &mut self.graph_targets.slot_names[slot];
+            if current != name {
+                if let Some(tex) = self.data_buffer.texture_buffer.remove(current) {
+                    self.data_buffer.texture_buffer.insert(name.clone(), tex);
+                }
+                *current = name.clone();
+            }
+        }
+    }
+
+    pub(super) fn execute_render_graph(&mut self, graph: &RenderGraph<Program>, uniform_data: &mut SceneUniformData) {
+        let compiled = match graph.compiled() {
+            Some(compiled) => compiled,
+            None => return,
+        };
+
+        let dimensions = self.display.get_framebuffer_dimensions();
+        self.allocate_graph_targets(compiled, dimensions);
+
+        for i in compiled.order.iter() {
+            let pass = &graph.passes()[*i];
+
+            for target in pass.writes.iter() {
+                self.bind_graph_target(compiled, &target.name);
+            }
+
+            uniform_data.render_target = None;
+            uniform_data.multiple_render_target = pass.reads.clone();
+
+            self.graph_pass(pass, uniform_data);
+        }
+
+        uniform_data.multiple_render_target = Vec::new();
+    }
+
+    fn graph_pass(&mut self, pass: &GraphPass<Program>, uniform_data: &SceneUniformData) {
+        let mut parameters = DrawParameters {
+            depth: Depth {
+                test: DepthTest::IfLessOrEqual,
+                write: true,
+                ..Default::default()
+            },
+            multisampling: self.antialising_enable,
+            ..Default::default()
+        };
+
+        parameters.load(&pass.pass_option);
+
+        if let GraphPassKind::FullScreen = pass.kind {
+            parameters.blend = Blend::alpha_blending();
+        }
+
+        if pass.is_frame_pass() {
+            let scene_target = match &self.post_process_data {
+                Some(post_process_data) if !self.post_process.is_empty() => post_process_data.scene_target(),
+                _ => None,
+            };
+
+            if let Some((color, depth)) = scene_target {
+                let mut frame = SimpleFrameBuffer::with_depth_buffer(&self.display, color, depth).unwrap();
+                self.draw_graph_pass(&mut frame, pass, uniform_data, &parameters);
+            } else if let Some(mut frame) = self.frame.take() {
+                self.draw_graph_pass(&mut frame, pass, uniform_data, &parameters);
+                self.frame = Some(frame);
+            }
+            return;
+        }
+
+        let output: Vec<(&str,&Texture2d)> = pass.writes
+            .iter()
+            .filter_map(|x| self.data_buffer.texture_buffer.get(&x.name).map(|tex| (x.name.as_str(), tex)))
+            .collect();
+
+        let depth = match output.first() {
+            Some((_, tex)) => &self.graph_targets.depth_textures[&tex.dimensions()],
+            None => return,
+        };
+
+        /* textures of the graph are cleared before their pass */
+        if output.len() == 1 {
+            let mut frame = SimpleFrameBuffer::with_depth_buffer(&self.display, output[0].1, depth).unwrap();
+            frame.clear_color_and_depth((0.0, 0.0, 0.0, 0.0), 1.0);
+            self.draw_graph_pass(&mut frame, pass, uniform_data, &parameters);
+        } else {
+            let mut frame = MultiOutputFrameBuffer::with_depth_buffer(&self.display, output, depth).unwrap();
+            frame.clear_color_and_depth((0.0, 0.0, 0.0, 0.0), 1.0);
+            self.draw_graph_pass(&mut frame, pass, uniform_data, &parameters);
+        }
+    }
+
+    /* scene passes draw opaque and alpha test objects in the queue order, skinned meshes and objects
+       with instances use the programs of the pass and are skipped without them. the forward programs of
+       the material shader would write its lit color to the outputs of the pass */
+    fn draw_graph_pass<S: Surface>(&self, frame: &mut S, pass: &GraphPass<Program>, uniform_data: &SceneUniformData, parameters: &DrawParameters) {
+        match &pass.kind {
+            GraphPassKind::Scene(shader_name) => {
+                let scene_buffer = &self.data_buffer.scene_buffer;
+                let programs = Programs {
+                    shader: &pass.shader,
+                    skinned: pass.skinned_shader.as_ref(),
+                    instanced: pass.instanced_shader.as_ref(),
+                };
+
+                for (phase, material_name) in self.render_queue.materials() {
+                    let material = match scene_buffer.materials.get(material_name) {
+                        Some(material) => material,
+                        None => continue,
+                    };
+
+                    if shader_name.as_ref().map_or(false, |name| *name != material.name) {
+                        continue;
+                    }
+
+                    let objects = self.render_queue
+                        .material_items(phase, material_name)
+                        .iter()
+                        .map(|x| &x.object_name);
+
+                    draw_material_objects(
+                        frame,
+                        scene_buffer,
+                        material_name,
+                        objects,
+                        programs,
+                        parameters,
+                        &material_uniforms(&self.data_buffer, material, uniform_data, pass.pass_option.lighting)
+                    );
+                }
+            },
+            GraphPassKind::FullScreen => {
+                let uniforms = SceneUniform::new(
+                    uniform_data,
+                    Default::default(),
+                    Vec::new(),
+                    pass.pass_option.lighting,
+                    &self.data_buffer.texture_buffer,
+                    &self.data_buffer.cubemap_buffer
+                );
+
+                frame.draw(
+                    &self.screen_data.vertex_buffer,
+                    &self.screen_data.index_buffer,
+                    &pass.shader,
+                    &uniforms,
+                    parameters
+                ).unwrap();
+            },
+        }
+    }
+}
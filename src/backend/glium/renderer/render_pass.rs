@@ -6,7 +6,13 @@ use glium::framebuffer::{SimpleFrameBuffer,MultiOutputFrameBuffer};
 use glium::texture::*;
 use glium::texture::Texture2d;
 use glium::texture::depth_texture2d::DepthTexture2d;
-use crate::renderer::RenderPassRenderer;
+use super::buffer::{DataBuffer, SceneBuffer};
+use crate::base::material::Material;
+use crate::base::transform::IDENTITY;
+use glium::buffer::Buffer;
+use glium::uniforms::Uniforms;
+use rmu::raw::Mat4f;
+use crate::renderer::{RenderPassRenderer, RenderQueue, RenderPhase};
 use crate::renderer::pipeline::*;
 
 impl RenderPassRenderer<SceneUniformData<'_>,Program> for GLRenderer {
@@ -24,28 +30,23 @@ impl RenderPassRenderer<SceneUniformData<'_>,Program> for GLRenderer {
 
         parameters.load(&render_pass.pass_option);
 
+        /* opaque objects overwrite the frame, post processing keeps alpha blending */
+        let opaque = match self.data_buffer.scene_buffer.materials.get(material_name) {
+            Some(material) => !material.is_transparent(),
+            None => false,
+        };
+
+        match &render_pass.render_pass_type {
+            RenderPassType::Pass | RenderPassType::TargetPass(_) | RenderPassType::MultipleRenderPass(_) if opaque => 
+                parameters.blend = glium::Blend::default(),
+            _ => (),
+        }
+
         match &render_pass.render_pass_type {
             RenderPassType::Pass => {
-                if let Some(frame) = &mut self.frame {
-                    if let Some(same_material_objects) = self.data_buffer.scene_buffer.same_material_objects.get(material_name) {
-                        for object_name in same_material_objects.keys() {
-                            let object = self.data_buffer.scene_buffer.objects.get(object_name).unwrap();
-                            let material = self.data_buffer.scene_buffer.materials.get(&object.material_name).unwrap();
-                   
-                            let uniforms = SceneUniform::new(
-                                uniform_data, 
-                                object.transform, 
-                                material.property(), 
-                                render_pass.pass_option.lighting, 
-                                &self.data_buffer.texture_buffer
-                            );
-
-                            if let Some(mesh) = self.data_buffer.scene_buffer.meshes.get(&object.mesh_name) {
-                                frame.draw(&mesh.vertex_buffer, &mesh.index_buffer, &render_pass.shader, &uniforms, &parameters).unwrap();
-                            }
-                        }
-                    }
-                }
+                scene_frame!(self, frame => {
+                    draw_objects(frame, &self.data_buffer, &self.shader_buffer, &self.render_queue, material_name, uniform_data, render_pass, &parameters);
+                });
             },
             RenderPassType::TargetPass((name,format)) => {
 
@@ -84,24 +85,7 @@ impl RenderPassRenderer<SceneUniformData<'_>,Program> for GLRenderer {
 
                 let mut frame = SimpleFrameBuffer::with_depth_buffer(&self.display, texture_ref, depth_ref).unwrap();
 
-                if let Some(same_material_objects) = self.data_buffer.scene_buffer.same_material_objects.get(material_name) {
-                    for object_name in same_material_objects.keys() {
-                            let object = self.data_buffer.scene_buffer.objects.get(object_name).unwrap();
-                            let material = self.data_buffer.scene_buffer.materials.get(&object.material_name).unwrap();
-                   
-                            let uniforms = SceneUniform::new(
-                                uniform_data, 
-                                object.transform, 
-                                material.property(), 
-                                render_pass.pass_option.lighting, 
-                                &self.data_buffer.texture_buffer
-                            );
-
-                            if let Some(mesh) = self.data_buffer.scene_buffer.meshes.get(&object.mesh_name) {
-                                frame.draw(&mesh.vertex_buffer, &mesh.index_buffer, &render_pass.shader, &uniforms, &parameters).unwrap();
-                            }
-                        }
-                }
+                draw_objects(&mut frame, &self.data_buffer, &self.shader_buffer, &self.render_queue, material_name, uniform_data, render_pass, &parameters);
 
                 uniform_data.render_target = Some(name.clone());
             },
@@ -151,36 +135,20 @@ impl RenderPassRenderer<SceneUniformData<'_>,Program> for GLRenderer {
 
                 let mut frame = MultiOutputFrameBuffer::with_depth_buffer(&self.display, frame_output, depth_ref).unwrap();
 
-                if let Some(same_material_objects) = self.data_buffer.scene_buffer.same_material_objects.get(material_name) {
-                    for object_name in same_material_objects.keys() {
-                            let object = self.data_buffer.scene_buffer.objects.get(object_name).unwrap();
-                            let material = self.data_buffer.scene_buffer.materials.get(&object.material_name).unwrap();
-                   
-                            let uniforms = SceneUniform::new(
-                                uniform_data, 
-                                object.transform, 
-                                material.property(), 
-                                render_pass.pass_option.lighting, 
-                                &self.data_buffer.texture_buffer
-                            );
-
-                            if let Some(mesh) = self.data_buffer.scene_buffer.meshes.get(&object.mesh_name) {
-                                frame.draw(&mesh.vertex_buffer, &mesh.index_buffer, &render_pass.shader, &uniforms, &parameters).unwrap();
-                            }
-                        }
-                }
+                draw_objects(&mut frame, &self.data_buffer, &self.shader_buffer, &self.render_queue, material_name, uniform_data, render_pass, &parameters);
 
                 uniform_data.multiple_render_target = multiple_render_target;
             },
             RenderPassType::PostPressingPass => {
-                if let Some(frame) = &mut self.frame {
+                scene_frame!(self, frame => {
 
                     let uniforms = SceneUniform::new(
                         uniform_data, 
                         Default::default(), 
                         Vec::new(), 
                         render_pass.pass_option.lighting, 
-                        &self.data_buffer.texture_buffer
+                        &self.data_buffer.texture_buffer,
+                        &self.data_buffer.cubemap_buffer
                     );
 
                     frame.draw(
@@ -190,7 +158,7 @@ impl RenderPassRenderer<SceneUniformData<'_>,Program> for GLRenderer {
                         &uniforms, 
                         &parameters
                     ).unwrap();
-                }
+                });
             }
             RenderPassType::TargetPostPressingPass((name,format)) => {
                     let w = self.config.size.width as u32;
@@ -233,7 +201,8 @@ impl RenderPassRenderer<SceneUniformData<'_>,Program> for GLRenderer {
                         Default::default(), 
                         Vec::new(), 
                         render_pass.pass_option.lighting, 
-                        &self.data_buffer.texture_buffer
+                        &self.data_buffer.texture_buffer,
+                        &self.data_buffer.cubemap_buffer
                     );
                    
                     frame.draw(
@@ -256,6 +225,189 @@ impl RenderPassRenderer<SceneUniformData<'_>,Program> for GLRenderer {
     }
 }
 
+/// # Programs
+/// programs of the mesh kinds of objects, a skinned mesh or a object with instances
+/// is not drawn if its program is missing
+#[derive(Clone,Copy)]
+pub(super) struct Programs<'a> {
+    pub shader: &'a Program,
+    pub skinned: Option<&'a Program>,
+    pub instanced: Option<&'a Program>,
+}
+
+impl<'a> Programs<'a> {
+    pub fn new(shader: &'a Program) -> Self {
+        Self {
+            shader,
+            skinned: None,
+            instanced: None,
+        }
+    }
+
+    /// `shader` with the skinned and instanced programs of a material shader
+    pub fn material(shader: &'a Program, shader_buffer: &'a GLShaderBuffer, shader_name: &String) -> Self {
+        Self {
+            shader,
+            skinned: shader_buffer.skinned_shader(shader_name),
+            instanced: shader_buffer.instanced_shader(shader_name),
+        }
+    }
+}
+
+/* draw objects with same material in the queue order, batches use the instanced program of the material shader */
+pub(super) fn draw_objects<S: Surface>(
+    frame        : &mut S,
+    data_buffer  : &DataBuffer,
+    shader_buffer: &GLShaderBuffer,
+    render_queue : &RenderQueue,
+    material_name: &String,
+    uniform_data : &SceneUniformData,
+    render_pass  : &RenderPass<Program>,
+    parameters   : &DrawParameters,
+) {
+    let material = match data_buffer.scene_buffer.materials.get(material_name) {
+        Some(material) => material,
+        None => return,
+    };
+
+    let programs = Programs::material(&render_pass.shader, shader_buffer, &material.name);
+    let objects = render_queue
+        .material_items(RenderPhase::from(material.blend_mode), material_name)
+        .iter()
+        .map(|x| &x.object_name);
+
+    draw_material_objects(
+        frame,
+        &data_buffer.scene_buffer,
+        material_name,
+        objects,
+        programs,
+        parameters,
+        &material_uniforms(data_buffer, material, uniform_data, render_pass.pass_option.lighting)
+    );
+}
+
+/* uniforms of objects with the material, for `draw_material_objects` and `draw_mesh` */
+pub(super) fn material_uniforms<'a>(
+    data_buffer : &'a DataBuffer,
+    material    : &'a Material,
+    uniform_data: &'a SceneUniformData<'a>,
+    lighting    : bool,
+) -> impl Fn(Mat4f, Option<&'a Buffer<[Mat4f]>>) -> SceneUniform<'a> + 'a {
+    move |transform, skin| {
+        let uniforms = SceneUniform::new(
+            uniform_data,
+            transform,
+            material.property(),
+            lighting,
+            &data_buffer.texture_buffer,
+            &data_buffer.cubemap_buffer
+        ).with_blend_mode(material.blend_mode, material.opacity);
+
+        match skin {
+            Some(skin) => uniforms.with_skin(skin),
+            None => uniforms,
+        }
+    }
+}
+
+/* draw objects of a material, the objects of its batches are drawn by one call each batch if there is
+   an instanced program, otherwise one by one. `uniforms` is called with the transform and the joint matrices */
+pub(super) fn draw_material_objects<'a, 'n, S, U, F, I>(
+    frame        : &mut S,
+    scene_buffer : &'a SceneBuffer,
+    material_name: &String,
+    objects      : I,
+    programs     : Programs,
+    parameters   : &DrawParameters,
+    uniforms     : &F,
+) where
+    S: Surface,
+    U: Uniforms,
+    F: Fn(Mat4f, Option<&'a Buffer<[Mat4f]>>) -> U,
+    I: IntoIterator<Item = &'n String>,
+{
+    if let (Some(batches), Some(shader)) = (scene_buffer.batches.get(material_name), programs.instanced) {
+        for batch in batches.iter() {
+            if let Some(mesh) = scene_buffer.meshes.get(&batch.mesh_name) {
+                frame.draw(
+                    (&mesh.vertex_buffer, batch.instance_buffer.per_instance().unwrap()),
+                    &mesh.index_buffer,
+                    shader,
+                    &uniforms(IDENTITY, None),
+                    parameters
+                ).unwrap();
+            }
+        }
+    }
+
+    for object_name in objects {
+        if programs.instanced.is_some() && scene_buffer.batched_objects.contains_key(object_name) {
+            continue;
+        }
+
+        draw_mesh(frame, scene_buffer, object_name, programs, parameters, uniforms);
+    }
+}
+
+/* draw a object with the program of its mesh kind */
+pub(super) fn draw_mesh<'a, S, U, F>(
+    frame       : &mut S,
+    scene_buffer: &'a SceneBuffer,
+    object_name : &String,
+    programs    : Programs,
+    parameters  : &DrawParameters,
+    uniforms    : &F,
+) where
+    S: Surface,
+    U: Uniforms,
+    F: Fn(Mat4f, Option<&'a Buffer<[Mat4f]>>) -> U,
+{
+    let object = match scene_buffer.objects.get(object_name) {
+        Some(object) => object,
+        None => return,
+    };
+
+    if let Some(instances) = scene_buffer.instances.get(object_name) {
+        if let (Some(mesh), Some(shader)) = (scene_buffer.meshes.get(&object.mesh_name), programs.instanced) {
+            frame.draw(
+                (&mesh.vertex_buffer, instances.per_instance().unwrap()),
+                &mesh.index_buffer,
+                shader,
+                &uniforms(object.transform, None),
+                parameters
+            ).unwrap();
+        }
+    } else if let Some(mesh) = scene_buffer.meshes.get(&object.mesh_name) {
+        frame.draw(&mesh.vertex_buffer, &mesh.index_buffer, programs.shader, &uniforms(object.transform, None), parameters).unwrap();
+    } else if let Some(mesh) = scene_buffer.skinned_meshes.get(&object.mesh_name) {
+        /* skinned mesh without joint matrices is not drawn */
+        if let (Some(skin), Some(shader)) = (scene_buffer.skins.get(object_name), programs.skinned) {
+            frame.draw(&mesh.vertex_buffer, &mesh.index_buffer, shader, &uniforms(object.transform, Some(skin)), parameters).unwrap();
+        }
+    }
+}
+
+/* draw a object with its material, see `draw_mesh` */
+pub(super) fn draw_object<S: Surface>(
+    frame       : &mut S,
+    data_buffer : &DataBuffer,
+    object_name : &String,
+    programs    : Programs,
+    uniform_data: &SceneUniformData,
+    lighting    : bool,
+    parameters  : &DrawParameters,
+) {
+    let scene_buffer = &data_buffer.scene_buffer;
+
+    let material = match scene_buffer.objects.get(object_name).and_then(|x| scene_buffer.materials.get(&x.material_name)) {
+        Some(material) => material,
+        None => return,
+    };
+
+    draw_mesh(frame, scene_buffer, object_name, programs, parameters, &material_uniforms(data_buffer, material, uniform_data, lighting));
+}
+
 impl From<OutputFormat> for UncompressedFloatFormat {
     fn from(output: OutputFormat) -> Self {
         match output {
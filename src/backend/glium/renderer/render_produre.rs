@@ -5,59 +5,138 @@ use crate::renderer::{
     RenderPassRenderer, 
     pipeline::ShaderBuffer,
 };
-use glium::uniforms::UniformBuffer;
+use super::render_pass::{Programs, draw_object};
+use super::shadow::draw_shadow_casters;
+use super::deferred::RenderPath;
+use crate::renderer::{view_depth, RenderPhase, pipeline::{OutputFormat, PassOptionLoader}};
+use crate::base::material::BlendMode;
+use glium::uniforms::{UniformBuffer, Sampler, SamplerWrapFunction, MagnifySamplerFilter, MinifySamplerFilter, DepthTextureComparison};
 use glium::Surface;
+use glium::draw_parameters::*;
+use glium::framebuffer::{SimpleFrameBuffer, MultiOutputFrameBuffer};
+use glium::texture::{Texture2d, DepthTexture2d, DepthFormat, MipmapsOption};
 use std::rc::Rc;
 
-impl RenderProdure for GLRenderer {
-    fn clear(&mut self) {
-        let [r,g,b,a] = self.data_buffer.bg_color;
-        let mut frame = self.display.draw();
-        frame.clear_color_and_depth((r, g, b, a), 1.0);
-        self.frame = Some(frame);
-    }
+impl GLRenderer {
+    /* transparent objects use the first pass of the material shader, they are drawn from back to front,
+       or alpha blend objects are drawn with weighted blended order independent transparency */
+    fn render_transparent(&mut self, uniform_data: &SceneUniformData) {
+        let scene_data = self.data_buffer.scene_buffer.clone();
+        let shader_buffer = self.shader_buffer.clone();
 
-    fn shadow_map(&mut self) {
+        /* back to front by the render queue */
+        let mut transparent_objects: Vec<(&String, BlendMode)> = self.render_queue
+            .phase_items(RenderPhase::Transparent)
+            .iter()
+            .filter_map(|item| {
+                let (name, object) = scene_data.objects.get_key_value(&item.object_name)?;
+                let material = scene_data.materials.get(&object.material_name)?;
+                Some((name, material.blend_mode))
+            })
+            .collect();
+
+        if transparent_objects.is_empty() {
+            return;
+        }
 
-        let mut camera = self.data_buffer.camera.clone();
+        if self.oit_enable {
+            let alpha_blend_objects: Vec<&String> = transparent_objects
+                .iter()
+                .filter(|(_, blend_mode)| *blend_mode == BlendMode::AlphaBlend)
+                .map(|(name, _)| *name)
+                .collect();
 
-        // let mut shadow_map_views = Vec::new();
+            self.render_oit(uniform_data, &alpha_blend_objects);
+            transparent_objects.retain(|(_, blend_mode)| *blend_mode != BlendMode::AlphaBlend);
+        }
 
-        for (name,light) in &self.data_buffer.light_buffer.lights {
-            camera.set_look_from(light.position());
+        scene_frame!(self, frame => {
+            for (name, blend_mode) in transparent_objects {
+                let object = &scene_data.objects[name];
+                let material = &scene_data.materials[&object.material_name];
 
-            use rmu::vector::Vector3;
-            let project = if light.is_parallel(){
-                camera.look_at = camera.look_from + Vector3::from(light.direction());
-                camera.ortho()
-            } else {
-                camera.perspective()
-            };
+                if let Some(render_pass) = shader_buffer.shader(&material.name) {
+                    let mut parameters = DrawParameters {
+                        depth: Depth {
+                            test: DepthTest::IfLessOrEqual,
+                            write: false,
+                            ..Default::default()
+                        },
+                        blend: transparent_blend(blend_mode),
+                        multisampling: self.antialising_enable,
+                        ..Default::default()
+                    };
 
-            let view = camera.view();
+                    parameters.load(&render_pass.pass_option);
+                    parameters.depth.write = false;
 
-            let light_camera_matrix = UniformBuffer::new(
-                &self.display, 
-                CameraMatrix {
-                    project,
-                    view: view,
+                    draw_object(
+                        frame, 
+                        &self.data_buffer, 
+                        name, 
+                        Programs::material(&render_pass.shader, &shader_buffer, &material.name), 
+                        uniform_data, 
+                        render_pass.pass_option.lighting, 
+                        &parameters
+                    );
                 }
+            }
+        });
+    }
+
+    /* accumulate the objects into two additive targets with the depth of opaque objects, then composite to frame */
+    fn render_oit(&mut self, uniform_data: &SceneUniformData, objects: &[&String]) {
+        if objects.is_empty() {
+            return;
+        }
+
+        let dimensions = self.display.get_framebuffer_dimensions();
+        let (w, h) = dimensions;
+
+        /* the targets are allocated again when the frame is resized */
+        for (name, format) in [("oit_accum", OutputFormat::Vec4F16), ("oit_revealage", OutputFormat::F16)].iter() {
+            match self.data_buffer.texture_buffer.get(*name) {
+                Some(tex) if tex.dimensions() == dimensions => continue,
+                _ => (),
+            }
+
+            let tex = Texture2d::empty_with_format(
+                &self.display,
+                From::from(*format),
+                MipmapsOption::NoMipmap,
+                w,
+                h
             ).unwrap();
+            self.data_buffer.texture_buffer.insert(name.to_string(), tex);
+        }
 
-            let shadow_map = self.data_buffer.light_buffer.shadow_maps.get(name).unwrap();
+        match &self.oit_depth {
+            Some(depth) if depth.dimensions() == dimensions => (),
+            _ => {
+                let depth = DepthTexture2d::empty_with_format(
+                    &self.display,
+                    DepthFormat::I24,
+                    MipmapsOption::NoMipmap,
+                    w,
+                    h
+                ).unwrap();
+                self.oit_depth = Some(depth);
+            },
+        }
 
-            //use rmu::matrix::Matrix4x4;
-            //shadow_map_views.push((Matrix4x4::from(project) * Matrix4x4::from(view)).into());
+        let scene_data = self.data_buffer.scene_buffer.clone();
+        let shader_buffer = self.shader_buffer.clone();
 
-            use glium::framebuffer::SimpleFrameBuffer;
-            let mut frame = SimpleFrameBuffer::depth_only(&self.display, shadow_map).unwrap();
+        let accum = self.data_buffer.texture_buffer.get("oit_accum").unwrap();
+        let revealage = self.data_buffer.texture_buffer.get("oit_revealage").unwrap();
+        let depth = self.oit_depth.as_ref().unwrap();
 
-            frame.clear_color(1.0, 1.0, 1.0, 1.0);
+        /* depth of opaque and alpha test objects, the shadow programs only write depth */
+        {
+            let mut frame = SimpleFrameBuffer::depth_only(&self.display, depth).unwrap();
             frame.clear_depth(1.0);
 
-            use glium::draw_parameters::*;
-
-            let paratmeter = DrawParameters {
+            let depth_parameters = DrawParameters {
                 depth: Depth {
                     test: DepthTest::IfLessOrEqual,
                     write: true,
@@ -66,29 +145,146 @@ impl RenderProdure for GLRenderer {
                 ..Default::default()
             };
 
-            use glium::uniform;
-
-            for object in self.data_buffer.scene_buffer.objects.values() {
-                if let Some(mesh) = self.data_buffer.scene_buffer.meshes.get(&object.mesh_name) {
-                    frame.draw(
-                        &mesh.vertex_buffer, 
-                        &mesh.index_buffer, 
-                        &self.screen_data.shadow_map, 
-                        &uniform!{
-                            Camera: &light_camera_matrix,
-                            transform: object.transform,
-                        }, 
-                        &paratmeter,
-                        ).unwrap();
+            let programs = Programs {
+                shader: &self.screen_data.shadow_map,
+                skinned: Some(&self.screen_data.skinned_shadow_map),
+                instanced: Some(&self.screen_data.instanced_shadow_map),
+            };
+
+            draw_shadow_casters(&mut frame, &scene_data, programs, &depth_parameters, |transform| glium::uniform! {
+                Camera: uniform_data.matrix,
+                transform: transform,
+            });
+        }
+
+        {
+            let output = vec![("oit_accum", accum), ("oit_revealage", revealage)];
+            let mut frame = MultiOutputFrameBuffer::with_depth_buffer(&self.display, output, depth).unwrap();
+            frame.clear_color(0.0, 0.0, 0.0, 0.0);
+
+            let parameters = DrawParameters {
+                depth: Depth {
+                    test: DepthTest::IfLessOrEqual,
+                    write: false,
+                    ..Default::default()
+                },
+                blend: Blend {
+                    color: BlendingFunction::Addition {
+                        source: LinearBlendingFactor::One,
+                        destination: LinearBlendingFactor::One,
+                    },
+                    alpha: BlendingFunction::Addition {
+                        source: LinearBlendingFactor::One,
+                        destination: LinearBlendingFactor::One,
+                    },
+                    constant_value: (0.0, 0.0, 0.0, 0.0),
+                },
+                ..Default::default()
+            };
+
+            for name in objects {
+                if let Some(object) = scene_data.objects.get(*name) {
+                    if let Some(material) = scene_data.materials.get(&object.material_name) {
+                        if let (Some(shader), Some(render_pass)) = (shader_buffer.oit_shader(&material.name), shader_buffer.shader(&material.name)) {
+                            draw_object(&mut frame, &self.data_buffer, name, Programs::new(shader), uniform_data, render_pass.pass_option.lighting, &parameters);
+                        }
+                    }
                 }
             }
         }
 
-        let light_buffer = Rc::get_mut(&mut self.data_buffer.light_buffer).unwrap();
-        // light_buffer.shadow_map_views = shadow_map_views;
+        scene_frame!(self, frame => {
+            let uniforms = glium::uniform! {
+                oit_accum: accum,
+                oit_revealage: revealage,
+            };
+
+            let parameters = DrawParameters {
+                blend: Blend::alpha_blending(),
+                ..Default::default()
+            };
+
+            frame.draw(
+                &self.screen_data.vertex_buffer, 
+                &self.screen_data.index_buffer, 
+                &self.screen_data.oit_composite, 
+                &uniforms, 
+                &parameters
+            ).unwrap();
+        });
+    }
+}
+
+impl GLRenderer {
+    /* group objects with same mesh and material for instanced drawing, before shadows and the scene */
+    pub(super) fn update_batches(&mut self) {
+        if let Some(scene_buffer) = Rc::get_mut(&mut self.data_buffer.scene_buffer) {
+            if scene_buffer.batches_dirty {
+                scene_buffer.build_batches(&self.display);
+            }
+        }
+    }
+
+    /* draw order of objects with loaded material */
+    fn build_render_queue(&mut self) {
+        let view = self.data_buffer.camera.view();
+        let scene_buffer = &self.data_buffer.scene_buffer;
+
+        self.render_queue.clear();
+
+        for (name, object) in scene_buffer.objects.iter() {
+            if let Some(material) = scene_buffer.materials.get(&object.material_name) {
+                self.render_queue.push(
+                    name, 
+                    &object.material_name, 
+                    self.material_instances.parent(&object.material_name),
+                    &material.name, 
+                    &object.mesh_name, 
+                    RenderPhase::from(material.blend_mode), 
+                    view_depth(&view, &object.transform)
+                );
+            }
+        }
+
+        self.render_queue.sort();
+    }
+}
+
+fn transparent_blend(blend_mode: BlendMode) -> Blend {
+    match blend_mode {
+        BlendMode::Additive => Blend {
+            color: BlendingFunction::Addition {
+                source: LinearBlendingFactor::SourceAlpha,
+                destination: LinearBlendingFactor::One,
+            },
+            alpha: BlendingFunction::Addition {
+                source: LinearBlendingFactor::Zero,
+                destination: LinearBlendingFactor::One,
+            },
+            constant_value: (0.0, 0.0, 0.0, 0.0),
+        },
+        _ => Blend::alpha_blending(),
+    }
+}
+
+impl RenderProdure for GLRenderer {
+    fn clear(&mut self) {
+        let [r,g,b,a] = self.data_buffer.bg_color;
+        let mut frame = self.display.draw();
+        frame.clear_color_and_depth((r, g, b, a), 1.0);
+        self.frame = Some(frame);
+    }
+
+    fn shadow_map(&mut self) {
+        self.render_shadow_maps();
     }
 
     fn render(&mut self) {
+        self.update_batches();
+        self.build_render_queue();
+        self.begin_post_process();
+        self.prepare_ssao();
+
         let matrix = UniformBuffer::new(
             &self.display, 
             CameraMatrix {
@@ -98,45 +294,67 @@ impl RenderProdure for GLRenderer {
         
         /* get lights buffer*/
         let light_buffer = self.data_buffer.light_buffer.clone();
+        let environment_data = self.environment_data.clone();
+        let ssao_data = self.ssao_data.clone();
         let lights_uniform = &light_buffer.unifrom_buffer();
         let lights_count = light_buffer.light_number() as i32;
 
-        use glium::texture::DepthTexture2d;
-        use glium::uniforms::*;
-
-        let shadow_maps_ref: Vec<Sampler<'_,DepthTexture2d>> 
-            = light_buffer.shadow_maps
-                .values()
-                .map( 
-                    |x| Sampler::new(x)
-                            .magnify_filter(glium::uniforms::MagnifySamplerFilter::Nearest)
-					        .minify_filter(glium::uniforms::MinifySamplerFilter::Nearest)
-                            .depth_texture_comparison(Some(DepthTextureComparison::LessOrEqual))
-                )
-                .collect();
+        let shadow_map = Sampler::new(&light_buffer.shadow_maps)
+            .wrap_function(SamplerWrapFunction::Clamp)
+            .magnify_filter(MagnifySamplerFilter::Linear)
+            .minify_filter(MinifySamplerFilter::Linear)
+            .depth_texture_comparison(Some(DepthTextureComparison::LessOrEqual));
 
-        // let shadow_map_views = light_buffer.shadow_map_views.clone();
+        let point_shadow_map = Sampler::new(&light_buffer.point_shadow_maps)
+            .magnify_filter(MagnifySamplerFilter::Linear)
+            .minify_filter(MinifySamplerFilter::Linear)
+            .depth_texture_comparison(Some(DepthTextureComparison::LessOrEqual));
 
         let mut uniform_data = SceneUniformData::new(
             &matrix, 
             &lights_uniform, 
             lights_count, 
             self.data_buffer.camera.look_from(), 
-            self.hdr_enable, self.gamma
-        );
+            self.hdr_enable && !self.post_process.has_tone_mapping(), self.gamma
+        )
+        .with_shadows(&light_buffer.shadow_buffer, shadow_map, point_shadow_map, self.shadow_config.filter)
+        .with_environment(environment_data.uniforms())
+        .with_ssao(ssao_data.uniforms(self.ssao_config.is_some()));
 
-        {
+        /* opaque and alpha test objects in the queue order */
+        let material_names: Vec<String> = self.render_queue
+            .materials()
+            .into_iter()
+            .map(|(_, name)| name.clone())
+            .collect();
+
+        /* the deferred path computes occlusion from its own g-buffer, the others draw it as a pre-pass */
+        if self.ssao_config.is_some() && (self.render_graph.is_some() || self.render_path != RenderPath::Deferred) {
+            self.render_gbuffer(&uniform_data, &material_names);
+            self.render_ssao(&uniform_data);
+        }
+
+        if let Some(graph) = self.render_graph.clone() {
+            self.execute_render_graph(&graph, &mut uniform_data);
+        } else if self.render_path == RenderPath::Deferred {
+            self.render_deferred(&mut uniform_data);
+        } else {
             let scene_data = self.data_buffer.scene_buffer.clone();
             let shader_buffer = self.shader_buffer.clone();
 
-            for material_name in scene_data.same_material_objects.keys() {
-                let material = scene_data.materials.get(material_name).unwrap();
-                if let Some(render_pass) = shader_buffer.shader(&material.name) {
-                    self.render_pass(&material_name, &mut uniform_data, render_pass);
+            for material_name in material_names.iter() {
+                if let Some(material) = scene_data.materials.get(material_name) {
+                    if let Some(render_pass) = shader_buffer.shader(&material.name) {
+                        self.render_pass(&material_name, &mut uniform_data, render_pass);
+                    }
                 }
             }
         }
 
+        self.render_skybox();
+        self.render_transparent(&uniform_data);
+        self.end_post_process();
+
     }
 
     fn swap_buffer(&mut self)  {
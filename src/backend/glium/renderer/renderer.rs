@@ -1,24 +1,60 @@
 use crate::application::*;
 use crate::renderer::*;
 use crate::base::font::FontSet;
+use crate::base::material_definition::MaterialDefinitions;
+use crate::base::material_instance::MaterialInstances;
 
 use super::buffer::*;
 use super::pipeline::*;
 use super::screen_data::*;
+use super::render_graph::GraphTargets;
+use super::deferred::{RenderPath, DeferredData};
+use super::post_process::PostProcessData;
+use super::environment::EnvironmentData;
+use super::ssao::SsaoData;
+use crate::renderer::pipeline::RenderGraph;
+use glium::program::Program;
 use glium::Display;
+use super::display::*;
 use glium::Frame;
+use glium::texture::DepthTexture2d;
 
 use std::rc::Rc;
 
 pub struct GLRenderer {
-    pub display           : Display,
+    pub display           : GLDisplay,
     pub frame             : Option<Frame>,
     pub data_buffer       : DataBuffer,
     pub shader_buffer     : Rc<GLShaderBuffer>,
+    /// materials are validated against these on `update_material`
+    pub material_definitions: MaterialDefinitions,
+    /// instances whose materials are in the scene buffer, with the parent they are updated with
+    pub material_instances: MaterialInstances,
+    /// replaces the render passes of material shaders if it is set
+    pub render_graph      : Option<Rc<RenderGraph<Program>>>,
+    pub graph_targets     : GraphTargets,
+    pub render_path       : RenderPath,
+    /// g-buffer programs, created when the deferred path is selected
+    pub deferred_data     : Option<DeferredData>,
+    /// effects applied to the frame in order, the scene is drawn to a f16 target if it is not empty
+    pub post_process      : PostProcessStack,
+    pub post_process_data : Option<PostProcessData>,
+    /// shadow maps of spot and parallel lights drawn by `shadow_map`
+    pub shadow_config     : ShadowConfig,
+    /// skybox and image based lighting maps from `set_environment`
+    pub environment_data  : Rc<EnvironmentData>,
+    /// ambient occlusion from `set_ssao`, `None` is disabled
+    pub ssao_config       : Option<SsaoConfig>,
+    pub ssao_data         : Rc<SsaoData>,
+    /// draw order of the frame, built at the begin of `render`
+    pub render_queue      : RenderQueue,
     pub screen_data       : ScreenData,
     pub antialising_enable: bool,
     pub config            : Config,
     pub hdr_enable        : bool,
+    pub oit_enable        : bool,
+    /// depth of opaque objects for order independent transparency, allocated again when the frame is resized
+    pub oit_depth         : Option<DepthTexture2d>,
     pub gamma             : f32,
     pub graphics_paint    : GLGraphicsPaint,
     pub text_paint        : GLTextPaint,
@@ -26,6 +62,16 @@ pub struct GLRenderer {
 
 impl GLRenderer {
     pub fn new(config: Config, display: Display) -> Self {
+        Self::with_display(config, GLDisplay::Window(display))
+    }
+
+    /// renderer without window, the frame size is the config size
+    pub fn headless(config: Config) -> Result<Self, HeadlessErr> {
+        let display = GLDisplay::headless(config.size.width as u32, config.size.height as u32)?;
+        Ok(Self::with_display(config, display))
+    }
+
+    pub fn with_display(config: Config, display: GLDisplay) -> Self {
         let mut shader_buffer = GLShaderBuffer::new();
         shader_buffer.load_bulidin(&display);
 
@@ -33,11 +79,26 @@ impl GLRenderer {
             frame             : None,
             data_buffer       : DataBuffer::new(&display),
             shader_buffer     : Rc::new(shader_buffer),
+            material_definitions: MaterialDefinitions::bulidin(),
+            material_instances: MaterialInstances::new(),
+            render_graph      : None,
+            graph_targets     : GraphTargets::new(),
+            render_path       : RenderPath::Forward,
+            deferred_data     : None,
+            post_process      : PostProcessStack::new(),
+            post_process_data : None,
+            shadow_config     : ShadowConfig::default(),
+            environment_data  : Rc::new(EnvironmentData::new(&display)),
+            ssao_config       : None,
+            ssao_data         : Rc::new(SsaoData::new(&display)),
+            render_queue      : RenderQueue::new(),
             screen_data       : ScreenData::new(&display),
             antialising_enable: true,
             display,
             config,
             hdr_enable        : true,
+            oit_enable        : false,
+            oit_depth         : None,
             gamma             : 2.2,
             graphics_paint    : GLGraphicsPaint::new(),
             text_paint        : GLTextPaint::new(),
@@ -64,6 +125,67 @@ impl Renderer for GLRenderer {
     fn set_gamma(&mut self, gamma: f32) {
         self.gamma = gamma;
     }
+
+    fn set_order_independent_transparency(&mut self, enable: bool) {
+        self.oit_enable = enable;
+    }
+
+    fn set_environment(&mut self, image: &Image) {
+        self.load_environment(image);
+    }
+
+    fn set_ssao(&mut self, config: Option<SsaoConfig>) {
+        self.load_ssao(config);
+    }
+
+    fn read_frame(&mut self) -> Image {
+        use glium::Surface;
+        use glium::texture::{Texture2d, RawImage2d, UncompressedFloatFormat, MipmapsOption};
+        use glium::uniforms::MagnifySamplerFilter;
+
+        let raw: RawImage2d<u8> = match &self.frame {
+            Some(frame) => {
+                let (width, height) = frame.get_dimensions();
+                let texture = Texture2d::empty_with_format(
+                    &self.display,
+                    UncompressedFloatFormat::U8U8U8U8,
+                    MipmapsOption::NoMipmap,
+                    width,
+                    height
+                ).unwrap();
+
+                frame.fill(&texture.as_surface(), MagnifySamplerFilter::Nearest);
+                texture.read()
+            },
+            None => self.display.read_front_buffer().unwrap(),
+        };
+
+        flipped_image(raw)
+    }
+
+    fn read_target(&mut self, name: &str) -> Option<Image> {
+        use glium::texture::RawImage2d;
+
+        let texture = self.data_buffer.texture_buffer.get(name)?;
+        let raw: RawImage2d<u8> = texture.read();
+        Some(flipped_image(raw))
+    }
+}
+
+/// opengl rows are from bottom to top
+fn flipped_image(raw: glium::texture::RawImage2d<u8>) -> Image {
+    let row_length = raw.width as usize * 4;
+    let data: Vec<u8> = raw.data
+        .chunks(row_length)
+        .rev()
+        .flat_map(|row| row.iter().cloned())
+        .collect();
+
+    Image {
+        data,
+        dimensions: (raw.width, raw.height),
+        image_type: ImageType::U8U8U8U8,
+    }
 }
 
 use crate::base::color::BLACK;
@@ -1,5 +1,6 @@
 use super::renderer::GLRenderer;
 use super::buffer::*;
+use super::display::GLDisplay;
 use std::rc::Rc;
 use glium::{
     buffer::*,
@@ -10,8 +11,14 @@ use glium::{
 use crate::base::{
     camera::Camera,
     material::Material,
+    material_definition::{MaterialDefinition, MaterialErr},
+    material_instance::MaterialInstance,
     index::Indices,
+    Vertex,
+    SkinnedVertex,
 };
+use crate::animation::JOINTS_MAX_NUMBER;
+use rmu::raw::Mat4f;
 use crate::renderer::{
     RendererManager,
     data::*,
@@ -27,24 +34,31 @@ impl RendererManager for GLRenderer {
             let name = name.to_string();
             match object.update {
                 DataUpdate::ALL => {
-                    scene_buffer.objects.insert(name.clone(),RenderObject::new(object.mesh_name.clone(), object.material_name.clone(), object.transform));
-
-                    use std::collections::HashMap;
-                    if let Some(same_material_objects) = scene_buffer.same_material_objects.get_mut(&object.material_name) {
-                        same_material_objects.insert(name.clone(),());
-                    } else {
-                        scene_buffer.same_material_objects.insert(object.material_name.clone(),HashMap::new());
-                        scene_buffer.same_material_objects.get_mut(&object.material_name).unwrap().insert(name.clone(),());
+                    let render_object = RenderObject::new(object.mesh_name.clone(), object.material_name.clone(), object.transform);
+                    if let Some(old_object) = scene_buffer.objects.insert(name.clone(), render_object) {
+                        scene_buffer.remove_same_material_object(&old_object.material_name, &name);
                     }
+
+                    scene_buffer.add_same_material_object(&object.material_name, &name);
                 },
                 DataUpdate::Transfrom => {
                     if let Some(render_object) = scene_buffer.objects.get_mut(&name) {
                         render_object.transform = object.transform;
                     }
+
+                    /* the batches keep their objects, only the slot is written */
+                    scene_buffer.update_batched_transform(&name);
                 },
                 DataUpdate::Material => {
-                    if let Some(render_object) = scene_buffer.objects.get_mut(&name) {
-                        render_object.material_name = object.material_name.clone();
+                    let old_material_name = if let Some(render_object) = scene_buffer.objects.get_mut(&name) {
+                        Some(std::mem::replace(&mut render_object.material_name, object.material_name.clone()))
+                    } else {
+                        None
+                    };
+
+                    if let Some(old_material_name) = old_material_name {
+                        scene_buffer.remove_same_material_object(&old_material_name, &name);
+                        scene_buffer.add_same_material_object(&object.material_name, &name);
                     }
                 },
                 DataUpdate::Statue => {
@@ -52,6 +66,12 @@ impl RendererManager for GLRenderer {
                 },
                 DataUpdate::Not => (),
             }
+
+            /* mesh or material changed */
+            if object.update == DataUpdate::ALL || object.update == DataUpdate::Material {
+                scene_buffer.batches_dirty = true;
+            }
+
             // update  finished
             object.update = DataUpdate::Not;
         } else {
@@ -60,6 +80,47 @@ impl RendererManager for GLRenderer {
 
     }
 
+    fn remove_object(&mut self, name: &str) {
+        if let Some(scene_buffer) = Rc::get_mut(&mut self.data_buffer.scene_buffer) {
+            let name = name.to_string();
+            if let Some(render_object) = scene_buffer.objects.remove(&name) {
+                scene_buffer.remove_same_material_object(&render_object.material_name, &name);
+            }
+            scene_buffer.skins.remove(&name);
+            scene_buffer.instances.remove(&name);
+            scene_buffer.batches_dirty = true;
+        }
+    }
+
+    fn update_instances(&mut self, name: &str, instances: &[Instance]) {
+        /* a vertex buffer can't be empty, the object is drawn once like a object without instances */
+        if instances.is_empty() {
+            self.remove_instances(name);
+            return;
+        }
+
+        if let Some(scene_buffer) = Rc::get_mut(&mut self.data_buffer.scene_buffer) {
+            let instances: Vec<InstanceAttribute> = instances.iter().map(|x| InstanceAttribute::from(*x)).collect();
+
+            match scene_buffer.instances.get_mut(name) {
+                Some(instance_buffer) if instance_buffer.len() == instances.len() => instance_buffer.write(&instances),
+                _ => {
+                    let instance_buffer = VertexBuffer::dynamic(&self.display, &instances).unwrap();
+                    scene_buffer.instances.insert(name.to_string(), instance_buffer);
+                    scene_buffer.batches_dirty = true;
+                },
+            }
+        }
+    }
+
+    fn remove_instances(&mut self, name: &str) {
+        if let Some(scene_buffer) = Rc::get_mut(&mut self.data_buffer.scene_buffer) {
+            if let Some(_) = scene_buffer.instances.remove(name) {
+                scene_buffer.batches_dirty = true;
+            }
+        }
+    }
+
     fn update_texture(&mut self, name: &str, image: &Image) {
         
         use std::borrow::Cow;
@@ -73,82 +134,227 @@ impl RendererManager for GLRenderer {
                 format: image.image_type.into(),
             },
             image.image_type.into(),
-            MipmapsOption::NoMipmap,
+            /* mipmaps for the mipmap filters of `SampledTexture` */
+            MipmapsOption::AutoGeneratedMipmaps,
         ).unwrap();
 
         self.data_buffer.texture_buffer.insert(name.to_string(), texture);
 
     }
 
+    fn update_cubemap(&mut self, name: &str, faces: &[Image;6]) {
+        use std::borrow::Cow;
+        use glium::Surface;
+        use glium::framebuffer::SimpleFrameBuffer;
+        use glium::uniforms::MagnifySamplerFilter;
+
+        let size = faces[0].dimensions.0;
+        let cubemap = Cubemap::empty_with_format(
+            &self.display,
+            faces[0].image_type.into(),
+            MipmapsOption::NoMipmap,
+            size,
+        ).unwrap();
+
+        let layers = [
+            CubeLayer::PositiveX, CubeLayer::NegativeX,
+            CubeLayer::PositiveY, CubeLayer::NegativeY,
+            CubeLayer::PositiveZ, CubeLayer::NegativeZ,
+        ];
+
+        /* a face is uploaded to a 2d texture and blitted to its layer */
+        for (face, layer) in faces.iter().zip(layers.iter()) {
+            let texture = Texture2d::with_format(
+                &self.display,
+                RawImage2d {
+                    data: Cow::Owned(face.data.clone()),
+                    width: face.dimensions.0,
+                    height: face.dimensions.1,
+                    format: face.image_type.into(),
+                },
+                face.image_type.into(),
+                MipmapsOption::NoMipmap,
+            ).unwrap();
+
+            let frame = SimpleFrameBuffer::new(&self.display, cubemap.main_level().image(*layer)).unwrap();
+            texture.as_surface().fill(&frame, MagnifySamplerFilter::Linear);
+        }
+
+        self.data_buffer.cubemap_buffer.insert(name.to_string(), cubemap);
+    }
+
+    fn remove_cubemap(&mut self, name: &str) {
+        self.data_buffer.cubemap_buffer.remove(name);
+    }
+
     fn update_mesh(&mut self, name: &str, mesh: &GMesh) {
         if let Some(scene_buffer) = Rc::get_mut(&mut self.data_buffer.scene_buffer) {
 
             let vertex_buffer = VertexBuffer::new(&self.display, &mesh.vertices).unwrap();
 
-            let index_buffer = match &mesh.indices {
-                Indices::Points(indices) => 
-                    IndexBuffer::new(&self.display, PrimitiveType::Points, &indices).unwrap(),
-                Indices::EdgeLists(indices) => 
-                    IndexBuffer::new(&self.display, PrimitiveType::LinesList, &indices).unwrap(),
-                Indices::TriangleFace(indices) => 
-                    IndexBuffer::new(&self.display, PrimitiveType::TrianglesList, &indices).unwrap(),
-            };
+            let index_buffer = index_buffer(&self.display, &mesh.indices);
 
+            scene_buffer.skinned_meshes.remove(name);
             scene_buffer.meshes.insert(name.to_string(), RenderMesh::new(vertex_buffer, index_buffer));
+            scene_buffer.batches_dirty = true;
         } 
     }
 
-    fn update_material(&mut self,name: &str, material: &Material) {
+    fn remove_mesh(&mut self, name: &str) {
         if let Some(scene_buffer) = Rc::get_mut(&mut self.data_buffer.scene_buffer) {
-            scene_buffer.materials.insert(name.to_string(), material.clone());
+            scene_buffer.meshes.remove(name);
+            scene_buffer.skinned_meshes.remove(name);
+            scene_buffer.batches_dirty = true;
         }
     }
 
-    fn remove_texture(&mut self, name: &str) {
-        self.data_buffer.texture_buffer.remove(name);
+    fn update_mesh_vertices(&mut self, name: &str, vertices: &[Vertex]) {
+        if let Some(scene_buffer) = Rc::get_mut(&mut self.data_buffer.scene_buffer) {
+            if let Some(mesh) = scene_buffer.meshes.get_mut(name) {
+                if mesh.vertex_buffer.len() == vertices.len() {
+                    mesh.vertex_buffer.write(vertices);
+                } else {
+                    mesh.vertex_buffer = VertexBuffer::dynamic(&self.display, vertices).unwrap();
+                }
+            }
+        }
     }
 
-    fn update_camera(&mut self, camera: &Camera) {
-        self.data_buffer.camera = camera.clone();
+    fn update_skinned_mesh(&mut self, name: &str, mesh: &GSkinnedMesh) {
+        if let Some(scene_buffer) = Rc::get_mut(&mut self.data_buffer.scene_buffer) {
+
+            let vertex_buffer = VertexBuffer::new(&self.display, &mesh.vertices).unwrap();
+            let index_buffer = index_buffer(&self.display, &mesh.indices);
+
+            scene_buffer.meshes.remove(name);
+            scene_buffer.skinned_meshes.insert(name.to_string(), RenderSkinnedMesh::new(vertex_buffer, index_buffer));
+            scene_buffer.batches_dirty = true;
+        }
     }
 
-    fn update_light(&mut self, name: &str, light: &Light) {
-        let light_buffer = Rc::get_mut(&mut self.data_buffer.light_buffer).unwrap();
-        if let None = light_buffer.lights.insert(name.to_string(), light.clone()) {
-            use glium::texture::*;
+    fn update_skinned_mesh_vertices(&mut self, name: &str, vertices: &[SkinnedVertex]) {
+        if let Some(scene_buffer) = Rc::get_mut(&mut self.data_buffer.scene_buffer) {
+            if let Some(mesh) = scene_buffer.skinned_meshes.get_mut(name) {
+                if mesh.vertex_buffer.len() == vertices.len() {
+                    mesh.vertex_buffer.write(vertices);
+                } else {
+                    mesh.vertex_buffer = VertexBuffer::dynamic(&self.display, vertices).unwrap();
+                }
+            }
+        }
+    }
 
-            let shadow_map_size = light_buffer.shadow_map_size;
-            let shadow_map = 
-                DepthTexture2d::empty(&self.display, shadow_map_size, shadow_map_size).unwrap();
-            light_buffer.shadow_maps.insert(name.to_string(), shadow_map);
+    fn update_skin(&mut self, name: &str, joint_matrices: &[Mat4f]) {
+        if let Some(scene_buffer) = Rc::get_mut(&mut self.data_buffer.scene_buffer) {
+            /* the uniform block has fixed size, joints out of range are dropped. the padding is zero,
+               the shader ignores joints without matrix like `skin_vertices` */
+            let mut matrices: Vec<Mat4f> = joint_matrices.iter().take(JOINTS_MAX_NUMBER).cloned().collect();
+            matrices.resize(JOINTS_MAX_NUMBER, [[0.0; 4]; 4]);
 
-            let lights: Vec<Light> =light_buffer.lights.values().map(|x| {*x}).collect();
-            light_buffer.buffer = 
-                Buffer::new(
+            if let Some(buffer) = scene_buffer.skins.get_mut(name) {
+                buffer.write(matrices.as_slice());
+            } else {
+                let buffer = Buffer::new(
                     &self.display, 
-                    lights.as_slice(), 
+                    matrices.as_slice(), 
                     BufferType::UniformBuffer, 
-                    BufferMode::default()
+                    BufferMode::Dynamic
                 ).unwrap();
+                scene_buffer.skins.insert(name.to_string(), buffer);
+            }
         }
     }
 
+    fn update_material_definition(&mut self, definition: &MaterialDefinition) {
+        self.material_definitions.insert(definition.clone());
+    }
+
+    fn update_material(&mut self,name: &str, material: &Material) -> Result<(), MaterialErr> {
+        let material = self.material_definitions.validate(material)?;
+        let instances = self.material_instances.resolve_instances(name, &material, &self.material_definitions)?;
+
+        let scene_buffer = match Rc::get_mut(&mut self.data_buffer.scene_buffer) {
+            Some(scene_buffer) => scene_buffer,
+            None => return Err(MaterialErr::BusyErr),
+        };
+
+        self.material_instances.remove(name);
+        scene_buffer.material_parents.remove(name);
+        scene_buffer.materials.insert(name.to_string(), material);
+
+        for (instance_name, instance_material) in instances {
+            scene_buffer.materials.insert(instance_name, instance_material);
+        }
+
+        scene_buffer.batches_dirty = true;
+
+        Ok(())
+    }
+
+    fn update_material_instance(&mut self, name: &str, instance: &MaterialInstance) -> Result<(), MaterialErr> {
+        let scene_buffer = match Rc::get_mut(&mut self.data_buffer.scene_buffer) {
+            Some(scene_buffer) => scene_buffer,
+            None => return Err(MaterialErr::BusyErr),
+        };
+
+        let material = self.material_instances.resolve(name, instance, &scene_buffer.materials, &self.material_definitions)?;
+        scene_buffer.materials.insert(name.to_string(), material);
+        scene_buffer.material_parents.insert(name.to_string(), instance.parent.clone());
+        scene_buffer.batches_dirty = true;
+        self.material_instances.insert(name, instance.clone());
+
+        Ok(())
+    }
+
+    fn remove_material(&mut self, name: &str) {
+        if let Some(scene_buffer) = Rc::get_mut(&mut self.data_buffer.scene_buffer) {
+            scene_buffer.materials.remove(name);
+            scene_buffer.material_parents.remove(name);
+
+            for instance_name in self.material_instances.instances_of(name) {
+                scene_buffer.materials.remove(&instance_name);
+                scene_buffer.material_parents.remove(&instance_name);
+            }
+
+            scene_buffer.batches_dirty = true;
+        }
+
+        self.material_instances.remove(name);
+    }
+
+    fn remove_texture(&mut self, name: &str) {
+        self.data_buffer.texture_buffer.remove(name);
+    }
+
+    fn update_camera(&mut self, camera: &Camera) {
+        self.data_buffer.camera = camera.clone();
+    }
+
+    fn update_light(&mut self, name: &str, light: &Light) {
+        let light_buffer = Rc::get_mut(&mut self.data_buffer.light_buffer).unwrap();
+        light_buffer.lights.insert(name.to_string(), light.clone());
+        light_buffer.rebuild(&self.display);
+    }
+
     fn remove_light(&mut self, name: &str) {
         let light_buffer = Rc::get_mut(&mut self.data_buffer.light_buffer).unwrap();
         if let Some(_) = light_buffer.lights.remove(name) {
-            light_buffer.shadow_maps.remove(name);
-            let lights: Vec<Light> =light_buffer.lights.values().map(|x| {*x}).collect();
-            light_buffer.buffer = 
-                Buffer::new(
-                    &self.display, 
-                    lights.as_slice(), 
-                    BufferType::UniformBuffer, 
-                    BufferMode::default()
-                ).unwrap();
+            light_buffer.rebuild(&self.display);
         }
     }
 }
 
+fn index_buffer(display: &GLDisplay, indices: &Indices) -> IndexBuffer<u32> {
+    match indices {
+        Indices::Points(indices) => 
+            IndexBuffer::new(display, PrimitiveType::Points, &indices).unwrap(),
+        Indices::EdgeLists(indices) => 
+            IndexBuffer::new(display, PrimitiveType::LinesList, &indices).unwrap(),
+        Indices::TriangleFace(indices) => 
+            IndexBuffer::new(display, PrimitiveType::TrianglesList, &indices).unwrap(),
+    }
+}
+
 use glium::texture::{ClientFormat,UncompressedFloatFormat};
 
 impl From<ImageType> for ClientFormat {
@@ -157,6 +363,7 @@ impl From<ImageType> for ClientFormat {
             ImageType::U8 => ClientFormat::U8,
             ImageType::U8U8U8 => ClientFormat::U8U8U8,
             ImageType::U8U8U8U8 => ClientFormat::U8U8U8U8,
+            ImageType::F32F32F32 => ClientFormat::F32F32F32,
         }
     }
 }
@@ -167,6 +374,7 @@ impl From<ImageType> for UncompressedFloatFormat {
             ImageType::U8 => UncompressedFloatFormat::U8,
             ImageType::U8U8U8 => UncompressedFloatFormat::U8U8U8,
             ImageType::U8U8U8U8 => UncompressedFloatFormat::U8U8U8U8,
+            ImageType::F32F32F32 => UncompressedFloatFormat::F32F32F32,
         }
     }
 }
\ No newline at end of file
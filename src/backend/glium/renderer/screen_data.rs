@@ -1,20 +1,28 @@
 use crate::base::Position;
-use glium::Display;
+use super::display::GLDisplay;
 use glium::{VertexBuffer, IndexBuffer, Program};
 
 pub struct ScreenData {
     pub vertex_buffer: VertexBuffer<Position>,
     pub index_buffer: IndexBuffer<u32>,
     pub shadow_map: Program,
+    pub skinned_shadow_map: Program,
+    pub instanced_shadow_map: Program,
+    /// distance to light of point light shadows
+    pub point_shadow_map: Program,
+    pub skinned_point_shadow_map: Program,
+    pub instanced_point_shadow_map: Program,
     pub color: Program,
     pub image: Program,
     pub font: Program,
+    pub oit_composite: Program,
 }
 
 use crate::renderer::pipeline::shader::glsl::*;
+use crate::animation::JOINTS_MAX_NUMBER;
 
 impl ScreenData {
-    pub fn new(display: &Display) -> Self {
+    pub fn new(display: &GLDisplay) -> Self {
         let vertex_buffer = glium::VertexBuffer::new(
             display,
             &[Position::new([-1.0, -1.0], [0.0, 0.0]),
@@ -31,13 +39,31 @@ impl ScreenData {
         let color_code = glsl(version.clone(), String::new(), color());
         let image_code = glsl(version.clone(), String::new(), image());
         let font_code = glsl(version.clone(), String::new(), font());
+        let oit_composite_code = glsl(version.clone(), String::new(), oit_composite());
 
-        let shadow_map_vert = glsl(version.clone(),vert_lib(),shadow_map_vert());
-        let shadow_map_frag = glsl(version, String::new(), shadow_map_frag());
+        let shadow_map_frag = glsl(version.clone(), String::new(), shadow_map_frag());
+        let point_shadow_frag = glsl(version.clone(), String::new(), point_shadow_frag());
 
-        let shadow_map = Program::from_source(
-            display, shadow_map_vert.as_str(), shadow_map_frag.as_str(), None
-        ).unwrap();
+        /* static, skinned and instanced variants of the shadow programs */
+        let vert_libs = [
+            vert_lib(),
+            skinned_vert_lib(JOINTS_MAX_NUMBER as u16),
+            instanced_vert_lib(),
+        ];
+
+        let [shadow_map, skinned_shadow_map, instanced_shadow_map] = vert_libs.clone().map(|lib| {
+            let shadow_map_vert = glsl(version.clone(), lib, shadow_map_vert());
+            Program::from_source(
+                display, shadow_map_vert.as_str(), shadow_map_frag.as_str(), None
+            ).unwrap()
+        });
+
+        let [point_shadow_map, skinned_point_shadow_map, instanced_point_shadow_map] = vert_libs.map(|lib| {
+            let point_shadow_vert = glsl(version.clone(), lib, point_shadow_vert());
+            Program::from_source(
+                display, point_shadow_vert.as_str(), point_shadow_frag.as_str(), None
+            ).unwrap()
+        });
 
         let color = Program::from_source(
             display, position.as_str(), color_code.as_str(), None
@@ -51,13 +77,23 @@ impl ScreenData {
             display, position.as_str(), font_code.as_str(), None
         ).unwrap();
 
+        let oit_composite = Program::from_source(
+            display, position.as_str(), oit_composite_code.as_str(), None
+        ).unwrap();
+
         Self {
             vertex_buffer,
             index_buffer,
             shadow_map,
+            skinned_shadow_map,
+            instanced_shadow_map,
+            point_shadow_map,
+            skinned_point_shadow_map,
+            instanced_point_shadow_map,
             color,
             font,
             image,
+            oit_composite,
         }
     }
 }
\ No newline at end of file
@@ -0,0 +1,229 @@
+use super::renderer::GLRenderer;
+use super::pipeline::uniforms::CameraMatrix;
+use super::buffer::SceneBuffer;
+use super::render_pass::{Programs, draw_material_objects};
+use crate::renderer::{ShadowConfig, ShadowData, LightShadow, PointShadow, light_shadow, point_shadow};
+use crate::base::transform::IDENTITY;
+use glium::{draw_parameters::*, Surface};
+use glium::buffer::{Buffer, BufferType, BufferMode};
+use glium::framebuffer::SimpleFrameBuffer;
+use glium::texture::{DepthTexture2dArray, DepthCubemapArray, CubeLayer, DepthFormat, MipmapsOption};
+use glium::program::Program;
+use glium::uniforms::{Uniforms, UniformValue, AsUniformValue};
+use glium::uniforms::UniformBuffer;
+use rmu::raw::Mat4f;
+use std::rc::Rc;
+
+impl GLRenderer {
+    /// the shadow maps are allocated again on next `shadow_map` if the size is changed
+    pub fn set_shadow_config(&mut self, config: ShadowConfig) {
+        self.shadow_config = config;
+    }
+
+    pub fn shadow_config(&self) -> &ShadowConfig {
+        &self.shadow_config
+    }
+
+    /* one layer of the shadow map array for each spot light and each cascade of parallel lights and
+       one cube of the cube map array for each point light, matrices of cascades are fit to the current
+       camera so shadow maps are drawn every frame */
+    pub(super) fn render_shadow_maps(&mut self) {
+        self.update_batches();
+
+        let config = self.shadow_config;
+        let camera = self.data_buffer.camera.clone();
+        let light_buffer = Rc::get_mut(&mut self.data_buffer.light_buffer).unwrap();
+
+        let lights = light_buffer.sorted_lights();
+        let shadows: Vec<Option<LightShadow>> = lights
+            .iter()
+            .map(|light| light_shadow(light, &camera, &config))
+            .collect();
+        let point_shadows: Vec<Option<PointShadow>> = lights
+            .iter()
+            .map(|light| point_shadow(light, &config))
+            .collect();
+
+        let layers = shadows
+            .iter()
+            .flatten()
+            .map(|shadow| shadow.matrices.len() as u32)
+            .sum::<u32>()
+            .max(1);
+
+        let size = config.map_size.max(1);
+        let shadow_maps = &light_buffer.shadow_maps;
+        if shadow_maps.get_width() != size || shadow_maps.get_height() != Some(size) || shadow_maps.get_array_size() != Some(layers) {
+            light_buffer.shadow_maps = DepthTexture2dArray::empty_with_format(
+                &self.display,
+                DepthFormat::I24,
+                MipmapsOption::NoMipmap,
+                size,
+                size,
+                layers
+            ).unwrap();
+        }
+
+        let cubes = point_shadows.iter().flatten().count().max(1) as u32;
+        let point_shadow_maps = &light_buffer.point_shadow_maps;
+        if point_shadow_maps.get_width() != size || point_shadow_maps.get_array_size() != Some(cubes) {
+            light_buffer.point_shadow_maps = DepthCubemapArray::empty_with_format(
+                &self.display,
+                DepthFormat::F32,
+                MipmapsOption::NoMipmap,
+                size,
+                cubes
+            ).unwrap();
+        }
+
+        let parameters = DrawParameters {
+            depth: Depth {
+                test: DepthTest::IfLessOrEqual,
+                write: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let scene_buffer = &self.data_buffer.scene_buffer;
+        let screen_data = &self.screen_data;
+        let programs = Programs {
+            shader: &screen_data.shadow_map,
+            skinned: Some(&screen_data.skinned_shadow_map),
+            instanced: Some(&screen_data.instanced_shadow_map),
+        };
+        let point_programs = Programs {
+            shader: &screen_data.point_shadow_map,
+            skinned: Some(&screen_data.skinned_point_shadow_map),
+            instanced: Some(&screen_data.instanced_point_shadow_map),
+        };
+
+        let mut shadow_data = Vec::new();
+        let mut layer = 0;
+        let mut cube = 0;
+
+        for (shadow, point) in shadows.iter().zip(point_shadows.iter()) {
+            if let Some(point) = point {
+                shadow_data.push(ShadowData::point(point, cube, &config));
+
+                let target = light_buffer.point_shadow_maps.main_level().layer(cube).unwrap();
+                for (face, matrix) in CUBE_LAYERS.iter().zip(point.matrices.iter()) {
+                    let light_camera_matrix = light_camera(&self.display, *matrix);
+
+                    let mut frame = SimpleFrameBuffer::depth_only(&self.display, target.image(*face)).unwrap();
+                    frame.clear_depth(1.0);
+
+                    use glium::uniform;
+
+                    draw_shadow_casters(&mut frame, scene_buffer, point_programs, &parameters, |transform| uniform! {
+                        Camera: &light_camera_matrix,
+                        transform: transform,
+                        light_position: point.position,
+                        far: point.far,
+                    });
+                }
+
+                cube += 1;
+                continue;
+            }
+
+            let shadow = match shadow {
+                Some(shadow) => shadow,
+                None => {
+                    shadow_data.push(ShadowData::none());
+                    continue;
+                },
+            };
+
+            shadow_data.push(ShadowData::new(shadow, layer, &config));
+
+            for matrix in shadow.matrices.iter() {
+                let light_camera_matrix = light_camera(&self.display, *matrix);
+
+                let target = light_buffer.shadow_maps.main_level().layer(layer).unwrap();
+                let mut frame = SimpleFrameBuffer::depth_only(&self.display, target).unwrap();
+                frame.clear_depth(1.0);
+
+                use glium::uniform;
+
+                draw_shadow_casters(&mut frame, scene_buffer, programs, &parameters, |transform| uniform! {
+                    Camera: &light_camera_matrix,
+                    transform: transform,
+                });
+
+                layer += 1;
+            }
+        }
+
+        if shadow_data.is_empty() {
+            shadow_data.push(ShadowData::none());
+        }
+
+        light_buffer.shadow_buffer = Buffer::new(
+            &self.display,
+            shadow_data.as_slice(),
+            BufferType::UniformBuffer,
+            BufferMode::default()
+        ).unwrap();
+    }
+}
+
+const CUBE_LAYERS: [CubeLayer;6] = [
+    CubeLayer::PositiveX,
+    CubeLayer::NegativeX,
+    CubeLayer::PositiveY,
+    CubeLayer::NegativeY,
+    CubeLayer::PositiveZ,
+    CubeLayer::NegativeZ,
+];
+
+/* light space matrix is the projection, the view is identity */
+fn light_camera(display: &super::display::GLDisplay, matrix: rmu::raw::Mat4f) -> UniformBuffer<CameraMatrix> {
+    UniformBuffer::new(
+        display,
+        CameraMatrix {
+            project: matrix,
+            view: IDENTITY,
+        }
+    ).unwrap()
+}
+
+/* opaque and alpha test objects cast shadows, skinned meshes and objects with instances are drawn with
+   their variants of the program */
+pub(super) fn draw_shadow_casters<S: Surface, U: Uniforms, F: Fn(Mat4f) -> U>(
+    frame       : &mut S,
+    scene_buffer: &SceneBuffer,
+    programs    : Programs,
+    parameters  : &DrawParameters,
+    uniforms    : F,
+) {
+    let caster_uniforms = |transform, skin| CasterUniforms {
+        uniforms: uniforms(transform),
+        skin,
+    };
+
+    for (material_name, objects) in scene_buffer.same_material_objects.iter() {
+        match scene_buffer.materials.get(material_name) {
+            Some(material) if !material.is_transparent() => (),
+            _ => continue,
+        }
+
+        draw_material_objects(frame, scene_buffer, material_name, objects.keys(), programs, parameters, &caster_uniforms);
+    }
+}
+
+/* uniforms of a shadow caster with the joint matrices of skinned meshes */
+struct CasterUniforms<'a, U> {
+    uniforms: U,
+    skin: Option<&'a Buffer<[Mat4f]>>,
+}
+
+impl<'a, U: Uniforms> Uniforms for CasterUniforms<'a, U> {
+    fn visit_values<'b, F: FnMut(&str, UniformValue<'b>)>(&'b self, mut output: F) {
+        self.uniforms.visit_values(&mut output);
+
+        if let Some(skin) = &self.skin {
+            output("Skin", skin.as_uniform_value());
+        }
+    }
+}
@@ -0,0 +1,219 @@
+use super::renderer::GLRenderer;
+use super::display::GLDisplay;
+use super::pipeline::uniforms::{SceneUniformData, SceneSsao};
+use crate::renderer::{SsaoConfig, SSAO_MAX_SAMPLES, ssao_kernel, ssao_noise};
+use crate::renderer::pipeline::shader::glsl::*;
+use glium::program::Program;
+use glium::{draw_parameters::*, Surface};
+use glium::buffer::{Buffer, BufferType, BufferMode};
+use glium::texture::{Texture2d, RawImage2d, ClientFormat, UncompressedFloatFormat, MipmapsOption};
+use glium::uniforms::{Sampler, SamplerWrapFunction, MagnifySamplerFilter, MinifySamplerFilter};
+use glium::implement_uniform_block;
+use rmu::raw::Vec4f;
+use std::rc::Rc;
+
+/// width and height of the tiled noise texture
+pub const SSAO_NOISE_SIZE: u32 = 4;
+
+#[derive(Copy,Clone)]
+pub struct SsaoSample {
+    pub position: Vec4f,
+}
+
+implement_uniform_block!(SsaoSample, position);
+
+/// # Ssao Data
+/// programs, kernel and targets of screen space ambient occlusion,
+/// the targets are 1 x 1 until the occlusion is enabled
+pub struct SsaoData {
+    pub occlusion: Program,
+    pub blur: Program,
+    /// `SSAO_MAX_SAMPLES` samples, the kernel of the sample count is followed by zeros
+    pub kernel: Buffer<[SsaoSample]>,
+    pub kernel_samples: u32,
+    pub noise: Texture2d,
+    /// occlusion before the blur
+    pub raw: Texture2d,
+    pub ambient_occlusion: Texture2d,
+}
+
+impl SsaoData {
+    pub fn new(display: &GLDisplay) -> Self {
+        let version = glsl_version(4, 60);
+        let vertex_shader = glsl(version.clone(), vert_lib(), deferred_vert());
+        let program = |pass: &str| {
+            let fragment_shader = glsl(version.clone(), String::new(), ssao(pass, SSAO_MAX_SAMPLES));
+            Program::from_source(display, vertex_shader.as_str(), fragment_shader.as_str(), None).unwrap()
+        };
+
+        let samples = SsaoConfig::default().samples;
+
+        Self {
+            occlusion: program("SSAO"),
+            blur: program("SSAO_BLUR"),
+            kernel: kernel_buffer(display, samples),
+            kernel_samples: samples,
+            noise: noise_texture(display),
+            raw: occlusion_target(display, (1, 1)),
+            ambient_occlusion: occlusion_target(display, (1, 1)),
+        }
+    }
+
+    pub fn uniforms(&self, enable: bool) -> SceneSsao<'_> {
+        SceneSsao {
+            enable,
+            ssao_map: Sampler::new(&self.ambient_occlusion)
+                .wrap_function(SamplerWrapFunction::Clamp)
+                .magnify_filter(MagnifySamplerFilter::Linear)
+                .minify_filter(MinifySamplerFilter::Linear),
+        }
+    }
+}
+
+impl GLRenderer {
+    pub fn ssao_config(&self) -> Option<SsaoConfig> {
+        self.ssao_config
+    }
+
+    /* the kernel is built again when the sample count changes */
+    pub(super) fn load_ssao(&mut self, config: Option<SsaoConfig>) {
+        if let Some(config) = config {
+            let samples = config.samples.max(1).min(SSAO_MAX_SAMPLES as u32);
+
+            if samples != self.ssao_data.kernel_samples {
+                if let Some(ssao_data) = Rc::get_mut(&mut self.ssao_data) {
+                    ssao_data.kernel = kernel_buffer(&self.display, samples);
+                    ssao_data.kernel_samples = samples;
+                }
+            }
+        }
+
+        self.ssao_config = config;
+    }
+
+    /* targets are allocated again when the frame is resized, before they are referenced by the scene uniforms */
+    pub(super) fn prepare_ssao(&mut self) {
+        if self.ssao_config.is_none() {
+            return;
+        }
+
+        let dimensions = self.display.get_framebuffer_dimensions();
+
+        if self.ssao_data.ambient_occlusion.dimensions() == dimensions {
+            return;
+        }
+
+        if let Some(ssao_data) = Rc::get_mut(&mut self.ssao_data) {
+            ssao_data.raw = occlusion_target(&self.display, dimensions);
+            ssao_data.ambient_occlusion = occlusion_target(&self.display, dimensions);
+        }
+    }
+
+    /* occlusion of the g-buffer depth and normals, blurred into the map sampled by lighting shaders */
+    pub(super) fn render_ssao(&mut self, uniform_data: &SceneUniformData) {
+        let config = match self.ssao_config {
+            Some(config) => config,
+            None => return,
+        };
+
+        let (g_depth, g_normal) = match (self.data_buffer.texture_buffer.get("g_depth"), self.data_buffer.texture_buffer.get("g_normal")) {
+            (Some(g_depth), Some(g_normal)) => (g_depth, g_normal),
+            _ => return,
+        };
+
+        let ssao_data = &self.ssao_data;
+        let camera = &self.data_buffer.camera;
+
+        let nearest = |texture| Sampler::new(texture)
+            .wrap_function(SamplerWrapFunction::Clamp)
+            .magnify_filter(MagnifySamplerFilter::Nearest)
+            .minify_filter(MinifySamplerFilter::Nearest);
+
+        use glium::uniform;
+
+        let uniforms = uniform! {
+            Camera: uniform_data.matrix,
+            SsaoKernel: &ssao_data.kernel,
+            g_depth: nearest(g_depth),
+            g_normal: nearest(g_normal),
+            noise: Sampler::new(&ssao_data.noise)
+                .wrap_function(SamplerWrapFunction::Repeat)
+                .magnify_filter(MagnifySamplerFilter::Nearest)
+                .minify_filter(MinifySamplerFilter::Nearest),
+            camera_view: camera.view(),
+            camera_project: camera.project(),
+            samples: ssao_data.kernel_samples as i32,
+            radius: config.radius,
+            bias: config.bias,
+            intensity: config.intensity,
+        };
+
+        ssao_data.raw.as_surface().draw(
+            &self.screen_data.vertex_buffer,
+            &self.screen_data.index_buffer,
+            &ssao_data.occlusion,
+            &uniforms,
+            &DrawParameters::default()
+        ).unwrap();
+
+        let uniforms = uniform! {
+            Camera: uniform_data.matrix,
+            g_depth: nearest(g_depth),
+            ssao_raw: nearest(&ssao_data.raw),
+            camera_project: camera.project(),
+            blur_radius: config.blur_radius as i32,
+        };
+
+        ssao_data.ambient_occlusion.as_surface().draw(
+            &self.screen_data.vertex_buffer,
+            &self.screen_data.index_buffer,
+            &ssao_data.blur,
+            &uniforms,
+            &DrawParameters::default()
+        ).unwrap();
+    }
+}
+
+fn kernel_buffer(display: &GLDisplay, samples: u32) -> Buffer<[SsaoSample]> {
+    let mut kernel: Vec<SsaoSample> = ssao_kernel(samples as usize)
+        .into_iter()
+        .map(|[x, y, z]| SsaoSample { position: [x, y, z, 0.0] })
+        .collect();
+    kernel.resize(SSAO_MAX_SAMPLES, SsaoSample { position: [0.0;4] });
+
+    Buffer::new(display, kernel.as_slice(), BufferType::UniformBuffer, BufferMode::default()).unwrap()
+}
+
+fn noise_texture(display: &GLDisplay) -> Texture2d {
+    use std::borrow::Cow;
+
+    let data: Vec<f32> = ssao_noise(SSAO_NOISE_SIZE as usize)
+        .iter()
+        .flat_map(|x| x.iter().cloned().collect::<Vec<f32>>())
+        .collect();
+
+    Texture2d::with_format(
+        display,
+        RawImage2d {
+            data: Cow::Owned(data),
+            width: SSAO_NOISE_SIZE,
+            height: SSAO_NOISE_SIZE,
+            format: ClientFormat::F32F32F32,
+        },
+        UncompressedFloatFormat::F32F32F32,
+        MipmapsOption::NoMipmap,
+    ).unwrap()
+}
+
+/* occlusion is 1.0 where the targets are not drawn */
+fn occlusion_target(display: &GLDisplay, dimensions: (u32,u32)) -> Texture2d {
+    let texture = Texture2d::empty_with_format(
+        display,
+        UncompressedFloatFormat::F16,
+        MipmapsOption::NoMipmap,
+        dimensions.0,
+        dimensions.1
+    ).unwrap();
+    texture.as_surface().clear_color(1.0, 1.0, 1.0, 1.0);
+    texture
+}
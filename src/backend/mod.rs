@@ -1 +1,3 @@
-pub mod glium;
\ No newline at end of file
+pub mod glium;
+pub mod software;
+pub mod recording;
@@ -0,0 +1,38 @@
+use crate::application::*;
+
+use super::event::ScriptedEventSystem;
+use super::renderer::RecordingRenderer;
+
+/// application of the recording renderer and scripted events,
+/// push the events to `event_system` before running the loop
+pub struct RecordingApplication {
+    pub config: Config,
+    pub event_system: ScriptedEventSystem,
+    pub renderer: RecordingRenderer,
+}
+
+impl Application<RecordingRenderer,ScriptedEventSystem> for RecordingApplication {
+    fn new(config: Config) -> RecordingApplication {
+        RecordingApplication {
+            event_system: ScriptedEventSystem::new(),
+            renderer: RecordingRenderer::new(config.clone()),
+            config
+        }
+    }
+
+    /// the loop also ends when the scripted events are finished, so it always terminates
+    fn rendering_loop<F: FnMut(&mut RecordingRenderer,&mut ScriptedEventSystem,&mut LoopControl)>(&mut self,mut f: F) {
+        let mut control = LoopControl::Continue;
+        while control != LoopControl::Exit {
+            f(&mut self.renderer,&mut self.event_system,&mut control);
+
+            if self.event_system.is_finished() {
+                break;
+            }
+        }
+    }
+
+    fn rendering<F: FnMut(&mut RecordingRenderer, &mut ScriptedEventSystem)>( &mut self, mut f: F) {
+        f(&mut self.renderer, &mut self.event_system);
+    }
+}
@@ -0,0 +1,88 @@
+use rmu::raw::{Vec2f, Vec3f, Vec4f, Mat4f};
+use crate::base::{Vertex, SkinnedVertex, Indices, material::Material, material_definition::MaterialDefinition, material_instance::MaterialInstance};
+use crate::renderer::{Object, Instance, Light, Image, Scale, Align, SsaoConfig};
+
+/// a call of the renderer and canvas traits, uploads own a copy of their data
+#[derive(Debug,Clone,PartialEq)]
+pub enum Command {
+    /* Renderer */
+    BackgroundColor(Vec4f),
+    SetAntialiasing(bool),
+    SetHdr(bool),
+    SetGamma(f32),
+    SetOrderIndependentTransparency(bool),
+    SetEnvironment(Image),
+    SetSsao(Option<SsaoConfig>),
+    ReadFrame,
+    ReadTarget(String),
+    /* RenderProdure */
+    Clear,
+    ShadowMap,
+    Render,
+    SwapBuffer,
+    /* RendererManager */
+    UpdateCamera { look_from: Vec3f, view: Mat4f, project: Mat4f },
+    UpdateMesh { name: String, vertices: Vec<Vertex>, indices: Indices },
+    RemoveMesh(String),
+    UpdateMeshVertices { name: String, vertices: Vec<Vertex> },
+    UpdateSkinnedMesh { name: String, vertices: Vec<SkinnedVertex>, indices: Indices },
+    UpdateSkinnedMeshVertices { name: String, vertices: Vec<SkinnedVertex> },
+    UpdateSkin { name: String, joint_matrices: Vec<Mat4f> },
+    UpdateMaterialDefinition(MaterialDefinition),
+    UpdateMaterial { name: String, material: Material },
+    UpdateMaterialInstance { name: String, instance: MaterialInstance },
+    RemoveMaterial(String),
+    /// the object is recorded before its update flag is reset
+    UpdateObject { name: String, object: Object },
+    RemoveObject(String),
+    UpdateInstances { name: String, instances: Vec<Instance> },
+    RemoveInstances(String),
+    UpdateLight { name: String, light: Light },
+    RemoveLight(String),
+    UpdateTexture { name: String, image: Image },
+    RemoveTexture(String),
+    UpdateCubemap { name: String, faces: Vec<Image> },
+    RemoveCubemap(String),
+    /* Canvas */
+    Init,
+    Finish,
+    /* GraphicsPaint */
+    SetSize(f32),
+    SetLineWidth(f32),
+    SetColor(Vec4f),
+    DrawPoints(Vec<Vec2f>),
+    DrawLine(Vec<Vec2f>),
+    DrawPolygon(Vec<Vec2f>),
+    DrawPolygonFill(Vec<Vec2f>),
+    DrawImage { position_uvs: Vec<(Vec2f,Vec2f)>, sprite_name: String },
+    LoadSprite { name: String, image: Image },
+    RemoveSprite(String),
+    /* TextPaint */
+    LoadFont { name: String, path: String },
+    SetFont(String),
+    SetFontScale(Scale),
+    SetFontColor(Vec4f),
+    SetFontSpace(f32),
+    SetLineSpace(f32),
+    SetAlign(Align),
+    SetMaxWidth(f32),
+    SetMaxLine(u32),
+    SetPosition(Vec2f),
+    DrawText(String),
+}
+
+impl Command {
+    /// draw calls of canvas and scene rendering
+    pub fn is_draw(&self) -> bool {
+        match self {
+            Command::Render
+            | Command::DrawPoints(_)
+            | Command::DrawLine(_)
+            | Command::DrawPolygon(_)
+            | Command::DrawPolygonFill(_)
+            | Command::DrawImage { .. }
+            | Command::DrawText(_) => true,
+            _ => false,
+        }
+    }
+}
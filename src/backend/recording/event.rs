@@ -0,0 +1,46 @@
+use crate::event::*;
+use std::collections::VecDeque;
+
+/// # Scripted Event System
+/// events are returned in the order they are pushed, `Event::Other` when the script is finished
+pub struct ScriptedEventSystem {
+    pub event_queue: VecDeque<Event>,
+    pub device_event_queue: VecDeque<Event>,
+}
+
+impl ScriptedEventSystem {
+    pub fn new() -> Self {
+        Self {
+            event_queue: VecDeque::new(),
+            device_event_queue: VecDeque::new(),
+        }
+    }
+
+    pub fn with_events(mut self, events: Vec<Event>) -> Self {
+        self.event_queue.extend(events);
+        self
+    }
+
+    pub fn push_event(&mut self, event: Event) {
+        self.event_queue.push_back(event);
+    }
+
+    pub fn push_device_event(&mut self, event: Event) {
+        self.device_event_queue.push_back(event);
+    }
+
+    /// all scripted events are returned
+    pub fn is_finished(&self) -> bool {
+        self.event_queue.is_empty() && self.device_event_queue.is_empty()
+    }
+}
+
+impl EventSystem for ScriptedEventSystem {
+    fn event(&mut self) -> Event {
+        self.event_queue.pop_front().unwrap_or(Event::Other)
+    }
+
+    fn device_event(&mut self) -> Event {
+        self.device_event_queue.pop_front().unwrap_or(Event::Other)
+    }
+}
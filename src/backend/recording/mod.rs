@@ -0,0 +1,12 @@
+//! null backend for tests, the renderer records a typed log of the calls
+//! and the events are read from a script
+
+pub mod command;
+pub mod renderer;
+pub mod event;
+pub mod application;
+
+pub use command::*;
+pub use renderer::*;
+pub use event::*;
+pub use application::*;
@@ -0,0 +1,384 @@
+use crate::application::Config;
+use crate::base::{camera::Camera, material::Material, material_definition::*, material_instance::*, Vertex, SkinnedVertex};
+use crate::renderer::*;
+use super::command::Command;
+use rmu::raw::{Vec2f, Vec4f, Mat4f};
+use std::collections::HashMap;
+
+/// # Recording Renderer
+/// renderer that draws nothing and records every call as a `Command`,
+/// for testing application code without a graphics context
+pub struct RecordingRenderer {
+    pub config: Config,
+    pub commands: Vec<Command>,
+    /// uploaded textures and sprites, returned by `read_target`
+    pub textures: HashMap<String,Image>,
+    pub bg_color: Vec4f,
+    pub material_definitions: MaterialDefinitions,
+    /// valid materials and instances, instances are validated against them
+    pub materials: HashMap<String,Material>,
+    pub material_instances: MaterialInstances,
+}
+
+impl RecordingRenderer {
+    pub fn new(config: Config) -> Self {
+        Self {
+            config,
+            commands: Vec::new(),
+            textures: HashMap::new(),
+            bg_color: [1.0,1.0,1.0,1.0],
+            material_definitions: MaterialDefinitions::bulidin(),
+            materials: HashMap::new(),
+            material_instances: MaterialInstances::new(),
+        }
+    }
+
+    pub fn commands(&self) -> &[Command] {
+        self.commands.as_slice()
+    }
+
+    /// take the recorded commands and start a new log
+    pub fn take_commands(&mut self) -> Vec<Command> {
+        std::mem::replace(&mut self.commands, Vec::new())
+    }
+
+    pub fn clear_commands(&mut self) {
+        self.commands.clear();
+    }
+
+    #[inline]
+    fn record(&mut self, command: Command) {
+        self.commands.push(command);
+    }
+}
+
+impl Renderer for RecordingRenderer {
+    fn background_color(&mut self, color: Vec4f) {
+        self.bg_color = color;
+        self.record(Command::BackgroundColor(color));
+    }
+
+    fn set_antialiasing(&mut self, enable: bool) {
+        self.record(Command::SetAntialiasing(enable));
+    }
+
+    fn set_hdr(&mut self, enable: bool) {
+        self.record(Command::SetHdr(enable));
+    }
+
+    fn set_gamma(&mut self, gamma: f32) {
+        self.record(Command::SetGamma(gamma));
+    }
+
+    fn set_order_independent_transparency(&mut self, enable: bool) {
+        self.record(Command::SetOrderIndependentTransparency(enable));
+    }
+
+    fn set_environment(&mut self, image: &Image) {
+        self.record(Command::SetEnvironment(image.clone()));
+    }
+
+    fn set_ssao(&mut self, config: Option<SsaoConfig>) {
+        self.record(Command::SetSsao(config));
+    }
+
+    /// frame of the config size filled with the background color
+    fn read_frame(&mut self) -> Image {
+        self.record(Command::ReadFrame);
+
+        let (width, height) = (self.config.size.width as u32, self.config.size.height as u32);
+        let pixel: Vec<u8> = self.bg_color.iter().map(|x| (x.max(0.0).min(1.0) * 255.0).round() as u8).collect();
+
+        Image {
+            data: pixel.repeat((width * height) as usize),
+            dimensions: (width, height),
+            image_type: ImageType::U8U8U8U8,
+        }
+    }
+
+    fn read_target(&mut self, name: &str) -> Option<Image> {
+        self.record(Command::ReadTarget(name.to_string()));
+        self.textures.get(name).cloned()
+    }
+}
+
+impl RenderProdure for RecordingRenderer {
+    fn clear(&mut self) {
+        self.record(Command::Clear);
+    }
+
+    fn shadow_map(&mut self) {
+        self.record(Command::ShadowMap);
+    }
+
+    fn render(&mut self) {
+        self.record(Command::Render);
+    }
+
+    fn swap_buffer(&mut self) {
+        self.record(Command::SwapBuffer);
+    }
+}
+
+impl RendererManager for RecordingRenderer {
+    fn update_camera(&mut self, camera: &Camera) {
+        self.record(Command::UpdateCamera {
+            look_from: camera.look_from(),
+            view: camera.view(),
+            project: camera.project(),
+        });
+    }
+
+    fn update_mesh(&mut self, name: &str, mesh: &GMesh) {
+        self.record(Command::UpdateMesh {
+            name: name.to_string(),
+            vertices: mesh.vertices.clone(),
+            indices: mesh.indices.clone(),
+        });
+    }
+
+    fn remove_mesh(&mut self, name: &str) {
+        self.record(Command::RemoveMesh(name.to_string()));
+    }
+
+    fn update_mesh_vertices(&mut self, name: &str, vertices: &[Vertex]) {
+        self.record(Command::UpdateMeshVertices {
+            name: name.to_string(),
+            vertices: vertices.to_vec(),
+        });
+    }
+
+    fn update_skinned_mesh(&mut self, name: &str, mesh: &GSkinnedMesh) {
+        self.record(Command::UpdateSkinnedMesh {
+            name: name.to_string(),
+            vertices: mesh.vertices.clone(),
+            indices: mesh.indices.clone(),
+        });
+    }
+
+    fn update_skinned_mesh_vertices(&mut self, name: &str, vertices: &[SkinnedVertex]) {
+        self.record(Command::UpdateSkinnedMeshVertices {
+            name: name.to_string(),
+            vertices: vertices.to_vec(),
+        });
+    }
+
+    fn update_skin(&mut self, name: &str, joint_matrices: &[Mat4f]) {
+        self.record(Command::UpdateSkin {
+            name: name.to_string(),
+            joint_matrices: joint_matrices.to_vec(),
+        });
+    }
+
+    fn update_material_definition(&mut self, definition: &MaterialDefinition) {
+        self.material_definitions.insert(definition.clone());
+        self.record(Command::UpdateMaterialDefinition(definition.clone()));
+    }
+
+    /// only valid materials are recorded, with the defaults of their definition
+    fn update_material(&mut self, name: &str, material: &Material) -> Result<(), MaterialErr> {
+        let material = self.material_definitions.validate(material)?;
+        let instances = self.material_instances.resolve_instances(name, &material, &self.material_definitions)?;
+        self.material_instances.remove(name);
+
+        self.materials.insert(name.to_string(), material.clone());
+        self.materials.extend(instances);
+        self.record(Command::UpdateMaterial {
+            name: name.to_string(),
+            material,
+        });
+        Ok(())
+    }
+
+    fn update_material_instance(&mut self, name: &str, instance: &MaterialInstance) -> Result<(), MaterialErr> {
+        let material = self.material_instances.resolve(name, instance, &self.materials, &self.material_definitions)?;
+        self.materials.insert(name.to_string(), material);
+        self.material_instances.insert(name, instance.clone());
+        self.record(Command::UpdateMaterialInstance {
+            name: name.to_string(),
+            instance: instance.clone(),
+        });
+        Ok(())
+    }
+
+    fn remove_material(&mut self, name: &str) {
+        self.material_instances.remove(name);
+        self.materials.remove(name);
+
+        for instance_name in self.material_instances.instances_of(name) {
+            self.materials.remove(&instance_name);
+        }
+
+        self.record(Command::RemoveMaterial(name.to_string()));
+    }
+
+    fn update_object(&mut self, name: &str, object: &mut Object) {
+        self.record(Command::UpdateObject {
+            name: name.to_string(),
+            object: object.clone(),
+        });
+
+        // update  finished
+        object.update = DataUpdate::Not;
+    }
+
+    fn remove_object(&mut self, name: &str) {
+        self.record(Command::RemoveObject(name.to_string()));
+    }
+
+    fn update_instances(&mut self, name: &str, instances: &[Instance]) {
+        self.record(Command::UpdateInstances {
+            name: name.to_string(),
+            instances: instances.to_vec(),
+        });
+    }
+
+    fn remove_instances(&mut self, name: &str) {
+        self.record(Command::RemoveInstances(name.to_string()));
+    }
+
+    fn update_light(&mut self, name: &str, light: &Light) {
+        self.record(Command::UpdateLight {
+            name: name.to_string(),
+            light: *light,
+        });
+    }
+
+    fn remove_light(&mut self, name: &str) {
+        self.record(Command::RemoveLight(name.to_string()));
+    }
+
+    fn update_texture(&mut self, name: &str, image: &Image) {
+        self.textures.insert(name.to_string(), image.clone());
+        self.record(Command::UpdateTexture {
+            name: name.to_string(),
+            image: image.clone(),
+        });
+    }
+
+    fn remove_texture(&mut self, name: &str) {
+        self.textures.remove(name);
+        self.record(Command::RemoveTexture(name.to_string()));
+    }
+
+    fn update_cubemap(&mut self, name: &str, faces: &[Image;6]) {
+        self.record(Command::UpdateCubemap {
+            name: name.to_string(),
+            faces: faces.to_vec(),
+        });
+    }
+
+    fn remove_cubemap(&mut self, name: &str) {
+        self.record(Command::RemoveCubemap(name.to_string()));
+    }
+}
+
+impl Canvas for RecordingRenderer {
+    fn init(&mut self) {
+        self.record(Command::Init);
+    }
+
+    fn finish(&mut self) {
+        self.record(Command::Finish);
+    }
+}
+
+impl GraphicsPaint for RecordingRenderer {
+    fn set_size(&mut self, size: f32) {
+        self.record(Command::SetSize(size));
+    }
+
+    fn set_line_width(&mut self, line_width: f32) {
+        self.record(Command::SetLineWidth(line_width));
+    }
+
+    fn set_color(&mut self, color: Vec4f) {
+        self.record(Command::SetColor(color));
+    }
+
+    fn draw_points(&mut self, positions: Vec<Vec2f>) {
+        self.record(Command::DrawPoints(positions));
+    }
+
+    fn draw_line(&mut self, positions: Vec<Vec2f>) {
+        self.record(Command::DrawLine(positions));
+    }
+
+    fn draw_polygon(&mut self, positions: Vec<Vec2f>) {
+        self.record(Command::DrawPolygon(positions));
+    }
+
+    fn draw_polygon_fill(&mut self, positions: Vec<Vec2f>) {
+        self.record(Command::DrawPolygonFill(positions));
+    }
+
+    fn draw_image(&mut self, position_uvs: Vec<(Vec2f,Vec2f)>, sprite_name: &str) {
+        self.record(Command::DrawImage {
+            position_uvs,
+            sprite_name: sprite_name.to_string(),
+        });
+    }
+
+    fn load_sprite(&mut self, name: &str, image: &Image) {
+        self.textures.insert(name.to_string(), image.clone());
+        self.record(Command::LoadSprite {
+            name: name.to_string(),
+            image: image.clone(),
+        });
+    }
+
+    fn remove_sprite(&mut self, name: &str) {
+        self.textures.remove(name);
+        self.record(Command::RemoveSprite(name.to_string()));
+    }
+}
+
+impl TextPaint for RecordingRenderer {
+    /// the font file is not read
+    fn load_font(&mut self, name: &str, path: &str) {
+        self.record(Command::LoadFont {
+            name: name.to_string(),
+            path: path.to_string(),
+        });
+    }
+
+    fn set_font(&mut self, name: &str) {
+        self.record(Command::SetFont(name.to_string()));
+    }
+
+    fn set_font_scale(&mut self, scale: Scale) {
+        self.record(Command::SetFontScale(scale));
+    }
+
+    fn set_font_color(&mut self, color: Vec4f) {
+        self.record(Command::SetFontColor(color));
+    }
+
+    fn set_font_space(&mut self, font_space: f32) {
+        self.record(Command::SetFontSpace(font_space));
+    }
+
+    fn set_line_space(&mut self, line_space: f32) {
+        self.record(Command::SetLineSpace(line_space));
+    }
+
+    fn set_align(&mut self, align: Align) {
+        self.record(Command::SetAlign(align));
+    }
+
+    fn set_max_width(&mut self, max_width: f32) {
+        self.record(Command::SetMaxWidth(max_width));
+    }
+
+    fn set_max_line(&mut self, max_line: u32) {
+        self.record(Command::SetMaxLine(max_line));
+    }
+
+    fn set_position(&mut self, position: Vec2f) {
+        self.record(Command::SetPosition(position));
+    }
+
+    fn draw_text(&mut self, text: &str) {
+        self.record(Command::DrawText(text.to_string()));
+    }
+}
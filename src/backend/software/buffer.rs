@@ -0,0 +1,129 @@
+/// # Software Buffer
+use rmu::raw::{Vec4f, Mat4f};
+use std::collections::HashMap;
+use crate::base::{Vertex, SkinnedVertex, Indices, material::Material, camera::Camera};
+use crate::renderer::{Light, Instance, Image, ImageType};
+
+/// color and depth of a frame, row 0 is the top of the frame
+pub struct FrameBuffer {
+    pub width: u32,
+    pub height: u32,
+    pub color: Vec<Vec4f>,
+    pub depth: Vec<f32>,
+}
+
+impl FrameBuffer {
+    pub fn new(width: u32, height: u32) -> Self {
+        let size = (width * height) as usize;
+        Self {
+            width,
+            height,
+            color: vec![[0.0, 0.0, 0.0, 0.0]; size],
+            depth: vec![1.0; size],
+        }
+    }
+
+    pub fn clear(&mut self, color: Vec4f) {
+        for pixel in self.color.iter_mut() {
+            *pixel = color;
+        }
+        for depth in self.depth.iter_mut() {
+            *depth = 1.0;
+        }
+    }
+
+    #[inline]
+    pub fn index(&self, x: u32, y: u32) -> usize {
+        (y * self.width + x) as usize
+    }
+
+    /// `source * alpha + destination * (1 - alpha)` for both color and alpha, like `Blend::alpha_blending`
+    pub fn blend_alpha(&mut self, x: u32, y: u32, color: Vec4f) {
+        let index = self.index(x, y);
+        let destination = self.color[index];
+        let alpha = color[3];
+
+        let mut result = [0.0; 4];
+        for i in 0..4 {
+            result[i] = color[i] * alpha + destination[i] * (1.0 - alpha);
+        }
+        self.color[index] = result;
+    }
+
+    /// `source * alpha + destination` for color, alpha is kept
+    pub fn blend_additive(&mut self, x: u32, y: u32, color: Vec4f) {
+        let index = self.index(x, y);
+        let destination = &mut self.color[index];
+        for i in 0..3 {
+            destination[i] += color[i] * color[3];
+        }
+    }
+
+    /// RGBA image with rows from top to bottom, colors are clamped to `[0, 1]`
+    pub fn image(&self) -> Image {
+        let data: Vec<u8> = self.color
+            .iter()
+            .flat_map(|pixel| pixel.iter().map(|x| (x.max(0.0).min(1.0) * 255.0).round() as u8).collect::<Vec<u8>>())
+            .collect();
+
+        Image {
+            data,
+            dimensions: (self.width, self.height),
+            image_type: ImageType::U8U8U8U8,
+        }
+    }
+}
+
+pub struct SoftwareMesh {
+    pub vertices: Vec<Vertex>,
+    pub indices: Indices,
+}
+
+pub struct SoftwareSkinnedMesh {
+    pub vertices: Vec<SkinnedVertex>,
+    pub indices: Indices,
+}
+
+pub struct SoftwareObject {
+    pub mesh_name: String,
+    pub material_name: String,
+    pub transform: Mat4f,
+}
+
+pub struct SceneData {
+    pub objects: HashMap<String,SoftwareObject>,
+    pub meshes: HashMap<String,SoftwareMesh>,
+    pub skinned_meshes: HashMap<String,SoftwareSkinnedMesh>,
+    /// joint matrices of objects
+    pub skins: HashMap<String,Vec<Mat4f>>,
+    pub instances: HashMap<String,Vec<Instance>>,
+    pub materials: HashMap<String,Material>,
+    pub lights: HashMap<String,Light>,
+    pub textures: HashMap<String,Image>,
+    pub camera: Camera,
+    pub bg_color: Vec4f,
+}
+
+impl SceneData {
+    pub fn new() -> Self {
+        Self {
+            objects: HashMap::new(),
+            meshes: HashMap::new(),
+            skinned_meshes: HashMap::new(),
+            skins: HashMap::new(),
+            instances: HashMap::new(),
+            materials: HashMap::new(),
+            lights: HashMap::new(),
+            textures: HashMap::new(),
+            camera: Default::default(),
+            bg_color: [1.0,1.0,1.0,1.0],
+        }
+    }
+
+    /// lights ordered by name, so the sum of light is same between runs
+    pub fn sorted_lights(&self) -> Vec<Light> {
+        let mut lights: Vec<(&String,&Light)> = self.lights.iter().collect();
+        lights.sort_by(|a, b| a.0.cmp(b.0));
+        lights.into_iter().map(|(_, light)| *light).collect()
+    }
+}
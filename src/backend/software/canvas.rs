@@ -0,0 +1,12 @@
+use crate::renderer::{Canvas,RenderProdure};
+use super::SoftwareRenderer;
+
+impl Canvas for SoftwareRenderer {
+    fn init(&mut self) {
+        self.clear();
+    }
+
+    fn finish(&mut self) {
+        self.swap_buffer();
+    }
+}
@@ -0,0 +1,159 @@
+use crate::renderer::{GraphicsPaint, Image, ImageType, RendererManager};
+use super::renderer::SoftwareRenderer;
+use super::buffer::FrameBuffer;
+use super::rasterizer::*;
+use rmu::raw::{Vec2f, Vec4f};
+
+impl GraphicsPaint for SoftwareRenderer {
+
+    fn set_size(&mut self, size: f32) {
+        self.graphics_paint.size = size
+    }
+
+    fn set_color(&mut self, color: Vec4f) {
+        self.graphics_paint.color = color
+    }
+
+    fn set_line_width(&mut self, line_width: f32) {
+        self.graphics_paint.line_width = line_width
+    }
+
+    fn draw_points(&mut self, positions: Vec<Vec2f>) {
+        let (size, color) = (self.graphics_paint.size, self.graphics_paint.color);
+
+        if let Some(frame) = &mut self.frame {
+            let (width, height) = (frame.width, frame.height);
+            for position in positions {
+                rasterize_point(&canvas_vertex(position, [0.0, 0.0]), size, width, height, &mut |fragment: &Fragment| {
+                    frame.blend_alpha(fragment.x, fragment.y, color);
+                });
+            }
+        }
+    }
+
+    fn draw_line(&mut self, positions: Vec<Vec2f>) {
+        let (line_width, color) = (self.graphics_paint.line_width, self.graphics_paint.color);
+
+        if let Some(frame) = &mut self.frame {
+            for segment in positions.windows(2) {
+                draw_segment(frame, segment[0], segment[1], line_width, color);
+            }
+        }
+    }
+
+    fn draw_polygon(&mut self, positions: Vec<Vec2f>) {
+        let (line_width, color) = (self.graphics_paint.line_width, self.graphics_paint.color);
+
+        if let Some(frame) = &mut self.frame {
+            for i in 0..positions.len() {
+                let next = (i + 1) % positions.len();
+                if next != i {
+                    draw_segment(frame, positions[i], positions[next], line_width, color);
+                }
+            }
+        }
+    }
+
+    fn draw_polygon_fill(&mut self, positions: Vec<Vec2f>) {
+        let color = self.graphics_paint.color;
+
+        if let Some(frame) = &mut self.frame {
+            let vertices: Vec<ClipVertex> = positions.iter().map(|x| canvas_vertex(*x, [0.0, 0.0])).collect();
+            draw_fan(frame, &vertices, &mut |_| color);
+        }
+    }
+
+    fn load_sprite(&mut self, name: &str, image: &Image) {
+        self.update_texture(name, image);
+    }
+
+    fn remove_sprite(&mut self, name: &str) {
+        self.remove_texture(name);
+    }
+
+    fn draw_image(&mut self, position_uvs: Vec<(Vec2f,Vec2f)>, sprite_name: &str) {
+        if let Some(sprite) = self.scene_data.textures.get(sprite_name) {
+            if let Some(frame) = &mut self.frame {
+                let vertices: Vec<ClipVertex> = position_uvs
+                    .iter()
+                    .map(|(position, uv)| canvas_vertex(*position, *uv))
+                    .collect();
+
+                draw_fan(frame, &vertices, &mut |varying| sample(sprite, [varying[6], varying[7]]));
+            }
+        }
+    }
+}
+
+/// canvas coordinate to clip space, uv is stored in the varying like the 3d vertices
+fn canvas_vertex(position: Vec2f, uv: Vec2f) -> ClipVertex {
+    let [x, y] = position;
+    let mut varying = [0.0; VARYING_NUMBER];
+    varying[6] = uv[0];
+    varying[7] = uv[1];
+    ClipVertex::new([2.0 * x - 1.0, -2.0 * y + 1.0, 0.0, 1.0], varying)
+}
+
+fn draw_fan<F: FnMut(&Varying) -> Vec4f>(frame: &mut FrameBuffer, vertices: &[ClipVertex], color: &mut F) {
+    let (width, height) = (frame.width, frame.height);
+
+    for i in 1..vertices.len().saturating_sub(1) {
+        rasterize_triangle(&[vertices[0], vertices[i], vertices[i + 1]], width, height, &mut |fragment: &Fragment| {
+            frame.blend_alpha(fragment.x, fragment.y, color(&fragment.varying));
+        });
+    }
+}
+
+/// line as a quad of `line_width` pixels
+fn draw_segment(frame: &mut FrameBuffer, from: Vec2f, to: Vec2f, line_width: f32, color: Vec4f) {
+    let (width, height) = (frame.width as f32, frame.height as f32);
+
+    let (dx, dy) = ((to[0] - from[0]) * width, (to[1] - from[1]) * height);
+    let length = (dx * dx + dy * dy).sqrt();
+    if length == 0.0 {
+        return;
+    }
+
+    /* half width normal in canvas coordinate */
+    let half = line_width.max(1.0) * 0.5;
+    let (nx, ny) = (-dy / length * half / width, dx / length * half / height);
+
+    let quad: Vec<ClipVertex> = [
+        [from[0] + nx, from[1] + ny],
+        [to[0] + nx, to[1] + ny],
+        [to[0] - nx, to[1] - ny],
+        [from[0] - nx, from[1] - ny],
+    ].iter().map(|x| canvas_vertex(*x, [0.0, 0.0])).collect();
+
+    draw_fan(frame, &quad, &mut |_| color);
+}
+
+/// nearest texel, v = 0 is the first row of image like the OpenGL texture
+fn sample(image: &Image, uv: Vec2f) -> Vec4f {
+    let (width, height) = image.dimensions;
+    if width == 0 || height == 0 {
+        return [0.0, 0.0, 0.0, 0.0];
+    }
+
+    let x = ((uv[0].max(0.0).min(1.0) * width as f32) as u32).min(width - 1);
+    let y = ((uv[1].max(0.0).min(1.0) * height as f32) as u32).min(height - 1);
+    let index = (y * width + x) as usize;
+
+    let texel = |i: usize| image.data.get(i).map(|x| *x as f32 / 255.0).unwrap_or(0.0);
+
+    match image.image_type {
+        ImageType::U8 => {
+            let value = texel(index);
+            [value, value, value, 1.0]
+        },
+        ImageType::U8U8U8 => [texel(index * 3), texel(index * 3 + 1), texel(index * 3 + 2), 1.0],
+        ImageType::U8U8U8U8 => [texel(index * 4), texel(index * 4 + 1), texel(index * 4 + 2), texel(index * 4 + 3)],
+        ImageType::F32F32F32 => {
+            let value = |i: usize| image.data
+                .get(i * 4..i * 4 + 4)
+                .map(|x| f32::from_ne_bytes([x[0], x[1], x[2], x[3]]))
+                .unwrap_or(0.0);
+            [value(index * 3), value(index * 3 + 1), value(index * 3 + 2), 1.0]
+        },
+    }
+}
@@ -0,0 +1,16 @@
+//! pure rust renderer, everything is rasterized on CPU into an image,
+//! it is the reference of the glium backend and works without OpenGL
+
+pub mod renderer;
+pub mod buffer;
+pub mod rasterizer;
+pub mod shading;
+pub mod render_produre;
+pub mod renderer_manager;
+
+pub mod canvas;
+pub mod graphics_paint;
+pub mod text_paint;
+
+pub use renderer::*;
+pub use buffer::*;
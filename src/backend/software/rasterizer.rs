@@ -0,0 +1,374 @@
+/// # Rasterizer
+/// triangle setup and scan conversion of clip space primitives.
+///
+/// the viewport maps ndc `[-1, 1]` to `[0, width] x [0, height]` with row 0 at the top,
+/// pixel centers are at `x + 0.5`. attributes are interpolated perspective correct by `1/w`
+/// and the depth is in `[0, 1]` like the OpenGL window depth.
+use rmu::raw::Vec4f;
+
+/// number of attributes interpolated over a primitive
+pub const VARYING_NUMBER: usize = 12;
+
+pub type Varying = [f32; VARYING_NUMBER];
+
+#[derive(Debug,Copy,Clone)]
+pub struct ClipVertex {
+    /// clip space position, output of `project * view * model`
+    pub position: Vec4f,
+    pub varying: Varying,
+}
+
+impl ClipVertex {
+    #[inline]
+    pub fn new(position: Vec4f, varying: Varying) -> Self {
+        Self {
+            position,
+            varying,
+        }
+    }
+}
+
+#[derive(Debug,Copy,Clone)]
+pub struct Fragment {
+    pub x: u32,
+    pub y: u32,
+    pub depth: f32,
+    pub varying: Varying,
+}
+
+#[derive(Copy,Clone)]
+struct ScreenVertex {
+    x: f32,
+    y: f32,
+    z: f32,
+    inv_w: f32,
+    /// varying divided by w
+    varying: Varying,
+}
+
+fn to_screen(vertex: &ClipVertex, width: u32, height: u32) -> ScreenVertex {
+    let [x, y, z, w] = vertex.position;
+    let inv_w = 1.0 / w;
+
+    let mut varying = vertex.varying;
+    for v in varying.iter_mut() {
+        *v *= inv_w;
+    }
+
+    ScreenVertex {
+        x: (x * inv_w * 0.5 + 0.5) * width as f32,
+        y: (0.5 - y * inv_w * 0.5) * height as f32,
+        z: z * inv_w * 0.5 + 0.5,
+        inv_w,
+        varying,
+    }
+}
+
+fn lerp_vertex(a: &ClipVertex, b: &ClipVertex, t: f32) -> ClipVertex {
+    let mut position = [0.0; 4];
+    for i in 0..4 {
+        position[i] = a.position[i] + (b.position[i] - a.position[i]) * t;
+    }
+
+    let mut varying = [0.0; VARYING_NUMBER];
+    for i in 0..VARYING_NUMBER {
+        varying[i] = a.varying[i] + (b.varying[i] - a.varying[i]) * t;
+    }
+
+    ClipVertex::new(position, varying)
+}
+
+/// distance to the near plane `z = -w`, positive inside
+#[inline]
+fn near_distance(vertex: &ClipVertex) -> f32 {
+    vertex.position[2] + vertex.position[3]
+}
+
+/// clip polygon by the near plane, other planes are handled by the viewport bounds and depth range
+fn clip_near(polygon: &[ClipVertex]) -> Vec<ClipVertex> {
+    let mut result = Vec::with_capacity(polygon.len() + 2);
+
+    for i in 0..polygon.len() {
+        let current = &polygon[i];
+        let next = &polygon[(i + 1) % polygon.len()];
+        let (d0, d1) = (near_distance(current), near_distance(next));
+
+        if d0 >= 0.0 {
+            result.push(*current);
+        }
+
+        if (d0 >= 0.0) != (d1 >= 0.0) {
+            result.push(lerp_vertex(current, next, d0 / (d0 - d1)));
+        }
+    }
+
+    result
+}
+
+#[inline]
+fn edge(a: &ScreenVertex, b: &ScreenVertex, x: f32, y: f32) -> f32 {
+    (b.x - a.x) * (y - a.y) - (b.y - a.y) * (x - a.x)
+}
+
+/// pixels exactly on a shared edge belong to one of the two triangles
+#[inline]
+fn is_top_left(a: &ScreenVertex, b: &ScreenVertex) -> bool {
+    let (dx, dy) = (b.x - a.x, b.y - a.y);
+    dy > 0.0 || (dy == 0.0 && dx < 0.0)
+}
+
+/// rasterize a triangle, both windings are drawn
+pub fn rasterize_triangle<F: FnMut(&Fragment)>(triangle: &[ClipVertex;3], width: u32, height: u32, fragment: &mut F) {
+    let polygon = clip_near(triangle);
+    if polygon.len() < 3 {
+        return;
+    }
+
+    let screen: Vec<ScreenVertex> = polygon.iter().map(|x| to_screen(x, width, height)).collect();
+
+    for i in 1..screen.len() - 1 {
+        raster(&screen[0], &screen[i], &screen[i + 1], width, height, fragment);
+    }
+}
+
+fn raster<F: FnMut(&Fragment)>(v0: &ScreenVertex, v1: &ScreenVertex, v2: &ScreenVertex, width: u32, height: u32, fragment: &mut F) {
+    let area = edge(v0, v1, v2.x, v2.y);
+    if area.abs() < std::f32::EPSILON {
+        return;
+    }
+
+    /* counter clockwise on screen, so the edge functions are positive inside */
+    let (v1, v2, area) = if area < 0.0 { (v2, v1, -area) } else { (v1, v2, area) };
+
+    let min_x = v0.x.min(v1.x).min(v2.x).floor().max(0.0) as i64;
+    let min_y = v0.y.min(v1.y).min(v2.y).floor().max(0.0) as i64;
+    let max_x = v0.x.max(v1.x).max(v2.x).ceil().min(width as f32 - 1.0) as i64;
+    let max_y = v0.y.max(v1.y).max(v2.y).ceil().min(height as f32 - 1.0) as i64;
+
+    let edges = [(v1, v2), (v2, v0), (v0, v1)];
+    let top_left = [is_top_left(v1, v2), is_top_left(v2, v0), is_top_left(v0, v1)];
+
+    for y in min_y..=max_y {
+        for x in min_x..=max_x {
+            let (px, py) = (x as f32 + 0.5, y as f32 + 0.5);
+
+            let mut weights = [0.0f32; 3];
+            let mut inside = true;
+            for i in 0..3 {
+                let e = edge(edges[i].0, edges[i].1, px, py);
+                if e < 0.0 || (e == 0.0 && !top_left[i]) {
+                    inside = false;
+                    break;
+                }
+                weights[i] = e / area;
+            }
+
+            if !inside {
+                continue;
+            }
+
+            let [w0, w1, w2] = weights;
+
+            /* z / w is linear in screen space */
+            let depth = w0 * v0.z + w1 * v1.z + w2 * v2.z;
+            if depth < 0.0 || depth > 1.0 {
+                continue;
+            }
+
+            let inv_w = w0 * v0.inv_w + w1 * v1.inv_w + w2 * v2.inv_w;
+            let mut varying = [0.0; VARYING_NUMBER];
+            for i in 0..VARYING_NUMBER {
+                varying[i] = (w0 * v0.varying[i] + w1 * v1.varying[i] + w2 * v2.varying[i]) / inv_w;
+            }
+
+            fragment(&Fragment {
+                x: x as u32,
+                y: y as u32,
+                depth,
+                varying,
+            });
+        }
+    }
+}
+
+/// one pixel wide line, the line is dropped if one of the end is behind the camera
+pub fn rasterize_line<F: FnMut(&Fragment)>(line: &[ClipVertex;2], width: u32, height: u32, fragment: &mut F) {
+    if near_distance(&line[0]) < 0.0 || near_distance(&line[1]) < 0.0 {
+        return;
+    }
+
+    let a = to_screen(&line[0], width, height);
+    let b = to_screen(&line[1], width, height);
+
+    let steps = (b.x - a.x).abs().max((b.y - a.y).abs()).ceil().max(1.0) as u32;
+
+    for step in 0..=steps {
+        let t = step as f32 / steps as f32;
+        let (x, y) = (a.x + (b.x - a.x) * t, a.y + (b.y - a.y) * t);
+
+        if x < 0.0 || y < 0.0 || x >= width as f32 || y >= height as f32 {
+            continue;
+        }
+
+        let depth = a.z + (b.z - a.z) * t;
+        if depth < 0.0 || depth > 1.0 {
+            continue;
+        }
+
+        let inv_w = a.inv_w + (b.inv_w - a.inv_w) * t;
+        let mut varying = [0.0; VARYING_NUMBER];
+        for i in 0..VARYING_NUMBER {
+            varying[i] = (a.varying[i] + (b.varying[i] - a.varying[i]) * t) / inv_w;
+        }
+
+        fragment(&Fragment {
+            x: x as u32,
+            y: y as u32,
+            depth,
+            varying,
+        });
+    }
+}
+
+/// square point of `size` pixels
+pub fn rasterize_point<F: FnMut(&Fragment)>(point: &ClipVertex, size: f32, width: u32, height: u32, fragment: &mut F) {
+    if near_distance(point) < 0.0 {
+        return;
+    }
+
+    let center = to_screen(point, width, height);
+    if center.z < 0.0 || center.z > 1.0 {
+        return;
+    }
+
+    let half = size.max(1.0) * 0.5;
+    let min_x = (center.x - half).round().max(0.0) as i64;
+    let min_y = (center.y - half).round().max(0.0) as i64;
+    let max_x = ((center.x + half).round() as i64).min(width as i64);
+    let max_y = ((center.y + half).round() as i64).min(height as i64);
+
+    let mut varying = center.varying;
+    for v in varying.iter_mut() {
+        *v /= center.inv_w;
+    }
+
+    for y in min_y..max_y {
+        for x in min_x..max_x {
+            fragment(&Fragment {
+                x: x as u32,
+                y: y as u32,
+                depth: center.z,
+                varying,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vertex(position: Vec4f, value: f32) -> ClipVertex {
+        let mut varying = [0.0; VARYING_NUMBER];
+        varying[0] = value;
+        ClipVertex::new(position, varying)
+    }
+
+    fn fragments(triangle: &[ClipVertex;3], width: u32, height: u32) -> Vec<Fragment> {
+        let mut result = Vec::new();
+        rasterize_triangle(triangle, width, height, &mut |x| result.push(*x));
+        result
+    }
+
+    /* a quad with its edges and diagonal through pixel centers, every pixel is covered once */
+    #[test]
+    fn top_left_fill() {
+        let (width, height) = (4, 4);
+        let [a, b, c, d] = [
+            vertex([-0.75, 0.75, 0.0, 1.0], 0.0),
+            vertex([0.75, 0.75, 0.0, 1.0], 0.0),
+            vertex([0.75, -0.75, 0.0, 1.0], 0.0),
+            vertex([-0.75, -0.75, 0.0, 1.0], 0.0),
+        ];
+
+        let mut coverage = vec![0; (width * height) as usize];
+        for triangle in [[a, b, c], [a, c, d]].iter() {
+            for fragment in fragments(triangle, width, height) {
+                coverage[(fragment.y * width + fragment.x) as usize] += 1;
+            }
+        }
+
+        assert!(coverage.iter().all(|x| *x <= 1));
+        /* centers at 0.5 .. 3.5, one of the two edges of each axis is inside */
+        assert_eq!(coverage.iter().sum::<i32>(), 9);
+
+        /* the winding doesn't change the covered pixels */
+        let mut first = fragments(&[a, b, c], width, height).iter().map(|x| (x.x, x.y)).collect::<Vec<_>>();
+        let mut reversed = fragments(&[c, b, a], width, height).iter().map(|x| (x.x, x.y)).collect::<Vec<_>>();
+        first.sort();
+        reversed.sort();
+        assert_eq!(first, reversed);
+    }
+
+    /* the value is 0 at w = 1 and 1 at w = 3, halfway on screen it is 0.25 not 0.5 */
+    #[test]
+    fn perspective_correct() {
+        let (width, height) = (8, 8);
+        let triangle = [
+            vertex([-1.0, -1.0, 0.0, 1.0], 0.0),
+            vertex([3.0, -3.0, 0.0, 3.0], 1.0),
+            vertex([-1.0, 1.0, 0.0, 1.0], 0.0),
+        ];
+
+        let result = fragments(&triangle, width, height);
+        assert!(!result.is_empty());
+
+        let mut max_error = 0.0f32;
+        for fragment in result.iter() {
+            /* screen weight of the second vertex, the others share x */
+            let s = (fragment.x as f32 + 0.5) / width as f32;
+            let expected = (s / 3.0) / (1.0 - s + s / 3.0);
+
+            assert!((fragment.varying[0] - expected).abs() < 1e-4);
+            assert!((fragment.depth - 0.5).abs() < 1e-5);
+            max_error = max_error.max((fragment.varying[0] - s).abs());
+        }
+
+        /* linear interpolation on screen would be off */
+        assert!(max_error > 0.1);
+    }
+
+    /* the value is the distance to the near plane, it is never negative after clipping.
+       the third vertex is behind the camera, the visible part of the triangle reaches the top of the screen */
+    #[test]
+    fn near_plane_clipping() {
+        let (width, height) = (8, 8);
+        let (near, far) = (0.1, 10.0);
+        /* 90 degrees perspective of a view space position */
+        let project = |x: f32, y: f32, z: f32| {
+            let position = [x, y, (far + near) / (near - far) * z + 2.0 * far * near / (near - far), -z];
+            vertex(position, position[2] + position[3])
+        };
+
+        let triangle = [
+            project(-1.0, -1.0, -2.0),
+            project(1.0, -1.0, -2.0),
+            project(0.0, 1.0, 1.0),
+        ];
+
+        let result = fragments(&triangle, width, height);
+        assert!(result.iter().any(|x| x.y == 0));
+
+        for fragment in result.iter() {
+            assert!(fragment.varying[0] >= -1e-4);
+            assert!(fragment.depth >= 0.0 && fragment.depth <= 1.0);
+        }
+
+        let behind = [
+            project(-1.0, -1.0, 1.0),
+            project(1.0, -1.0, 1.0),
+            project(0.0, 1.0, 0.05),
+        ];
+        assert!(fragments(&behind, width, height).is_empty());
+    }
+}
@@ -0,0 +1,212 @@
+use super::renderer::SoftwareRenderer;
+use super::buffer::*;
+use super::rasterizer::*;
+use super::shading::*;
+use crate::renderer::{RenderProdure, RenderPhase, view_depth};
+use crate::base::{Vertex, Indices, material::BlendMode};
+use crate::animation::skin_vertices;
+use rmu::raw::{Vec3f, Vec4f, Mat4f};
+use std::borrow::Cow;
+
+impl SoftwareRenderer {
+    fn build_render_queue(&mut self) {
+        let view = self.scene_data.camera.view();
+        let scene_data = &self.scene_data;
+
+        self.render_queue.clear();
+
+        for (name, object) in scene_data.objects.iter() {
+            if let Some(material) = scene_data.materials.get(&object.material_name) {
+                self.render_queue.push(
+                    name,
+                    &object.material_name,
+                    self.material_instances.parent(&object.material_name),
+                    &material.name,
+                    &object.mesh_name,
+                    RenderPhase::from(material.blend_mode),
+                    view_depth(&view, &object.transform)
+                );
+            }
+        }
+
+        self.render_queue.sort();
+    }
+
+    /* vertex stage of `base_vert.glsl` then rasterize and shade each fragment */
+    fn draw_object(&self, frame: &mut FrameBuffer, object_name: &str, context: &ShadingContext) {
+        let scene_data = &self.scene_data;
+
+        let object = match scene_data.objects.get(object_name) {
+            Some(object) => object,
+            None => return,
+        };
+
+        let material = match scene_data.materials.get(&object.material_name) {
+            Some(material) => material,
+            None => return,
+        };
+
+        let (vertices, indices): (Cow<[Vertex]>, &Indices) = if let Some(mesh) = scene_data.meshes.get(&object.mesh_name) {
+            (Cow::Borrowed(&mesh.vertices), &mesh.indices)
+        } else if let Some(mesh) = scene_data.skinned_meshes.get(&object.mesh_name) {
+            let joint_matrices = scene_data.skins.get(object_name).map(|x| x.as_slice()).unwrap_or(&[]);
+            (Cow::Owned(skin_vertices(&mesh.vertices, joint_matrices)), &mesh.indices)
+        } else {
+            return;
+        };
+
+        let models: Vec<(Mat4f, Vec4f)> = match scene_data.instances.get(object_name) {
+            Some(instances) => instances
+                .iter()
+                .map(|x| (mul_matrix(&object.transform, &x.transform), x.tint))
+                .collect(),
+            None => vec![(object.transform, [1.0, 1.0, 1.0, 1.0])],
+        };
+
+        let camera = &scene_data.camera;
+        let (view, project) = (camera.view(), camera.project());
+        let (width, height) = (frame.width, frame.height);
+        let blend_mode = material.blend_mode;
+
+        let mut write_fragment = |fragment: &Fragment| {
+            let index = frame.index(fragment.x, fragment.y);
+
+            let passed = match blend_mode {
+                BlendMode::Opaque | BlendMode::AlphaTest { .. } => fragment.depth < frame.depth[index],
+                BlendMode::AlphaBlend | BlendMode::Additive => fragment.depth <= frame.depth[index],
+            };
+
+            if !passed {
+                return;
+            }
+
+            let v = &fragment.varying;
+            let surface = Surface {
+                position: [v[0], v[1], v[2]],
+                normal: [v[3], v[4], v[5]],
+                tint: [v[8], v[9], v[10], v[11]],
+            };
+
+            if let Some(color) = shade(material, context, &surface) {
+                match blend_mode {
+                    BlendMode::Opaque | BlendMode::AlphaTest { .. } => {
+                        frame.color[index] = color;
+                        frame.depth[index] = fragment.depth;
+                    },
+                    BlendMode::AlphaBlend => frame.blend_alpha(fragment.x, fragment.y, color),
+                    BlendMode::Additive => frame.blend_additive(fragment.x, fragment.y, color),
+                }
+            }
+        };
+
+        for (model, tint) in models {
+            let clip_vertices: Vec<ClipVertex> = vertices
+                .iter()
+                .map(|vertex| {
+                    let [x, y, z] = vertex.position;
+                    let world = mul_vector(&model, [x, y, z, 1.0]);
+                    let clip = mul_vector(&project, mul_vector(&view, world));
+                    let normal = mul_direction(&model, vertex.normal);
+                    let [u, v] = vertex.tex_coordinate;
+
+                    ClipVertex::new(clip, [
+                        world[0], world[1], world[2],
+                        normal[0], normal[1], normal[2],
+                        u, v,
+                        tint[0], tint[1], tint[2], tint[3],
+                    ])
+                })
+                .collect();
+
+            let vertex = |i: &u32| clip_vertices.get(*i as usize).cloned();
+
+            match indices {
+                Indices::TriangleFace(indices) => {
+                    for triangle in indices.chunks_exact(3) {
+                        if let (Some(a), Some(b), Some(c)) = (vertex(&triangle[0]), vertex(&triangle[1]), vertex(&triangle[2])) {
+                            rasterize_triangle(&[a, b, c], width, height, &mut write_fragment);
+                        }
+                    }
+                },
+                Indices::EdgeLists(indices) => {
+                    for line in indices.chunks_exact(2) {
+                        if let (Some(a), Some(b)) = (vertex(&line[0]), vertex(&line[1])) {
+                            rasterize_line(&[a, b], width, height, &mut write_fragment);
+                        }
+                    }
+                },
+                Indices::Points(indices) => {
+                    for point in indices.iter().filter_map(|x| vertex(x)) {
+                        rasterize_point(&point, 1.0, width, height, &mut write_fragment);
+                    }
+                },
+            }
+        }
+    }
+}
+
+impl RenderProdure for SoftwareRenderer {
+    fn clear(&mut self) {
+        let mut frame = FrameBuffer::new(self.width(), self.height());
+        frame.clear(self.scene_data.bg_color);
+        self.frame = Some(frame);
+    }
+
+    /// objects are not shadowed by the software renderer
+    fn shadow_map(&mut self) {
+
+    }
+
+    fn render(&mut self) {
+        let mut frame = match self.frame.take() {
+            Some(frame) => frame,
+            None => return,
+        };
+
+        self.build_render_queue();
+
+        let context = ShadingContext {
+            lights: self.scene_data.sorted_lights(),
+            view_position: self.scene_data.camera.look_from(),
+            hdr_enable: self.hdr_enable,
+            gamma: self.gamma,
+        };
+
+        /* opaque and alpha test objects, then transparent objects from back to front */
+        for item in self.render_queue.items() {
+            self.draw_object(&mut frame, &item.object_name, &context);
+        }
+
+        self.frame = Some(frame);
+    }
+
+    fn swap_buffer(&mut self) {
+        if let Some(frame) = self.frame.take() {
+            self.front_buffer = frame;
+        }
+    }
+}
+
+/// `a * b` of column major matrices
+fn mul_matrix(a: &Mat4f, b: &Mat4f) -> Mat4f {
+    let mut result = [[0.0; 4]; 4];
+    for c in 0..4 {
+        result[c] = mul_vector(a, b[c]);
+    }
+    result
+}
+
+fn mul_vector(m: &Mat4f, v: Vec4f) -> Vec4f {
+    let mut result = [0.0; 4];
+    for c in 0..4 {
+        for r in 0..4 {
+            result[r] += m[c][r] * v[c];
+        }
+    }
+    result
+}
+
+fn mul_direction(m: &Mat4f, v: Vec3f) -> Vec3f {
+    let [x, y, z, _] = mul_vector(m, [v[0], v[1], v[2], 0.0]);
+    [x, y, z]
+}
@@ -0,0 +1,155 @@
+use crate::application::Config;
+use crate::renderer::*;
+use crate::base::font::FontSet;
+use crate::base::color::BLACK;
+use crate::base::material_definition::MaterialDefinitions;
+use crate::base::material_instance::MaterialInstances;
+use super::buffer::*;
+use rmu::raw::{Vec2f, Vec4f};
+
+/// # Software Renderer
+/// rasterize the scene and canvas on CPU into a frame buffer of the config size.
+///
+/// `clear` begins a frame, `swap_buffer` presents it, `read_frame` reads the current frame
+/// or the presented frame. transparent objects are always sorted, the order independent transparency
+/// switch has no effect and there is no antialiasing
+pub struct SoftwareRenderer {
+    pub config: Config,
+    /// frame being drawn, from `clear` to `swap_buffer`
+    pub frame: Option<FrameBuffer>,
+    /// last presented frame
+    pub front_buffer: FrameBuffer,
+    pub scene_data: SceneData,
+    /// materials are validated against these on `update_material`
+    pub material_definitions: MaterialDefinitions,
+    /// instances whose materials are in the scene data, with the parent they are updated with
+    pub material_instances: MaterialInstances,
+    /// draw order of the frame, built at the begin of `render`
+    pub render_queue: RenderQueue,
+    pub antialising_enable: bool,
+    pub hdr_enable: bool,
+    pub oit_enable: bool,
+    pub gamma: f32,
+    pub graphics_paint: SoftwareGraphicsPaint,
+    pub text_paint: SoftwareTextPaint,
+}
+
+impl SoftwareRenderer {
+    pub fn new(config: Config) -> Self {
+        let (width, height) = (config.size.width as u32, config.size.height as u32);
+
+        Self {
+            config,
+            frame: None,
+            front_buffer: FrameBuffer::new(width, height),
+            scene_data: SceneData::new(),
+            material_definitions: MaterialDefinitions::bulidin(),
+            material_instances: MaterialInstances::new(),
+            render_queue: RenderQueue::new(),
+            antialising_enable: false,
+            hdr_enable: true,
+            oit_enable: false,
+            gamma: 2.2,
+            graphics_paint: SoftwareGraphicsPaint::new(),
+            text_paint: SoftwareTextPaint::new(),
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.front_buffer.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.front_buffer.height
+    }
+}
+
+impl Renderer for SoftwareRenderer {
+
+    fn background_color(&mut self, color: Vec4f) {
+        self.scene_data.bg_color = color;
+    }
+
+    fn set_antialiasing(&mut self, enable: bool) {
+        self.antialising_enable = enable;
+    }
+
+    fn set_hdr(&mut self, enable: bool) {
+        self.hdr_enable = enable;
+    }
+
+    fn set_gamma(&mut self, gamma: f32) {
+        self.gamma = gamma;
+    }
+
+    fn set_order_independent_transparency(&mut self, enable: bool) {
+        self.oit_enable = enable;
+    }
+
+    /// the environment is not drawn and does not light objects in the software renderer
+    fn set_environment(&mut self, _image: &Image) {
+
+    }
+
+    /// objects are not occluded by the software renderer
+    fn set_ssao(&mut self, _config: Option<SsaoConfig>) {
+
+    }
+
+    fn read_frame(&mut self) -> Image {
+        match &self.frame {
+            Some(frame) => frame.image(),
+            None => self.front_buffer.image(),
+        }
+    }
+
+    fn read_target(&mut self, name: &str) -> Option<Image> {
+        self.scene_data.textures.get(name).cloned()
+    }
+}
+
+pub struct SoftwareGraphicsPaint {
+    pub size: f32,
+    pub line_width: f32,
+    pub color: Vec4f,
+}
+
+impl SoftwareGraphicsPaint {
+    pub fn new() -> Self {
+        Self {
+            size: 1f32,
+            line_width: 1f32,
+            color: BLACK,
+        }
+    }
+}
+
+pub struct SoftwareTextPaint {
+    pub font_set: FontSet,
+    pub active_font: String,
+    pub font_scale: Scale,
+    pub font_color: Vec4f,
+    pub font_space: f32,
+    pub line_space: f32,
+    pub align: Align,
+    pub max_line: u32,
+    pub max_width: f32,
+    pub postion: Vec2f,
+}
+
+impl SoftwareTextPaint {
+    pub fn new() -> Self {
+        Self {
+            font_set: FontSet::new(),
+            active_font: String::default(),
+            font_scale: Scale::uniform(14f32),
+            font_color: BLACK,
+            font_space: 0f32,
+            line_space: 0f32,
+            align: Align::Left,
+            max_line: 1u32,
+            max_width: 100f32,
+            postion: [0f32;2]
+        }
+    }
+}
@@ -0,0 +1,164 @@
+use super::renderer::SoftwareRenderer;
+use super::buffer::*;
+use crate::base::{
+    camera::Camera,
+    material::Material,
+    material_definition::{MaterialDefinition, MaterialErr},
+    material_instance::MaterialInstance,
+    Vertex,
+    SkinnedVertex,
+};
+use rmu::raw::Mat4f;
+use crate::renderer::{
+    RendererManager,
+    data::*,
+    image::*,
+    light::*,
+};
+
+impl RendererManager for SoftwareRenderer {
+    fn update_object(&mut self, name: &str, object: &mut Object) {
+        let objects = &mut self.scene_data.objects;
+
+        match object.update {
+            DataUpdate::ALL => {
+                let software_object = SoftwareObject {
+                    mesh_name: object.mesh_name.clone(),
+                    material_name: object.material_name.clone(),
+                    transform: object.transform,
+                };
+                objects.insert(name.to_string(), software_object);
+            },
+            DataUpdate::Transfrom => {
+                if let Some(software_object) = objects.get_mut(name) {
+                    software_object.transform = object.transform;
+                }
+            },
+            DataUpdate::Material => {
+                if let Some(software_object) = objects.get_mut(name) {
+                    software_object.material_name = object.material_name.clone();
+                }
+            },
+            DataUpdate::Statue | DataUpdate::Not => (),
+        }
+
+        // update  finished
+        object.update = DataUpdate::Not;
+    }
+
+    fn remove_object(&mut self, name: &str) {
+        self.scene_data.objects.remove(name);
+        self.scene_data.skins.remove(name);
+        self.scene_data.instances.remove(name);
+    }
+
+    fn update_instances(&mut self, name: &str, instances: &[Instance]) {
+        /* same as the glium backend, no instances draw the object once */
+        if instances.is_empty() {
+            self.remove_instances(name);
+            return;
+        }
+
+        self.scene_data.instances.insert(name.to_string(), instances.to_vec());
+    }
+
+    fn remove_instances(&mut self, name: &str) {
+        self.scene_data.instances.remove(name);
+    }
+
+    fn update_texture(&mut self, name: &str, image: &Image) {
+        self.scene_data.textures.insert(name.to_string(), image.clone());
+    }
+
+    fn remove_texture(&mut self, name: &str) {
+        self.scene_data.textures.remove(name);
+    }
+
+    /// cube maps are not sampled by the software shading
+    fn update_cubemap(&mut self, _name: &str, _faces: &[Image;6]) {}
+
+    fn remove_cubemap(&mut self, _name: &str) {}
+
+    fn update_mesh(&mut self, name: &str, mesh: &GMesh) {
+        let software_mesh = SoftwareMesh {
+            vertices: mesh.vertices.clone(),
+            indices: mesh.indices.clone(),
+        };
+
+        self.scene_data.skinned_meshes.remove(name);
+        self.scene_data.meshes.insert(name.to_string(), software_mesh);
+    }
+
+    fn remove_mesh(&mut self, name: &str) {
+        self.scene_data.meshes.remove(name);
+        self.scene_data.skinned_meshes.remove(name);
+    }
+
+    fn update_mesh_vertices(&mut self, name: &str, vertices: &[Vertex]) {
+        if let Some(mesh) = self.scene_data.meshes.get_mut(name) {
+            mesh.vertices = vertices.to_vec();
+        }
+    }
+
+    fn update_skinned_mesh_vertices(&mut self, name: &str, vertices: &[SkinnedVertex]) {
+        if let Some(mesh) = self.scene_data.skinned_meshes.get_mut(name) {
+            mesh.vertices = vertices.to_vec();
+        }
+    }
+
+    fn update_skinned_mesh(&mut self, name: &str, mesh: &GSkinnedMesh) {
+        let software_mesh = SoftwareSkinnedMesh {
+            vertices: mesh.vertices.clone(),
+            indices: mesh.indices.clone(),
+        };
+
+        self.scene_data.meshes.remove(name);
+        self.scene_data.skinned_meshes.insert(name.to_string(), software_mesh);
+    }
+
+    fn update_skin(&mut self, name: &str, joint_matrices: &[Mat4f]) {
+        self.scene_data.skins.insert(name.to_string(), joint_matrices.to_vec());
+    }
+
+    fn update_material_definition(&mut self, definition: &MaterialDefinition) {
+        self.material_definitions.insert(definition.clone());
+    }
+
+    fn update_material(&mut self, name: &str, material: &Material) -> Result<(), MaterialErr> {
+        let material = self.material_definitions.validate(material)?;
+        let instances = self.material_instances.resolve_instances(name, &material, &self.material_definitions)?;
+        self.material_instances.remove(name);
+
+        self.scene_data.materials.insert(name.to_string(), material);
+        self.scene_data.materials.extend(instances);
+        Ok(())
+    }
+
+    fn update_material_instance(&mut self, name: &str, instance: &MaterialInstance) -> Result<(), MaterialErr> {
+        let material = self.material_instances.resolve(name, instance, &self.scene_data.materials, &self.material_definitions)?;
+        self.scene_data.materials.insert(name.to_string(), material);
+        self.material_instances.insert(name, instance.clone());
+        Ok(())
+    }
+
+    fn remove_material(&mut self, name: &str) {
+        self.material_instances.remove(name);
+        self.scene_data.materials.remove(name);
+
+        for instance_name in self.material_instances.instances_of(name) {
+            self.scene_data.materials.remove(&instance_name);
+        }
+    }
+
+    fn update_camera(&mut self, camera: &Camera) {
+        self.scene_data.camera = camera.clone();
+    }
+
+    fn update_light(&mut self, name: &str, light: &Light) {
+        self.scene_data.lights.insert(name.to_string(), *light);
+    }
+
+    fn remove_light(&mut self, name: &str) {
+        self.scene_data.lights.remove(name);
+    }
+}
@@ -0,0 +1,259 @@
+/// # Shading
+/// the builtin materials evaluated on CPU, the lighting model is same as `light_lib.glsl`,
+/// materials with other shader are shaded as pure color
+use rmu::raw::{Vec3f, Vec4f};
+use crate::base::material::{Material, PropertyValue, BlendMode};
+use crate::renderer::Light;
+use std::f32::consts::PI;
+
+const POINT: f32 = 0.0;
+const SPOT: f32 = 1.0;
+const PARALLEL: f32 = 2.0;
+
+const LUMEN: f32 = 0.0;
+
+/// uniforms shared by all objects of a frame
+pub struct ShadingContext {
+    pub lights: Vec<Light>,
+    pub view_position: Vec3f,
+    pub hdr_enable: bool,
+    pub gamma: f32,
+}
+
+/// interpolated surface of a fragment
+#[derive(Debug,Copy,Clone)]
+pub struct Surface {
+    pub position: Vec3f,
+    pub normal: Vec3f,
+    pub tint: Vec4f,
+}
+
+/// color of the fragment, `None` if it is discarded by the alpha test
+pub fn shade(material: &Material, context: &ShadingContext, surface: &Surface) -> Option<Vec4f> {
+    let color = match material.name.as_str() {
+        "Blinn Phong BRDF" => hdr(blinn_phong(material, context, surface), context),
+        "Cook Torrance BRDF" => hdr(cook_torrance(material, context, surface), context),
+        _ => {
+            let [r, g, b] = vec3_property(material, "material.color", [1.0, 1.0, 1.0]);
+            let [tr, tg, tb, ta] = surface.tint;
+            [r * tr, g * tg, b * tb, ta]
+        },
+    };
+
+    write_color(color, material)
+}
+
+/// same as `write_color` of `frag_lib.glsl`
+fn write_color(mut color: Vec4f, material: &Material) -> Option<Vec4f> {
+    color[3] *= material.opacity;
+
+    match material.blend_mode {
+        BlendMode::AlphaTest { cutoff } if color[3] < cutoff => None,
+        _ => Some(color),
+    }
+}
+
+fn blinn_phong(material: &Material, context: &ShadingContext, surface: &Surface) -> Vec4f {
+    let ambient = vec3_property(material, "material.ambient", [0.0, 0.0, 0.0]);
+    let diffuse = vec3_property(material, "material.diffuse", [0.0, 0.0, 0.0]);
+    let specular = vec3_property(material, "material.specular", [0.0, 0.0, 0.0]);
+    let shininess = float_property(material, "material.shininess", 1.0);
+
+    let normal = normalize(surface.normal);
+    let view_direction = normalize(sub(context.view_position, surface.position));
+    let mut color = [0.0; 3];
+
+    for light in context.lights.iter() {
+        let light_direction = light_direction(light, surface.position);
+        let radiance = light_color(light, light_direction);
+        let halfway_direction = normalize(add(light_direction, view_direction));
+        let attenuation = attenuation(light, surface.position);
+
+        let diffuse_factor = dot(normal, light_direction).max(0.0);
+        let specular_factor = dot(normal, halfway_direction).max(0.0).powf(shininess);
+
+        for i in 0..3 {
+            let reflected = ambient[i] + diffuse[i] * diffuse_factor + specular[i] * specular_factor;
+            color[i] += radiance[i] * reflected * attenuation;
+        }
+    }
+
+    let [tr, tg, tb, ta] = surface.tint;
+    [color[0] * tr, color[1] * tg, color[2] * tb, ta]
+}
+
+fn cook_torrance(material: &Material, context: &ShadingContext, surface: &Surface) -> Vec4f {
+    let albedo = vec3_property(material, "material.albedo", [1.0, 1.0, 1.0]);
+    let roughness = float_property(material, "material.roughness", 0.5);
+    let metallic = float_property(material, "material.metallic", 0.0);
+    let ao = float_property(material, "material.ao", 1.0);
+
+    let normal = normalize(surface.normal);
+    let view_direction = normalize(sub(context.view_position, surface.position));
+    let nv = dot(normal, view_direction).max(0.0);
+
+    let mut f0 = [0.0; 3];
+    for i in 0..3 {
+        f0[i] = 0.04 + (albedo[i] - 0.04) * metallic;
+    }
+
+    let mut color = [0.0; 3];
+
+    for light in context.lights.iter() {
+        let light_direction = light_direction(light, surface.position);
+        let halfway_direction = normalize(add(view_direction, light_direction));
+        let radiance = light_color(light, light_direction);
+        let attenuation = attenuation(light, surface.position);
+
+        let nl = dot(normal, light_direction).max(0.0);
+        let ndf = ggx_distribution(dot(normal, halfway_direction).max(0.0), roughness);
+        let g = ggx_schlick(nv, roughness) * ggx_schlick(nl, roughness);
+        let cos_theta = dot(halfway_direction, view_direction).max(0.0);
+
+        for i in 0..3 {
+            let f = f0[i] + (1.0 - f0[i]) * (1.0 - cos_theta).powi(5);
+            let kd = (1.0 - f) * (1.0 - metallic);
+            let specular = ndf * g * f / (4.0 * nv * nl + 0.001);
+
+            let lo = (kd * albedo[i] / PI + specular) * radiance[i] * attenuation * nl;
+            color[i] += 0.03 * albedo[i] * ao + lo;
+        }
+    }
+
+    let [tr, tg, tb, ta] = surface.tint;
+    [color[0] * tr, color[1] * tg, color[2] * tb, ta]
+}
+
+fn ggx_distribution(nh: f32, roughness: f32) -> f32 {
+    let a = roughness * roughness;
+    let a2 = a * a;
+    let denominator = nh * nh * (a2 - 1.0) + 1.0;
+    a2 / (PI * denominator * denominator)
+}
+
+fn ggx_schlick(nv: f32, roughness: f32) -> f32 {
+    let r = roughness + 1.0;
+    let k = (r * r) / 8.0;
+    nv / (nv * (1.0 - k) + k)
+}
+
+fn light_direction(light: &Light, position: Vec3f) -> Vec3f {
+    if light.direction_type[3] != PARALLEL {
+        normalize(sub(light.position(), position))
+    } else {
+        normalize(scale(light.direction(), -1.0))
+    }
+}
+
+/// luminous intensity in candela of point light and spot light, illuminance in lux of parallel light
+fn luminous_intensity(light: &Light) -> f32 {
+    let intensity = light.color_intensity[3];
+    let light_type = light.direction_type[3];
+
+    if light.unit == LUMEN {
+        if light_type == SPOT {
+            return intensity / (2.0 * PI * (1.0 - light.outer_angle.cos())).max(1e-6);
+        } else if light_type == POINT {
+            return intensity / (4.0 * PI);
+        }
+    }
+
+    intensity
+}
+
+fn light_color(light: &Light, direction: Vec3f) -> Vec3f {
+    let color = [light.color_intensity[0], light.color_intensity[1], light.color_intensity[2]];
+    let color = scale(color, luminous_intensity(light));
+
+    if light.direction_type[3] == SPOT {
+        let theta = dot(scale(direction, -1.0), normalize(light.direction()));
+        let cut_off = light.inner_angle.cos();
+        let outer_cut_off = light.outer_angle.cos();
+        let epsilon = (cut_off - outer_cut_off).max(1e-4);
+        let intensity = ((theta - outer_cut_off) / epsilon).max(0.0).min(1.0);
+        scale(color, intensity)
+    } else {
+        color
+    }
+}
+
+/// inverse square falloff windowed to zero at the range of light
+fn attenuation(light: &Light, position: Vec3f) -> f32 {
+    if light.direction_type[3] == PARALLEL {
+        return 1.0;
+    }
+
+    let distance = length(sub(light.position(), position));
+    let mut falloff = 1.0 / (distance * distance).max(0.0001);
+
+    let range = light.position_range[3];
+    if range > 0.0 {
+        let window = (1.0 - (distance / range).powi(4)).max(0.0).min(1.0);
+        falloff *= window * window;
+    }
+
+    falloff
+}
+
+/// reinhard tone mapping and gamma correction
+fn hdr(color: Vec4f, context: &ShadingContext) -> Vec4f {
+    if !context.hdr_enable {
+        return color;
+    }
+
+    let mut result = color;
+    for i in 0..3 {
+        result[i] = (color[i] / (color[i] + 1.0)).powf(1.0 / context.gamma);
+    }
+    result
+}
+
+fn vec3_property(material: &Material, name: &str, default: Vec3f) -> Vec3f {
+    match material.property.iter().find(|(x, _)| x == name) {
+        Some((_, PropertyValue::Vec3(value))) => *value,
+        Some((_, PropertyValue::Vec4(value))) => [value[0], value[1], value[2]],
+        _ => default,
+    }
+}
+
+fn float_property(material: &Material, name: &str, default: f32) -> f32 {
+    match material.property.iter().find(|(x, _)| x == name) {
+        Some((_, PropertyValue::Float(value))) => *value,
+        _ => default,
+    }
+}
+
+#[inline]
+pub fn add(a: Vec3f, b: Vec3f) -> Vec3f {
+    [a[0] + b[0], a[1] + b[1], a[2] + b[2]]
+}
+
+#[inline]
+pub fn sub(a: Vec3f, b: Vec3f) -> Vec3f {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+#[inline]
+pub fn scale(a: Vec3f, s: f32) -> Vec3f {
+    [a[0] * s, a[1] * s, a[2] * s]
+}
+
+#[inline]
+pub fn dot(a: Vec3f, b: Vec3f) -> f32 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+#[inline]
+pub fn length(a: Vec3f) -> f32 {
+    dot(a, a).sqrt()
+}
+
+#[inline]
+pub fn normalize(a: Vec3f) -> Vec3f {
+    let length = length(a);
+    if length > 0.0 {
+        scale(a, 1.0 / length)
+    } else {
+        a
+    }
+}
@@ -0,0 +1,83 @@
+use crate::renderer::canvas::*;
+use crate::base::font::layout_paragraph;
+use super::renderer::*;
+use rmu::raw::{ Vec2f, Vec4f};
+
+impl TextPaint for SoftwareRenderer {
+
+    fn load_font(&mut self, name: &str, path: &str) {
+        self.text_paint.font_set.add(name, path)
+    }
+
+    fn set_font(&mut self, name: &str) {
+        self.text_paint.active_font = name.to_string()
+    }
+
+    fn set_align(&mut self, align: Align) {
+        self.text_paint.align = align
+    }
+
+    fn set_font_scale(&mut self, scale: Scale) {
+        self.text_paint.font_scale = scale
+    }
+
+    fn set_font_color(&mut self, color: Vec4f) {
+        self.text_paint.font_color = color
+    }
+
+    fn set_font_space(&mut self, font_space: f32) {
+        self.text_paint.font_space = font_space
+    }
+
+    fn set_line_space(&mut self, line_space: f32) {
+        self.text_paint.line_space = line_space
+    }
+
+    fn set_max_line(&mut self, max_line: u32) {
+        self.text_paint.max_line = max_line
+    }
+
+    fn set_max_width(&mut self,max_width: f32) {
+        self.text_paint.max_width = max_width
+    }
+
+    fn set_position(&mut self, position: Vec2f) {
+        self.text_paint.postion = position
+    }
+
+    /// glyph coverage is blended as the alpha of font color
+    fn draw_text(&mut self, text: &str) {
+        let text_paint = &self.text_paint;
+
+        if let Some(frame) = &mut self.frame {
+            if let Some(data) = text_paint.font_set.font_byte(&text_paint.active_font) {
+                if let Some(font) = rusttype::Font::try_from_bytes(data) {
+                    let scale = rusttype::Scale {
+                        x: text_paint.font_scale.width,
+                        y: text_paint.font_scale.height,
+                    };
+
+                    let glyphs = layout_paragraph(&font, scale, text_paint.max_width as u32, text);
+
+                    let (width, height) = (frame.width as i32, frame.height as i32);
+                    let origin_x = (text_paint.postion[0] * width as f32) as i32;
+                    let origin_y = (text_paint.postion[1] * height as f32) as i32;
+                    let [r, g, b, a] = text_paint.font_color;
+
+                    for glyph in glyphs.iter() {
+                        if let Some(bounding_box) = glyph.pixel_bounding_box() {
+                            glyph.draw(|x, y, coverage| {
+                                let x = origin_x + bounding_box.min.x + x as i32;
+                                let y = origin_y + bounding_box.min.y + y as i32;
+
+                                if x >= 0 && y >= 0 && x < width && y < height {
+                                    frame.blend_alpha(x as u32, y as u32, [r, g, b, a * coverage]);
+                                }
+                            });
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -1,18 +1,22 @@
 use rmu::raw::{Vec3f,Mat4f};
 use rmu::geometry::transform::rotation3;
+use serde_derive::{Serialize,Deserialize};
 
-#[derive(Debug, Copy,Clone)]
+#[derive(Debug,Copy,Clone,Serialize,Deserialize)]
 pub enum CameraMode {
     Perspective,
     Orthogonal,
 }
 
 
-#[derive(Debug,Copy,Clone)]
+#[derive(Debug,Copy,Clone,Serialize,Deserialize)]
 pub struct Camera {
+    #[serde(with = "vector3")]
     pub look_from: Vector3,
+    #[serde(with = "vector3")]
     pub look_at: Vector3,
     // view up
+    #[serde(with = "vector3")]
     pub vup: Vector3,
     // field of view
     pub fov: f32,
@@ -119,6 +123,22 @@ impl Default for Camera {
 
 use rmu::vector::Vector3;
 
+/// serialize `Vector3` as `[x, y, z]`
+mod vector3 {
+    use rmu::vector::Vector3;
+    use rmu::raw::Vec3f;
+    use serde::{Serialize, Serializer, Deserialize, Deserializer};
+
+    pub fn serialize<S: Serializer>(vector: &Vector3, serializer: S) -> Result<S::Ok, S::Error> {
+        [vector.x, vector.y, vector.z].serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vector3, D::Error> {
+        let vector = Vec3f::deserialize(deserializer)?;
+        Ok(vector.into())
+    }
+}
+
 pub fn camera(look_from: Vector3, look_at: Vector3, vup: Vector3) -> Mat4f {
     let w = (look_from - look_at).normalized();
     let u = Vector3::cross(Vector3::from(vup), w).normalized();
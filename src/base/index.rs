@@ -1,4 +1,4 @@
-#[derive(Clone,PartialEq,PartialOrd)]
+#[derive(Debug,Clone,PartialEq,PartialOrd)]
 pub enum Indices {
     TriangleFace(Vec<u32>),
     EdgeLists(Vec<u32>),
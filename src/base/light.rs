@@ -1,59 +1,117 @@
 use rmu::raw::Vec3f;
+use serde_derive::{Serialize,Deserialize};
 
-//
-#[derive(Copy,Clone)]
-pub enum Light {
-    PointLight(PointLight),
-    ParallelLight(ParallelLight),
-    SpotLight(SpotLight),
+/// unit of `Light::intensity`
+/// 
+/// * Lumen: luminous flux, for point light and spot light
+/// * Candela: luminous intensity, for point light and spot light
+/// * Lux: illuminance, for parallel light. a point light or spot light in lux is
+///   the illuminance at one meter, which is equal to its intensity in candela
+#[derive(Debug,Copy,Clone,PartialEq,Serialize,Deserialize)]
+pub enum LightUnit {
+    Lumen,
+    Candela,
+    Lux,
 }
 
-//
-#[derive(Copy,Clone)]
-pub struct PointLight{
+#[derive(Debug,Copy,Clone,PartialEq,Serialize,Deserialize)]
+#[serde(tag = "type")]
+pub enum LightType {
+    Point,
+    Parallel,
+    /// half angles of the cone in radian, the light fade out between inner and outer angle
+    Spot { inner_angle: f32, outer_angle: f32 },
+}
+
+/// light for authoring, `renderer::light::Light` is the packed data for GPU
+#[derive(Debug,Copy,Clone,PartialEq,Serialize,Deserialize)]
+pub struct Light {
     pub position: Vec3f,
+    pub direction: Vec3f,
+    /// linear rgb color
     pub color: Vec3f,
+    pub intensity: f32,
+    pub unit: LightUnit,
+    /// distance where the light fade out to zero, 0.0 is infinite
+    pub range: f32,
+    pub light_type: LightType,
 }
 
-impl PointLight {
-    pub fn new(position: Vec3f, color: Vec3f) -> Self {
-        PointLight {
+impl Light {
+    /// point light with luminous flux in lumen
+    pub fn point(position: Vec3f, color: Vec3f, lumen: f32) -> Self {
+        Self {
             position,
+            direction: [0.0, 0.0, 0.0],
             color,
+            intensity: lumen,
+            unit: LightUnit::Lumen,
+            range: 0.0,
+            light_type: LightType::Point,
         }
     }
-}
 
-#[derive(Copy, Clone)]
-pub struct ParallelLight {
-    pub direction: Vec3f,
-    pub color: Vec3f,
-}
-
-impl ParallelLight {
-    pub fn new(direction: Vec3f,color: Vec3f) -> Self {
+    /// parallel light with illuminance in lux
+    pub fn parallel(direction: Vec3f, color: Vec3f, lux: f32) -> Self {
         Self {
+            position: [0.0, 0.0, 0.0],
             direction,
             color,
+            intensity: lux,
+            unit: LightUnit::Lux,
+            range: 0.0,
+            light_type: LightType::Parallel,
         }
     }
-}
-
-#[derive(Copy,Clone)]
-pub struct SpotLight {
-    pub position: Vec3f,
-    pub direction: Vec3f,
-    pub theta: f32,
-    pub color: Vec3f,
-}
 
-impl SpotLight {
-    pub fn new(position: Vec3f, direction: Vec3f, theta: f32, color: Vec3f) -> Self {
+    /// spot light with luminous flux in lumen
+    pub fn spot(position: Vec3f, direction: Vec3f, inner_angle: f32, outer_angle: f32, color: Vec3f, lumen: f32) -> Self {
         Self {
             position,
             direction,
-            theta,
             color,
+            intensity: lumen,
+            unit: LightUnit::Lumen,
+            range: 0.0,
+            light_type: LightType::Spot { inner_angle, outer_angle },
         }
     }
-}
\ No newline at end of file
+
+    pub fn with_intensity(mut self, intensity: f32, unit: LightUnit) -> Self {
+        self.intensity = intensity;
+        self.unit = unit;
+        self
+    }
+
+    pub fn with_range(mut self, range: f32) -> Self {
+        self.range = range;
+        self
+    }
+
+    /// luminous intensity in candela of point light and spot light, illuminance in lux of parallel light
+    pub fn luminous_intensity(&self) -> f32 {
+        match (self.unit, self.light_type) {
+            (LightUnit::Lumen, LightType::Point) => 
+                self.intensity / (4.0 * std::f32::consts::PI),
+            (LightUnit::Lumen, LightType::Spot { outer_angle, .. }) => 
+                self.intensity / (2.0 * std::f32::consts::PI * (1.0 - outer_angle.cos())).max(std::f32::EPSILON),
+            _ => self.intensity,
+        }
+    }
+
+    /// range windowed inverse square falloff
+    pub fn attenuation(&self, distance: f32) -> f32 {
+        match self.light_type {
+            LightType::Parallel => 1.0,
+            _ => {
+                let falloff = 1.0 / (distance * distance).max(0.0001);
+                if self.range > 0.0 {
+                    let window = (1.0 - (distance / self.range).powi(4)).max(0.0).min(1.0);
+                    falloff * window * window
+                } else {
+                    falloff
+                }
+            },
+        }
+    }
+}
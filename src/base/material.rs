@@ -1,19 +1,194 @@
 use rmu::raw::*;
+use serde_derive::{Serialize,Deserialize};
 
-#[derive(Debug,Clone,PartialEq)]
+#[derive(Debug,Clone,PartialEq,Serialize,Deserialize)]
+#[serde(tag = "type", content = "value")]
 pub enum PropertyValue {
     Bool(bool),
     Float(f32),
     Vec2(Vec2f),
     Vec3(Vec3f),
     Vec4(Vec4f),
+    Int(i32),
+    IVec2([i32;2]),
+    IVec3([i32;3]),
+    IVec4([i32;4]),
+    /// column major
+    Mat3([[f32;3];3]),
+    /// column major
+    Mat4(Mat4f),
+    /// uniform `float name[n]`, elements past the length of the shader array are ignored
+    FloatArray(Vec<f32>),
+    /// texture by name with default sampling
     Texture(String),
+    /// texture by name with sampler state
+    SampledTexture(SampledTexture),
+    /// cube map by name, see `RendererManager::update_cubemap`
+    Cubemap(String),
 }
 
-#[derive(Clone)]
+#[derive(Debug,Copy,Clone,PartialEq,Serialize,Deserialize)]
+pub enum WrapMode {
+    Repeat,
+    MirroredRepeat,
+    Clamp,
+}
+
+impl Default for WrapMode {
+    fn default() -> Self {
+        WrapMode::Repeat
+    }
+}
+
+#[derive(Debug,Copy,Clone,PartialEq,Serialize,Deserialize)]
+pub enum FilterMode {
+    Nearest,
+    Linear,
+}
+
+impl Default for FilterMode {
+    fn default() -> Self {
+        FilterMode::Linear
+    }
+}
+
+/// # Sampled Texture
+/// texture with the sampler it is read by. `mipmap_filter` blends mipmap levels, `None` reads the first level.
+/// `mip_bias` is not sampler state in every backend, glium sets it as the uniform `<name>_mip_bias`
+/// for the shader to pass to `texture`
+#[derive(Debug,Clone,PartialEq,Serialize,Deserialize)]
+pub struct SampledTexture {
+    pub texture: String,
+    #[serde(default)]
+    pub wrap: WrapMode,
+    #[serde(default)]
+    pub min_filter: FilterMode,
+    #[serde(default)]
+    pub mag_filter: FilterMode,
+    #[serde(default)]
+    pub mipmap_filter: Option<FilterMode>,
+    /// maximum anisotropy, 1 is off
+    #[serde(default = "default_anisotropy")]
+    pub anisotropy: u16,
+    /// offset of the mipmap level
+    #[serde(default)]
+    pub mip_bias: f32,
+}
+
+fn default_anisotropy() -> u16 {
+    1
+}
+
+impl SampledTexture {
+    pub fn new(texture: &str) -> Self {
+        Self {
+            texture: texture.to_string(),
+            wrap: WrapMode::default(),
+            min_filter: FilterMode::default(),
+            mag_filter: FilterMode::default(),
+            mipmap_filter: None,
+            anisotropy: 1,
+            mip_bias: 0.0,
+        }
+    }
+
+    pub fn with_wrap(mut self, wrap: WrapMode) -> Self {
+        self.wrap = wrap;
+        self
+    }
+
+    pub fn with_filter(mut self, min_filter: FilterMode, mag_filter: FilterMode, mipmap_filter: Option<FilterMode>) -> Self {
+        self.min_filter = min_filter;
+        self.mag_filter = mag_filter;
+        self.mipmap_filter = mipmap_filter;
+        self
+    }
+
+    pub fn with_anisotropy(mut self, anisotropy: u16) -> Self {
+        self.anisotropy = anisotropy.max(1);
+        self
+    }
+
+    pub fn with_mip_bias(mut self, mip_bias: f32) -> Self {
+        self.mip_bias = mip_bias;
+        self
+    }
+}
+
+/// how the color of a material is composited with the frame
+#[derive(Debug,Copy,Clone,PartialEq,Serialize,Deserialize)]
+#[serde(tag = "type")]
+pub enum BlendMode {
+    Opaque,
+    /// fragments with alpha less than cutoff are discarded, rendered with opaque objects
+    AlphaTest { cutoff: f32 },
+    /// rendered after opaque objects from back to front
+    AlphaBlend,
+    /// color is added to the frame, rendered after opaque objects
+    Additive,
+}
+
+impl Default for BlendMode {
+    fn default() -> Self {
+        BlendMode::Opaque
+    }
+}
+
+impl BlendMode {
+    pub fn is_transparent(&self) -> bool {
+        match self {
+            BlendMode::AlphaBlend | BlendMode::Additive => true,
+            BlendMode::Opaque | BlendMode::AlphaTest { .. } => false,
+        }
+    }
+}
+
+#[derive(Debug,Clone,PartialEq,Serialize,Deserialize)]
 pub struct Material {
     pub name: String, // shader name
+    #[serde(with = "property")]
     pub property: Vec<(String,PropertyValue)>,
+    #[serde(default)]
+    pub blend_mode: BlendMode,
+    /// alpha of the material color
+    #[serde(default = "default_opacity")]
+    pub opacity: f32,
+}
+
+fn default_opacity() -> f32 {
+    1.0
+}
+
+/// serialize property as a list of `{ name, value }` tables, toml can't store tables in a tuple
+pub(crate) mod property {
+    use super::PropertyValue;
+    use serde::{Serialize, Serializer, Deserialize, Deserializer};
+    use serde_derive::{Serialize,Deserialize};
+
+    #[derive(Serialize)]
+    struct NamedPropertyRef<'a> {
+        name: &'a String,
+        value: &'a PropertyValue,
+    }
+
+    #[derive(Deserialize)]
+    struct NamedProperty {
+        name: String,
+        value: PropertyValue,
+    }
+
+    pub fn serialize<S: Serializer>(property: &Vec<(String,PropertyValue)>, serializer: S) -> Result<S::Ok, S::Error> {
+        property
+            .iter()
+            .map(|(name, value)| NamedPropertyRef { name, value })
+            .collect::<Vec<_>>()
+            .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<(String,PropertyValue)>, D::Error> {
+        let property = Vec::<NamedProperty>::deserialize(deserializer)?;
+        Ok(property.into_iter().map(|x| (x.name, x.value)).collect())
+    }
 }
 
 impl Material {
@@ -24,6 +199,20 @@ impl Material {
     pub fn property(&self) -> Vec<(String,PropertyValue)> {
         self.property.clone()
     }
+
+    pub fn with_blend_mode(mut self, blend_mode: BlendMode) -> Self {
+        self.blend_mode = blend_mode;
+        self
+    }
+
+    pub fn with_opacity(mut self, opacity: f32) -> Self {
+        self.opacity = opacity;
+        self
+    }
+
+    pub fn is_transparent(&self) -> bool {
+        self.blend_mode.is_transparent()
+    }
 }
 
 ///
@@ -34,6 +223,8 @@ pub fn pure_color_material(color: Vec3f) -> Material {
     Material {
         name,
         property,
+        blend_mode: BlendMode::Opaque,
+        opacity: 1.0,
     }
 }
 
@@ -51,6 +242,8 @@ pub fn blinn_phong_brdf(ambient: Vec3f, diffuse: Vec3f, specular: Vec3f, shinine
     Material {
         name,
         property,
+        blend_mode: BlendMode::Opaque,
+        opacity: 1.0,
     }
 }
 
@@ -58,7 +251,7 @@ pub fn cook_torrance_brdf<'a>(albedo: Vec3f, roughness: f32, metallic: f32, ao:
     let name: String =  "Cook Torrance BRDF".into();
 
     let property: Vec<(String,PropertyValue)> = vec![
-        ("material.ambient".into()  , PropertyValue::Vec3(albedo)),
+        ("material.albedo".into()   , PropertyValue::Vec3(albedo)),
         ("material.roughness".into(), PropertyValue::Float(roughness)),
         ("material.metallic".into() , PropertyValue::Float(metallic)),
         ("material.ao".into()       , PropertyValue::Float(ao))
@@ -67,5 +260,7 @@ pub fn cook_torrance_brdf<'a>(albedo: Vec3f, roughness: f32, metallic: f32, ao:
     Material {
         name,
         property,
+        blend_mode: BlendMode::Opaque,
+        opacity: 1.0,
     }
 }
\ No newline at end of file
@@ -0,0 +1,295 @@
+use super::material::{Material, PropertyValue};
+use serde_derive::{Serialize,Deserialize};
+use std::collections::HashMap;
+use std::fs::read_to_string;
+use std::fmt;
+
+#[derive(Debug,Copy,Clone,PartialEq,Eq,Serialize,Deserialize)]
+pub enum ParameterType {
+    Bool,
+    Float,
+    Vec2,
+    Vec3,
+    Vec4,
+    Int,
+    IVec2,
+    IVec3,
+    IVec4,
+    Mat3,
+    Mat4,
+    FloatArray,
+    /// name of a texture, with or without sampler state
+    Texture,
+    Cubemap,
+}
+
+impl From<&PropertyValue> for ParameterType {
+    fn from(value: &PropertyValue) -> Self {
+        match value {
+            PropertyValue::Bool(_) => ParameterType::Bool,
+            PropertyValue::Float(_) => ParameterType::Float,
+            PropertyValue::Vec2(_) => ParameterType::Vec2,
+            PropertyValue::Vec3(_) => ParameterType::Vec3,
+            PropertyValue::Vec4(_) => ParameterType::Vec4,
+            PropertyValue::Int(_) => ParameterType::Int,
+            PropertyValue::IVec2(_) => ParameterType::IVec2,
+            PropertyValue::IVec3(_) => ParameterType::IVec3,
+            PropertyValue::IVec4(_) => ParameterType::IVec4,
+            PropertyValue::Mat3(_) => ParameterType::Mat3,
+            PropertyValue::Mat4(_) => ParameterType::Mat4,
+            PropertyValue::FloatArray(_) => ParameterType::FloatArray,
+            PropertyValue::Texture(_) | PropertyValue::SampledTexture(_) => ParameterType::Texture,
+            PropertyValue::Cubemap(_) => ParameterType::Cubemap,
+        }
+    }
+}
+
+/// a uniform of the shader, `min` and `max` bound a number or each component of a vector or array
+#[derive(Debug,Clone,PartialEq,Serialize,Deserialize)]
+pub struct ParameterDefinition {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub parameter_type: ParameterType,
+    /// value used when the material doesn't set the parameter, it is required without default
+    #[serde(default)]
+    pub default: Option<PropertyValue>,
+    #[serde(default)]
+    pub min: Option<f32>,
+    #[serde(default)]
+    pub max: Option<f32>,
+}
+
+/// # Material Definition
+/// the parameters a shader reads, materials with this shader are validated against them.
+/// it is written as toml
+///
+/// ```toml
+/// shader = "Cook Torrance BRDF"
+///
+/// [[parameters]]
+/// name = "material.roughness"
+/// type = "Float"
+/// default = { type = "Float", value = 0.5 }
+/// min = 0.0
+/// max = 1.0
+/// ```
+#[derive(Debug,Clone,PartialEq,Serialize,Deserialize)]
+pub struct MaterialDefinition {
+    pub shader: String,
+    #[serde(default)]
+    pub parameters: Vec<ParameterDefinition>,
+}
+
+#[derive(Debug,Clone,PartialEq)]
+pub enum MaterialDefinitionErr {
+    PathErr,
+    SrcErr(String),
+    /// the default of a parameter is not of its type or out of its range
+    DefaultErr(String),
+}
+
+impl fmt::Display for MaterialDefinitionErr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MaterialDefinitionErr::PathErr => write!(f, "material definition file can't be read"),
+            MaterialDefinitionErr::SrcErr(message) => write!(f, "material definition is not valid toml: {}", message),
+            MaterialDefinitionErr::DefaultErr(parameter) => write!(f, "default of parameter {} doesn't match its type or range", parameter),
+        }
+    }
+}
+
+impl std::error::Error for MaterialDefinitionErr {}
+
+#[derive(Debug,Clone,PartialEq)]
+pub enum MaterialErr {
+    /// no definition for the shader of material
+    ShaderErr(String),
+    /// the shader doesn't declare the parameter
+    ParameterErr { shader: String, parameter: String },
+    TypeErr { parameter: String, expected: ParameterType, found: ParameterType },
+    RangeErr { parameter: String, min: Option<f32>, max: Option<f32> },
+    /// parameter without default is not set
+    MissingErr { shader: String, parameter: String },
+    /// parent of a material instance is not a material or is another instance
+    ParentErr(String),
+    /// a material with instances can't become a instance
+    InstanceErr(String),
+    /// the renderer is drawing with its materials, push the material again after the frame
+    BusyErr,
+}
+
+impl fmt::Display for MaterialErr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MaterialErr::ShaderErr(shader) => write!(f, "no material definition for shader {}", shader),
+            MaterialErr::ParameterErr { shader, parameter } => write!(f, "shader {} has no parameter {}", shader, parameter),
+            MaterialErr::TypeErr { parameter, expected, found } => write!(f, "parameter {} is {:?} but {:?} is expected", parameter, found, expected),
+            MaterialErr::RangeErr { parameter, min, max } => write!(
+                f,
+                "parameter {} is out of range {} to {}",
+                parameter,
+                min.map_or("-inf".to_string(), |x| x.to_string()),
+                max.map_or("inf".to_string(), |x| x.to_string())
+            ),
+            MaterialErr::MissingErr { shader, parameter } => write!(f, "parameter {} of shader {} is not set and has no default", parameter, shader),
+            MaterialErr::ParentErr(parent) => write!(f, "parent {} is not a material, instances of instances are not supported", parent),
+            MaterialErr::InstanceErr(name) => write!(f, "material {} has instances and can't be a instance", name),
+            MaterialErr::BusyErr => write!(f, "materials can't be changed while the renderer is drawing"),
+        }
+    }
+}
+
+impl std::error::Error for MaterialErr {}
+
+impl ParameterDefinition {
+    fn in_range(&self, value: &PropertyValue) -> bool {
+        let components: Vec<f32> = match value {
+            PropertyValue::Float(x) => vec![*x],
+            PropertyValue::Vec2(x) => x.to_vec(),
+            PropertyValue::Vec3(x) => x.to_vec(),
+            PropertyValue::Vec4(x) => x.to_vec(),
+            PropertyValue::Int(x) => vec![*x as f32],
+            PropertyValue::IVec2(x) => x.iter().map(|x| *x as f32).collect(),
+            PropertyValue::IVec3(x) => x.iter().map(|x| *x as f32).collect(),
+            PropertyValue::IVec4(x) => x.iter().map(|x| *x as f32).collect(),
+            PropertyValue::FloatArray(x) => x.clone(),
+            PropertyValue::Bool(_)
+            | PropertyValue::Mat3(_)
+            | PropertyValue::Mat4(_)
+            | PropertyValue::Texture(_)
+            | PropertyValue::SampledTexture(_)
+            | PropertyValue::Cubemap(_) => Vec::new(),
+        };
+
+        components
+            .iter()
+            .all(|x| self.min.map_or(true, |min| *x >= min) && self.max.map_or(true, |max| *x <= max))
+    }
+
+    pub fn validate(&self, value: &PropertyValue) -> Result<(), MaterialErr> {
+        let found = ParameterType::from(value);
+
+        if found != self.parameter_type {
+            return Err(MaterialErr::TypeErr {
+                parameter: self.name.clone(),
+                expected: self.parameter_type,
+                found,
+            });
+        }
+
+        if !self.in_range(value) {
+            return Err(MaterialErr::RangeErr {
+                parameter: self.name.clone(),
+                min: self.min,
+                max: self.max,
+            });
+        }
+
+        Ok(())
+    }
+}
+
+impl MaterialDefinition {
+    pub fn parse(src: &str) -> Result<Self, MaterialDefinitionErr> {
+        let definition: Self = toml::from_str(src).map_err(|e| MaterialDefinitionErr::SrcErr(e.to_string()))?;
+
+        for parameter in definition.parameters.iter() {
+            if let Some(default) = &parameter.default {
+                if parameter.validate(default).is_err() {
+                    return Err(MaterialDefinitionErr::DefaultErr(parameter.name.clone()));
+                }
+            }
+        }
+
+        Ok(definition)
+    }
+
+    pub fn load(path: &str) -> Result<Self, MaterialDefinitionErr> {
+        let src = read_to_string(path).map_err(|_| MaterialDefinitionErr::PathErr)?;
+        Self::parse(src.as_str())
+    }
+
+    pub fn parameter(&self, name: &str) -> Option<&ParameterDefinition> {
+        self.parameters.iter().find(|x| x.name == name)
+    }
+
+    /// check the parameters of material, the result has the defaults of parameters it doesn't set
+    pub fn validate(&self, material: &Material) -> Result<Material, MaterialErr> {
+        if material.name != self.shader {
+            return Err(MaterialErr::ShaderErr(material.name.clone()));
+        }
+
+        for (name, value) in material.property.iter() {
+            match self.parameter(name) {
+                Some(parameter) => parameter.validate(value)?,
+                None => return Err(MaterialErr::ParameterErr {
+                    shader: self.shader.clone(),
+                    parameter: name.clone(),
+                }),
+            }
+        }
+
+        let mut material = material.clone();
+
+        for parameter in self.parameters.iter() {
+            if material.property.iter().any(|(name, _)| *name == parameter.name) {
+                continue;
+            }
+
+            match &parameter.default {
+                Some(default) => material.property.push((parameter.name.clone(), default.clone())),
+                None => return Err(MaterialErr::MissingErr {
+                    shader: self.shader.clone(),
+                    parameter: parameter.name.clone(),
+                }),
+            }
+        }
+
+        Ok(material)
+    }
+}
+
+/// # Material Definitions
+/// definitions by shader name, `bulidin` has the definitions of bulidin materials
+#[derive(Debug,Clone,PartialEq)]
+pub struct MaterialDefinitions {
+    pub definitions: HashMap<String,MaterialDefinition>,
+}
+
+impl MaterialDefinitions {
+    pub fn new() -> Self {
+        Self {
+            definitions: HashMap::new(),
+        }
+    }
+
+    pub fn bulidin() -> Self {
+        let mut definitions = Self::new();
+
+        for src in [
+            include_str!("materials/pure_color_material.toml"),
+            include_str!("materials/blinn_phong_brdf.toml"),
+            include_str!("materials/cook_torrance_brdf.toml"),
+        ].iter() {
+            definitions.insert(MaterialDefinition::parse(src).unwrap());
+        }
+
+        definitions
+    }
+
+    /// replace the definition of the same shader
+    pub fn insert(&mut self, definition: MaterialDefinition) {
+        self.definitions.insert(definition.shader.clone(), definition);
+    }
+
+    pub fn get(&self, shader: &str) -> Option<&MaterialDefinition> {
+        self.definitions.get(shader)
+    }
+
+    pub fn validate(&self, material: &Material) -> Result<Material, MaterialErr> {
+        match self.definitions.get(&material.name) {
+            Some(definition) => definition.validate(material),
+            None => Err(MaterialErr::ShaderErr(material.name.clone())),
+        }
+    }
+}
@@ -0,0 +1,147 @@
+use super::material::{Material, PropertyValue, BlendMode};
+use super::material_definition::{MaterialDefinitions, MaterialErr};
+use serde_derive::{Serialize,Deserialize};
+use std::collections::HashMap;
+
+/// # Material Instance
+/// material with the shader and properties of its parent, the properties it sets override those of parent.
+/// blend mode and opacity are of parent if they are not set
+#[derive(Debug,Clone,PartialEq,Serialize,Deserialize)]
+pub struct MaterialInstance {
+    pub parent: String,
+    #[serde(with = "super::material::property")]
+    pub property: Vec<(String,PropertyValue)>,
+    #[serde(default)]
+    pub blend_mode: Option<BlendMode>,
+    #[serde(default)]
+    pub opacity: Option<f32>,
+}
+
+impl MaterialInstance {
+    pub fn new(parent: &str) -> Self {
+        Self {
+            parent: parent.to_string(),
+            property: Vec::new(),
+            blend_mode: None,
+            opacity: None,
+        }
+    }
+
+    /// override a property, it replaces the value set before
+    pub fn with_property(mut self, name: &str, value: PropertyValue) -> Self {
+        self.property.retain(|(x, _)| x != name);
+        self.property.push((name.to_string(), value));
+        self
+    }
+
+    pub fn with_blend_mode(mut self, blend_mode: BlendMode) -> Self {
+        self.blend_mode = Some(blend_mode);
+        self
+    }
+
+    pub fn with_opacity(mut self, opacity: f32) -> Self {
+        self.opacity = Some(opacity);
+        self
+    }
+
+    /// the parent with overrides applied, in the property order of parent
+    pub fn resolve(&self, parent: &Material) -> Material {
+        let mut material = parent.clone();
+
+        for (name, value) in self.property.iter() {
+            match material.property.iter_mut().find(|(x, _)| x == name) {
+                Some((_, parent_value)) => *parent_value = value.clone(),
+                None => material.property.push((name.clone(), value.clone())),
+            }
+        }
+
+        material.blend_mode = self.blend_mode.unwrap_or(parent.blend_mode);
+        material.opacity = self.opacity.unwrap_or(parent.opacity);
+        material
+    }
+}
+
+/// # Material Instances
+/// instances by name, the parent of an instance is a material and not another instance
+#[derive(Debug,Clone,PartialEq)]
+pub struct MaterialInstances {
+    pub instances: HashMap<String,MaterialInstance>,
+}
+
+impl MaterialInstances {
+    pub fn new() -> Self {
+        Self {
+            instances: HashMap::new(),
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&MaterialInstance> {
+        self.instances.get(name)
+    }
+
+    pub fn insert(&mut self, name: &str, instance: MaterialInstance) {
+        self.instances.insert(name.to_string(), instance);
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<MaterialInstance> {
+        self.instances.remove(name)
+    }
+
+    /// parent of a instance, a material is its own parent
+    pub fn parent<'a>(&'a self, name: &'a str) -> &'a str {
+        match self.instances.get(name) {
+            Some(instance) => instance.parent.as_str(),
+            None => name,
+        }
+    }
+
+    /// names of the instances of parent in name order
+    pub fn instances_of(&self, parent: &str) -> Vec<String> {
+        let mut names: Vec<String> = self.instances
+            .iter()
+            .filter(|(_, instance)| instance.parent == parent)
+            .map(|(name, _)| name.clone())
+            .collect();
+
+        names.sort();
+        names
+    }
+
+    /// validated material of a instance named `name`, its parent is looked up in materials
+    pub fn resolve(
+        &self,
+        name: &str,
+        instance: &MaterialInstance,
+        materials: &HashMap<String,Material>,
+        definitions: &MaterialDefinitions
+    ) -> Result<Material, MaterialErr> {
+        if !self.instances_of(name).is_empty() {
+            return Err(MaterialErr::InstanceErr(name.to_string()));
+        }
+
+        if instance.parent == name || self.instances.contains_key(&instance.parent) {
+            return Err(MaterialErr::ParentErr(instance.parent.clone()));
+        }
+
+        match materials.get(&instance.parent) {
+            Some(parent) => definitions.validate(&instance.resolve(parent)),
+            None => Err(MaterialErr::ParentErr(instance.parent.clone())),
+        }
+    }
+
+    /// validated materials of the instances of parent, they are all valid or the first error is returned
+    pub fn resolve_instances(
+        &self,
+        parent_name: &str,
+        parent: &Material,
+        definitions: &MaterialDefinitions
+    ) -> Result<Vec<(String,Material)>, MaterialErr> {
+        self.instances_of(parent_name)
+            .into_iter()
+            .map(|name| {
+                let material = definitions.validate(&self.instances[&name].resolve(parent))?;
+                Ok((name, material))
+            })
+            .collect()
+    }
+}
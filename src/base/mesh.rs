@@ -1,17 +1,112 @@
 use rmu::vector::{Vector3,Vector2};
+use rmu::raw::Vec4f;
 
 #[derive(Clone)]
 pub struct Mesh {
     pub vertices: Vec<Vector3>,
     pub vertex_normals: Vec<Vector3>,
     pub uv: Vec<Vector2>,
+    /// joint indices of each vertex for skinning, empty if the mesh is not skinned
+    pub joints: Vec<[u32;4]>,
+    /// joint weights of each vertex for skinning
+    pub weights: Vec<Vec4f>,
+    /// blend shapes, the mesh rendered is the base mesh with the weighted deltas added
+    pub morph_targets: Vec<MorphTarget>,
     pub edges: Vec<[u32;2]>,
     /// x is vertex index, y is normal index, z is uv index 
     /// index is start from 1, if index is 0 , it' mean no attribute
     pub faces: Vec<Vec<[u32;3]>>,
 }
 
+/// # Morph Target
+/// named position and normal deltas of a mesh.
+/// `position_deltas` is indexed like `Mesh::vertices` and `normal_deltas` like `Mesh::vertex_normals`,
+/// both may be empty
+#[derive(Clone)]
+pub struct MorphTarget {
+    pub name: String,
+    pub position_deltas: Vec<Vector3>,
+    pub normal_deltas: Vec<Vector3>,
+    pub weight: f32,
+}
+
+impl MorphTarget {
+    pub fn new(name: &str, position_deltas: Vec<Vector3>, normal_deltas: Vec<Vector3>) -> Self {
+        Self {
+            name: name.to_string(),
+            position_deltas,
+            normal_deltas,
+            weight: 0.0,
+        }
+    }
+}
+
 impl Mesh {
+    pub fn add_morph_target(&mut self, morph_target: MorphTarget) {
+        match self.morph_targets.iter_mut().find(|x| x.name == morph_target.name) {
+            Some(old_target) => *old_target = morph_target,
+            None => self.morph_targets.push(morph_target),
+        }
+    }
+
+    /// return false if the morph target is not exist
+    pub fn set_morph_weight(&mut self, name: &str, weight: f32) -> bool {
+        match self.morph_targets.iter_mut().find(|x| x.name == name) {
+            Some(morph_target) => {
+                morph_target.weight = weight;
+                true
+            },
+            None => false,
+        }
+    }
+
+    pub fn morph_weight(&self, name: &str) -> Option<f32> {
+        self.morph_targets.iter().find(|x| x.name == name).map(|x| x.weight)
+    }
+
+    pub fn morph_weights(&self) -> Vec<f32> {
+        self.morph_targets.iter().map(|x| x.weight).collect()
+    }
+
+    /// evaluate the morph targets on CPU, the result has same topology and no morph targets
+    pub fn morphed(&self) -> Mesh {
+        let mut vertices = self.vertices.clone();
+        let mut vertex_normals = self.vertex_normals.clone();
+
+        for morph_target in self.morph_targets.iter() {
+            if morph_target.weight == 0.0 {
+                continue;
+            }
+
+            for (vertex, delta) in vertices.iter_mut().zip(morph_target.position_deltas.iter()) {
+                *vertex = *vertex + morph_target.weight * *delta;
+            }
+
+            for (normal, delta) in vertex_normals.iter_mut().zip(morph_target.normal_deltas.iter()) {
+                *normal = *normal + morph_target.weight * *delta;
+            }
+        }
+
+        if !self.morph_targets.is_empty() {
+            for normal in vertex_normals.iter_mut() {
+                if normal.x != 0.0 || normal.y != 0.0 || normal.z != 0.0 {
+                    *normal = normal.normalized();
+                }
+            }
+        }
+
+        Mesh {
+            vertices,
+            vertex_normals,
+            uv: self.uv.clone(),
+            joints: self.joints.clone(),
+            weights: self.weights.clone(),
+            morph_targets: Vec::new(),
+            edges: self.edges.clone(),
+            faces: self.faces.clone(),
+        }
+    }
+
     pub fn as_bytes(&self) -> Vec<u8> {
         let mut result = Vec::new();
 
@@ -38,6 +133,39 @@ impl Mesh {
             result.push(b'\n');
         }
 
+        for joint in self.joints.iter() {
+            result.push(b'j');
+            for index in joint {
+                append_u32(&mut result, *index);
+            }
+            result.push(b'\n');
+        }
+
+        for weight in self.weights.iter() {
+            result.push(b'w');
+            for value in weight {
+                append_f32(&mut result, *value);
+            }
+            result.push(b'\n');
+        }
+
+        for morph_target in self.morph_targets.iter() {
+            // m: Morph target
+            result.push(b'm');
+            append_u32(&mut result, morph_target.name.len() as u32);
+            result.append(&mut morph_target.name.as_bytes().to_vec());
+            append_f32(&mut result, morph_target.weight);
+            append_u32(&mut result, morph_target.position_deltas.len() as u32);
+            for delta in morph_target.position_deltas.iter() {
+                append_vector3(&mut result, delta);
+            }
+            append_u32(&mut result, morph_target.normal_deltas.len() as u32);
+            for delta in morph_target.normal_deltas.iter() {
+                append_vector3(&mut result, delta);
+            }
+            result.push(b'\n');
+        }
+
         for edge in self.edges.iter() {
             result.push(b'e');
             append_u32(&mut result, edge[0]);
@@ -47,6 +175,7 @@ impl Mesh {
 
         for face in self.faces.iter() {
             result.push(b'f');
+            append_u32(&mut result, face.len() as u32);
             for face_attr in face {
                 append_u32_3(&mut result, face_attr);
             }
@@ -57,8 +186,104 @@ impl Mesh {
     }
 
     pub fn from_bytes(data: &Vec<u8>) -> Option<Mesh> {
-        // TODO: byte to mesh 
-        None
+        let header = b"mesh data\n";
+
+        if !data.starts_with(header) {
+            return None;
+        }
+
+        let mut mesh = Mesh {
+            vertices: Vec::new(),
+            vertex_normals: Vec::new(),
+            uv: Vec::new(),
+            joints: Vec::new(),
+            weights: Vec::new(),
+            morph_targets: Vec::new(),
+            edges: Vec::new(),
+            faces: Vec::new(),
+        };
+
+        let mut reader = ByteReader { data: data.as_slice(), offset: header.len() };
+
+        while let Some(tag) = reader.byte() {
+            match tag {
+                b'v' => mesh.vertices.push(reader.vector3()?),
+                b'n' => mesh.vertex_normals.push(reader.vector3()?),
+                b'c' => mesh.uv.push(Vector2::new(reader.f32()?, reader.f32()?)),
+                b'j' => mesh.joints.push([reader.u32()?, reader.u32()?, reader.u32()?, reader.u32()?]),
+                b'w' => mesh.weights.push([reader.f32()?, reader.f32()?, reader.f32()?, reader.f32()?]),
+                b'm' => {
+                    let name_length = reader.u32()? as usize;
+                    let name = String::from_utf8(reader.bytes(name_length)?.to_vec()).ok()?;
+                    let weight = reader.f32()?;
+
+                    let mut position_deltas = Vec::new();
+                    for _ in 0..reader.u32()? {
+                        position_deltas.push(reader.vector3()?);
+                    }
+
+                    let mut normal_deltas = Vec::new();
+                    for _ in 0..reader.u32()? {
+                        normal_deltas.push(reader.vector3()?);
+                    }
+
+                    mesh.morph_targets.push(MorphTarget { name, position_deltas, normal_deltas, weight });
+                },
+                b'e' => mesh.edges.push([reader.u32()?, reader.u32()?]),
+                b'f' => {
+                    let count = reader.u32()?;
+                    let mut face = Vec::new();
+                    for _ in 0..count {
+                        face.push([reader.u32()?, reader.u32()?, reader.u32()?]);
+                    }
+                    mesh.faces.push(face);
+                },
+                _ => return None,
+            }
+
+            if reader.byte()? != b'\n' {
+                return None;
+            }
+        }
+
+        Some(mesh)
+    }
+}
+
+struct ByteReader<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> ByteReader<'a> {
+    fn byte(&mut self) -> Option<u8> {
+        let byte = *self.data.get(self.offset)?;
+        self.offset += 1;
+        Some(byte)
+    }
+
+    fn bytes(&mut self, length: usize) -> Option<&'a [u8]> {
+        let bytes = self.data.get(self.offset..self.offset + length)?;
+        self.offset += length;
+        Some(bytes)
+    }
+
+    fn bytes4(&mut self) -> Option<[u8;4]> {
+        let bytes = self.data.get(self.offset..self.offset + 4)?;
+        self.offset += 4;
+        Some([bytes[0], bytes[1], bytes[2], bytes[3]])
+    }
+
+    fn f32(&mut self) -> Option<f32> {
+        Some(f32::from_le_bytes(self.bytes4()?))
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        Some(u32::from_le_bytes(self.bytes4()?))
+    }
+
+    fn vector3(&mut self) -> Option<Vector3> {
+        Some(Vector3::new(self.f32()?, self.f32()?, self.f32()?))
     }
 }
 
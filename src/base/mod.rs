@@ -2,6 +2,7 @@
 pub mod vertex;
 pub mod index;
 pub mod transform;
+pub mod quaternion;
 pub mod color;
 pub mod camera;
 pub mod utils;
@@ -10,6 +11,8 @@ pub mod either;
 pub mod mesh;
 pub mod light;
 pub mod material;
+pub mod material_definition;
+pub mod material_instance;
 
 pub mod font;
 
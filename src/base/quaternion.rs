@@ -0,0 +1,181 @@
+use rmu::raw::{Vec3f,Vec4f,Mat4f};
+use serde_derive::{Serialize,Deserialize};
+
+/// unit quaternion for rotation, `w` is the real part
+#[derive(Debug,Copy,Clone,PartialEq,Serialize,Deserialize)]
+pub struct Quaternion {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    pub w: f32,
+}
+
+impl Quaternion {
+    #[inline]
+    pub fn new(x: f32, y: f32, z: f32, w: f32) -> Self {
+        Self { x, y, z, w }
+    }
+
+    #[inline]
+    pub fn identity() -> Self {
+        Self::new(0.0, 0.0, 0.0, 1.0)
+    }
+
+    /// rotation of `angle` radian around `axis`
+    pub fn from_axis_angle(axis: Vec3f, angle: f32) -> Self {
+        let length = (axis[0] * axis[0] + axis[1] * axis[1] + axis[2] * axis[2]).sqrt();
+        if length == 0.0 {
+            return Self::identity();
+        }
+
+        let s = (angle / 2.0).sin() / length;
+        Self::new(axis[0] * s, axis[1] * s, axis[2] * s, (angle / 2.0).cos())
+    }
+
+    /// from the z-x-z euler angles of `Transform::rotation`
+    pub fn from_euler(rotation: Vec3f) -> Self {
+        let z1 = Self::from_axis_angle([0.0, 0.0, 1.0], rotation[0]);
+        let x = Self::from_axis_angle([1.0, 0.0, 0.0], rotation[1]);
+        let z2 = Self::from_axis_angle([0.0, 0.0, 1.0], rotation[2]);
+        z1 * x * z2
+    }
+
+    /// to the z-x-z euler angles of `Transform::rotation`
+    pub fn to_euler(&self) -> Vec3f {
+        let m = self.rotation_matrix();
+
+        let y = m[2][2].max(-1.0).min(1.0).acos();
+        if y.sin().abs() > 1e-6 {
+            let x = m[0][2].atan2(-m[1][2]);
+            let z = m[2][0].atan2(m[2][1]);
+            [x, y, z]
+        } else {
+            // gimbal lock, only x + z or x - z is determined
+            let x = m[1][0].atan2(m[0][0]);
+            [x, y, 0.0]
+        }
+    }
+
+    pub fn dot(a: Self, b: Self) -> f32 {
+        a.x * b.x + a.y * b.y + a.z * b.z + a.w * b.w
+    }
+
+    pub fn normalized(&self) -> Self {
+        let length = Self::dot(*self, *self).sqrt();
+        if length == 0.0 {
+            Self::identity()
+        } else {
+            Self::new(self.x / length, self.y / length, self.z / length, self.w / length)
+        }
+    }
+
+    pub fn conjugate(&self) -> Self {
+        Self::new(-self.x, -self.y, -self.z, self.w)
+    }
+
+    /// normalized linear interpolation on the shortest path
+    pub fn nlerp(a: Self, b: Self, t: f32) -> Self {
+        let b = if Self::dot(a, b) < 0.0 { -b } else { b };
+        Self::new(
+            a.x + (b.x - a.x) * t,
+            a.y + (b.y - a.y) * t,
+            a.z + (b.z - a.z) * t,
+            a.w + (b.w - a.w) * t,
+        ).normalized()
+    }
+
+    /// spherical linear interpolation on the shortest path
+    pub fn slerp(a: Self, b: Self, t: f32) -> Self {
+        let mut cos_theta = Self::dot(a, b);
+        let b = if cos_theta < 0.0 {
+            cos_theta = -cos_theta;
+            -b
+        } else {
+            b
+        };
+
+        if cos_theta > 0.9995 {
+            return Self::nlerp(a, b, t);
+        }
+
+        let theta = cos_theta.acos();
+        let sin_theta = theta.sin();
+        let wa = ((1.0 - t) * theta).sin() / sin_theta;
+        let wb = (t * theta).sin() / sin_theta;
+
+        Self::new(
+            a.x * wa + b.x * wb,
+            a.y * wa + b.y * wb,
+            a.z * wa + b.z * wb,
+            a.w * wa + b.w * wb,
+        )
+    }
+
+    /// rotation matrix indexed by [row][column]
+    pub fn rotation_matrix(&self) -> [[f32;3];3] {
+        let Self { x, y, z, w } = self.normalized();
+
+        [[1.0 - 2.0 * (y * y + z * z), 2.0 * (x * y - z * w)      , 2.0 * (x * z + y * w)      ]
+        ,[2.0 * (x * y + z * w)      , 1.0 - 2.0 * (x * x + z * z), 2.0 * (y * z - x * w)      ]
+        ,[2.0 * (x * z - y * w)      , 2.0 * (y * z + x * w)      , 1.0 - 2.0 * (x * x + y * y)]]
+    }
+
+    /// column major 4x4 matrix for GPU
+    pub fn matrix(&self) -> Mat4f {
+        let m = self.rotation_matrix();
+
+        [[m[0][0], m[1][0], m[2][0], 0.0]
+        ,[m[0][1], m[1][1], m[2][1], 0.0]
+        ,[m[0][2], m[1][2], m[2][2], 0.0]
+        ,[  0.0  ,   0.0  ,   0.0  , 1.0]]
+    }
+
+    pub fn rotate(&self, v: Vec3f) -> Vec3f {
+        let m = self.rotation_matrix();
+
+        [m[0][0] * v[0] + m[0][1] * v[1] + m[0][2] * v[2]
+        ,m[1][0] * v[0] + m[1][1] * v[1] + m[1][2] * v[2]
+        ,m[2][0] * v[0] + m[2][1] * v[1] + m[2][2] * v[2]]
+    }
+}
+
+impl Default for Quaternion {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+use std::ops::{Mul, Neg};
+
+impl Mul for Quaternion {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        Self::new(
+            self.w * rhs.x + self.x * rhs.w + self.y * rhs.z - self.z * rhs.y,
+            self.w * rhs.y - self.x * rhs.z + self.y * rhs.w + self.z * rhs.x,
+            self.w * rhs.z + self.x * rhs.y - self.y * rhs.x + self.z * rhs.w,
+            self.w * rhs.w - self.x * rhs.x - self.y * rhs.y - self.z * rhs.z,
+        )
+    }
+}
+
+impl Neg for Quaternion {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self::new(-self.x, -self.y, -self.z, -self.w)
+    }
+}
+
+impl From<Vec4f> for Quaternion {
+    fn from(v: Vec4f) -> Self {
+        Self::new(v[0], v[1], v[2], v[3])
+    }
+}
+
+impl From<Quaternion> for Vec4f {
+    fn from(q: Quaternion) -> Self {
+        [q.x, q.y, q.z, q.w]
+    }
+}
@@ -37,7 +37,7 @@ impl Default for Area {
 }
 
 /// a data with height and width 
-#[derive(Debug,Clone,Copy)]
+#[derive(Debug,Clone,Copy,PartialEq)]
 pub struct Size {
     pub width: f32,
     pub height: f32,
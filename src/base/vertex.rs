@@ -1,8 +1,8 @@
-use rmu::raw::{Vec2f, Vec3f};
+use rmu::raw::{Vec2f, Vec3f, Vec4f};
 use rmu::vector::{Vector3,Vector2};
 
 //3d vertex coordination
-#[derive(Debug,Copy,Clone)]
+#[derive(Debug,Copy,Clone,PartialEq)]
 pub struct Vertex {
     pub position: Vec3f,
     pub normal: Vec3f,
@@ -34,6 +34,34 @@ impl From<Vector3> for Vertex {
     }
 }
 
+//3d vertex with joint indices and weights for linear blend skinning
+#[derive(Debug,Copy,Clone,PartialEq)]
+pub struct SkinnedVertex {
+    pub position: Vec3f,
+    pub normal: Vec3f,
+    pub tex_coordinate: Vec2f,
+    pub joints: [u32;4],
+    pub weights: Vec4f,
+}
+
+impl SkinnedVertex {
+    #[inline]
+    pub fn new(vertex: Vertex, joints: [u32;4], weights: Vec4f) -> Self {
+        Self {
+            position: vertex.position,
+            normal: vertex.normal,
+            tex_coordinate: vertex.tex_coordinate,
+            joints,
+            weights,
+        }
+    }
+
+    #[inline]
+    pub fn vertex(&self) -> Vertex {
+        Vertex::new(self.position, self.normal, self.tex_coordinate)
+    }
+}
+
 // 2d  Vertex coordination
 #[derive(Debug,Copy,Clone)]
 pub struct Position {
@@ -7,6 +7,7 @@ pub mod asset;
 pub mod renderer;
 
 pub mod scene;
+pub mod animation;
 pub mod graphics;
 
 pub mod model;
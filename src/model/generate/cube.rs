@@ -34,5 +34,8 @@ pub fn cube(length: f32) -> Mesh {
                     vec![[3,5,0], [7,5,0], [8,5,0], [4,6,0]], 
                     vec![[4,6,0], [8,6,0], [5,6,0], [1,6,0]]],
         uv: Vec::new(),
+        joints: Vec::new(),
+        weights: Vec::new(),
+        morph_targets: Vec::new(),
     }
 } 
\ No newline at end of file
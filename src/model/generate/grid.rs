@@ -30,6 +30,9 @@ pub fn grid(step: f32, row: usize, column: usize) -> Mesh {
         vertices,
         vertex_normals: Vec::new(),
         uv: Vec::new(),
+        joints: Vec::new(),
+        weights: Vec::new(),
+        morph_targets: Vec::new(),
         edges: Vec::new(),
         faces: Vec::new(),
     }
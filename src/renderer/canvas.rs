@@ -36,6 +36,7 @@ use crate::base::utils::Size;
 
 pub type Scale = Size;
 
+#[derive(Debug,Copy,Clone,PartialEq)]
 pub enum Align {
     Left,
     Center,
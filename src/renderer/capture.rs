@@ -0,0 +1,399 @@
+use super::image::*;
+use super::renderer::Renderer;
+use std::fmt;
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug,Copy,Clone,PartialEq)]
+pub enum CaptureFormat {
+    Png,
+    /// binary portable pixmap, alpha is dropped
+    Ppm,
+}
+
+impl CaptureFormat {
+    /// format from the extension of path
+    pub fn from_path(path: &str) -> Option<Self> {
+        let extension = Path::new(path).extension()?.to_str()?.to_lowercase();
+        match extension.as_str() {
+            "png" => Some(CaptureFormat::Png),
+            "ppm" => Some(CaptureFormat::Ppm),
+            _ => None,
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            CaptureFormat::Png => "png",
+            CaptureFormat::Ppm => "ppm",
+        }
+    }
+}
+
+#[derive(Debug,Clone,PartialEq)]
+pub enum CaptureErr {
+    /// extension of path is not png or ppm
+    FormatErr,
+    PathErr,
+    /// render target is not exist
+    TargetErr(String),
+}
+
+impl fmt::Display for CaptureErr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CaptureErr::FormatErr => write!(f, "capture format is not png or ppm"),
+            CaptureErr::PathErr => write!(f, "capture file can't be written"),
+            CaptureErr::TargetErr(name) => write!(f, "render target {} does not exist", name),
+        }
+    }
+}
+
+impl std::error::Error for CaptureErr {}
+
+impl Image {
+    /// rows from top to bottom, hdr images are clamped to 8 bits
+    pub fn encode(&self, format: CaptureFormat) -> Vec<u8> {
+        if self.image_type == ImageType::F32F32F32 {
+            let data = self.rgb_f32()
+                .iter()
+                .flat_map(|x| x.iter().map(|x| (x.max(0.0).min(1.0) * 255.0).round() as u8).collect::<Vec<u8>>())
+                .collect();
+            let image = Image { data, dimensions: self.dimensions, image_type: ImageType::U8U8U8 };
+            return image.encode(format);
+        }
+
+        match format {
+            CaptureFormat::Png => self.png(),
+            CaptureFormat::Ppm => self.ppm(),
+        }
+    }
+
+    /// save image as png or ppm by the extension of path
+    pub fn save(&self, path: &str) -> Result<(),CaptureErr> {
+        let format = CaptureFormat::from_path(path).ok_or(CaptureErr::FormatErr)?;
+        let data = self.encode(format);
+
+        match File::create(path) {
+            Ok(mut file) => file.write_all(&data).map_err(|_| CaptureErr::PathErr),
+            Err(_) => Err(CaptureErr::PathErr),
+        }
+    }
+
+    fn channels(&self) -> usize {
+        match self.image_type {
+            ImageType::U8 => 1,
+            ImageType::U8U8U8 | ImageType::F32F32F32 => 3,
+            ImageType::U8U8U8U8 => 4,
+        }
+    }
+
+    fn ppm(&self) -> Vec<u8> {
+        let (width, height) = self.dimensions;
+        let channels = self.channels();
+
+        let mut result = format!("P6\n{} {}\n255\n", width, height).into_bytes();
+
+        for pixel in self.data.chunks(channels).take((width * height) as usize) {
+            match channels {
+                1 => result.extend_from_slice(&[pixel[0], pixel[0], pixel[0]]),
+                _ => result.extend_from_slice(&pixel[0..3]),
+            }
+        }
+
+        result
+    }
+
+    /// png with uncompressed deflate blocks, it is large but need no compression library
+    fn png(&self) -> Vec<u8> {
+        let (width, height) = self.dimensions;
+        let channels = self.channels();
+        let color_type = match self.image_type {
+            ImageType::U8 => 0,
+            ImageType::U8U8U8 | ImageType::F32F32F32 => 2,
+            ImageType::U8U8U8U8 => 6,
+        };
+
+        let mut result = b"\x89PNG\r\n\x1a\n".to_vec();
+
+        let mut header = Vec::new();
+        header.extend_from_slice(&width.to_be_bytes());
+        header.extend_from_slice(&height.to_be_bytes());
+        header.extend_from_slice(&[8, color_type, 0, 0, 0]);
+        png_chunk(&mut result, b"IHDR", &header);
+
+        /* each row start with filter type 0 */
+        let row_length = width as usize * channels;
+        let mut raw = Vec::with_capacity((row_length + 1) * height as usize);
+        for row in self.data.chunks(row_length).take(height as usize) {
+            raw.push(0);
+            raw.extend_from_slice(row);
+        }
+
+        png_chunk(&mut result, b"IDAT", &zlib_stored(&raw));
+        png_chunk(&mut result, b"IEND", &[]);
+
+        result
+    }
+}
+
+fn png_chunk(result: &mut Vec<u8>, chunk_type: &[u8;4], data: &[u8]) {
+    result.extend_from_slice(&(data.len() as u32).to_be_bytes());
+
+    let start = result.len();
+    result.extend_from_slice(chunk_type);
+    result.extend_from_slice(data);
+
+    let crc = crc32(&result[start..]);
+    result.extend_from_slice(&crc.to_be_bytes());
+}
+
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    let mut result = vec![0x78, 0x01];
+
+    let blocks: Vec<&[u8]> = data.chunks(65535).collect();
+    if blocks.is_empty() {
+        result.extend_from_slice(&[1, 0, 0, 0xff, 0xff]);
+    }
+
+    for (i, block) in blocks.iter().enumerate() {
+        let last = if i + 1 == blocks.len() { 1 } else { 0 };
+        let length = block.len() as u16;
+        result.push(last);
+        result.extend_from_slice(&length.to_le_bytes());
+        result.extend_from_slice(&(!length).to_le_bytes());
+        result.extend_from_slice(block);
+    }
+
+    result.extend_from_slice(&adler32(data).to_be_bytes());
+    result
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xffff_ffffu32;
+    for byte in data {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 { (crc >> 1) ^ 0xedb8_8320 } else { crc >> 1 };
+        }
+    }
+    !crc
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for byte in data {
+        a = (a + *byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+    (b << 16) | a
+}
+
+/// # Frame Recorder
+/// write numbered frames for a fixed simulated timestep, e.g. 1/60 second,
+/// drive the scene with `time()` instead of the wall clock so that the video speed
+/// is independent of how long a frame takes
+pub struct FrameRecorder {
+    pub directory: PathBuf,
+    pub prefix: String,
+    pub format: CaptureFormat,
+    pub timestep: f32,
+    pub frame_index: u32,
+}
+
+impl FrameRecorder {
+    pub fn new(directory: &str, timestep: f32) -> Self {
+        Self {
+            directory: PathBuf::from(directory),
+            prefix: String::from("frame"),
+            format: CaptureFormat::Png,
+            timestep,
+            frame_index: 0,
+        }
+    }
+
+    pub fn with_prefix(mut self, prefix: &str) -> Self {
+        self.prefix = prefix.to_string();
+        self
+    }
+
+    pub fn with_format(mut self, format: CaptureFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// simulated time of the next frame
+    pub fn time(&self) -> f32 {
+        self.frame_index as f32 * self.timestep
+    }
+
+    pub fn frame_path(&self, frame_index: u32) -> PathBuf {
+        self.directory.join(format!("{}_{:06}.{}", self.prefix, frame_index, self.format.extension()))
+    }
+
+    /// write the image as next frame and advance the simulated time
+    pub fn record(&mut self, image: &Image) -> Result<PathBuf,CaptureErr> {
+        let path = self.frame_path(self.frame_index);
+
+        match File::create(&path) {
+            Ok(mut file) => file.write_all(&image.encode(self.format)).map_err(|_| CaptureErr::PathErr)?,
+            Err(_) => return Err(CaptureErr::PathErr),
+        }
+
+        self.frame_index += 1;
+        Ok(path)
+    }
+
+    /// read back the current frame of renderer and record it
+    pub fn capture<R: Renderer>(&mut self, renderer: &mut R) -> Result<PathBuf,CaptureErr> {
+        let image = renderer.read_frame();
+        self.record(&image)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::{create_dir_all, read, remove_dir_all};
+
+    fn image(data: Vec<u8>, dimensions: (u32,u32), image_type: ImageType) -> Image {
+        Image { data, dimensions, image_type }
+    }
+
+    fn be_u32(data: &[u8]) -> u32 {
+        u32::from_be_bytes([data[0], data[1], data[2], data[3]])
+    }
+
+    /* chunk types and data of a png, the crc of every chunk is checked */
+    fn png_chunks(png: &[u8]) -> Vec<([u8;4], Vec<u8>)> {
+        assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+
+        let mut chunks = Vec::new();
+        let mut i = 8;
+        while i < png.len() {
+            let length = be_u32(&png[i..]) as usize;
+            let chunk = &png[i + 4..i + 8 + length];
+            assert_eq!(be_u32(&png[i + 8 + length..]), crc32(chunk));
+
+            chunks.push(([chunk[0], chunk[1], chunk[2], chunk[3]], chunk[4..].to_vec()));
+            i += 12 + length;
+        }
+        chunks
+    }
+
+    /* data and block lengths of a zlib stream of stored blocks */
+    fn inflate_stored(zlib: &[u8]) -> (Vec<u8>, Vec<usize>) {
+        assert_eq!(&zlib[..2], &[0x78, 0x01]);
+
+        let mut data = Vec::new();
+        let mut blocks = Vec::new();
+        let mut i = 2;
+        loop {
+            let last = zlib[i];
+            let length = u16::from_le_bytes([zlib[i + 1], zlib[i + 2]]);
+            assert_eq!(u16::from_le_bytes([zlib[i + 3], zlib[i + 4]]), !length);
+
+            data.extend_from_slice(&zlib[i + 5..i + 5 + length as usize]);
+            blocks.push(length as usize);
+            i += 5 + length as usize;
+
+            if last == 1 {
+                break;
+            }
+        }
+
+        assert_eq!(be_u32(&zlib[i..]), adler32(&data));
+        assert_eq!(zlib.len(), i + 4);
+        (data, blocks)
+    }
+
+    #[test]
+    fn checksums() {
+        assert_eq!(crc32(b""), 0);
+        assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+        assert_eq!(crc32(b"IEND"), 0xae42_6082);
+        assert_eq!(adler32(b""), 1);
+        assert_eq!(adler32(b"Wikipedia"), 0x11e6_0398);
+    }
+
+    #[test]
+    fn png() {
+        let rgb = image(vec![255, 0, 0, 0, 255, 0, 0, 0, 255, 9, 9, 9], (2, 2), ImageType::U8U8U8);
+        let chunks = png_chunks(&rgb.encode(CaptureFormat::Png));
+
+        assert_eq!(chunks.len(), 3);
+        assert_eq!(&chunks[0].0, b"IHDR");
+        assert_eq!(chunks[0].1, vec![0, 0, 0, 2, 0, 0, 0, 2, 8, 2, 0, 0, 0]);
+        assert_eq!(&chunks[1].0, b"IDAT");
+        assert_eq!(&chunks[2].0, b"IEND");
+        assert!(chunks[2].1.is_empty());
+
+        let (raw, blocks) = inflate_stored(&chunks[1].1);
+        assert_eq!(raw, vec![0, 255, 0, 0, 0, 255, 0, 0, 0, 0, 255, 9, 9, 9]);
+        assert_eq!(blocks, vec![14]);
+
+        let rgba = image(vec![1, 2, 3, 4], (1, 1), ImageType::U8U8U8U8);
+        assert_eq!(png_chunks(&rgba.encode(CaptureFormat::Png))[0].1[9], 6);
+    }
+
+    #[test]
+    fn png_blocks() {
+        /* 300 rows of 1 filter byte and 300 gray bytes, more than one stored block */
+        let data: Vec<u8> = (0..300 * 300).map(|x| (x % 251) as u8).collect();
+        let gray = image(data.clone(), (300, 300), ImageType::U8);
+        let chunks = png_chunks(&gray.encode(CaptureFormat::Png));
+
+        assert_eq!(chunks[0].1[9], 0);
+
+        let (raw, blocks) = inflate_stored(&chunks[1].1);
+        assert_eq!(blocks, vec![65535, 301 * 300 - 65535]);
+
+        let rows: Vec<u8> = data.chunks(300).flat_map(|row| std::iter::once(0).chain(row.iter().cloned())).collect();
+        assert_eq!(raw, rows);
+    }
+
+    #[test]
+    fn ppm() {
+        let rgba = image(vec![1, 2, 3, 4, 5, 6, 7, 8], (2, 1), ImageType::U8U8U8U8);
+        assert_eq!(rgba.encode(CaptureFormat::Ppm), b"P6\n2 1\n255\n\x01\x02\x03\x05\x06\x07".to_vec());
+
+        let gray = image(vec![7, 9], (1, 2), ImageType::U8);
+        assert_eq!(gray.encode(CaptureFormat::Ppm), b"P6\n1 2\n255\n\x07\x07\x07\x09\x09\x09".to_vec());
+
+        let hdr = Image::hdr(&[[2.0, 0.5, -1.0]], (1, 1));
+        assert_eq!(hdr.encode(CaptureFormat::Ppm), b"P6\n1 1\n255\n\xff\x80\x00".to_vec());
+    }
+
+    #[test]
+    fn format_from_path() {
+        assert_eq!(CaptureFormat::from_path("shot.png"), Some(CaptureFormat::Png));
+        assert_eq!(CaptureFormat::from_path("dir/SHOT.PPM"), Some(CaptureFormat::Ppm));
+        assert_eq!(CaptureFormat::from_path("shot.jpg"), None);
+        assert_eq!(CaptureFormat::from_path("shot"), None);
+        assert_eq!(image(vec![0], (1, 1), ImageType::U8).save("shot.jpg"), Err(CaptureErr::FormatErr));
+    }
+
+    #[test]
+    fn frame_recorder() {
+        let directory = std::env::temp_dir().join(format!("frame_recorder_{}", std::process::id()));
+        create_dir_all(&directory).unwrap();
+
+        let mut recorder = FrameRecorder::new(directory.to_str().unwrap(), 0.5)
+            .with_prefix("shot")
+            .with_format(CaptureFormat::Ppm);
+
+        assert_eq!(recorder.frame_path(12), directory.join("shot_000012.ppm"));
+        assert_eq!(recorder.time(), 0.0);
+
+        let frame = image(vec![1, 2, 3], (1, 1), ImageType::U8U8U8);
+        assert_eq!(recorder.record(&frame).unwrap(), directory.join("shot_000000.ppm"));
+        assert_eq!(recorder.record(&frame).unwrap(), directory.join("shot_000001.ppm"));
+
+        assert_eq!(recorder.frame_index, 2);
+        assert_eq!(recorder.time(), 1.0);
+        assert_eq!(read(directory.join("shot_000001.ppm")).unwrap(), frame.encode(CaptureFormat::Ppm));
+
+        remove_dir_all(&directory).unwrap();
+    }
+}
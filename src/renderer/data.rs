@@ -1,8 +1,9 @@
-use crate::base::Vertex;
+use crate::base::{Vertex, SkinnedVertex};
+use crate::base::mesh::Mesh;
 use crate::base::Indices;
-use rmu::raw::Mat4f;
+use rmu::raw::{Mat4f, Vec4f};
 
-#[derive(Copy,Clone,PartialEq)]
+#[derive(Debug,Copy,Clone,PartialEq)]
 pub enum DataUpdate {
     ALL,
     Material,
@@ -11,7 +12,7 @@ pub enum DataUpdate {
     Not,
 }
 
-#[derive(Clone)]
+#[derive(Debug,Clone,PartialEq)]
 pub struct Object {
     pub mesh_name: String,
     pub material_name: String,
@@ -31,6 +32,38 @@ impl Object {
     }
 }
 
+/// distance from the camera to the origin of `transform` along the view direction,
+/// `view` is the column major view matrix of camera
+pub fn view_depth(view: &Mat4f, transform: &Mat4f) -> f32 {
+    let [x, y, z, _] = transform[3];
+    -(view[0][2] * x + view[1][2] * y + view[2][2] * z + view[3][2])
+}
+
+/// a copy of a object mesh drawn by one instanced draw call,
+/// the transform is applied after the object transform
+#[derive(Debug,Copy,Clone,PartialEq)]
+pub struct Instance {
+    pub transform: Mat4f,
+    /// multiplied with the output color
+    pub tint: Vec4f,
+}
+
+impl Instance {
+    #[inline]
+    pub fn new(transform: Mat4f) -> Self {
+        Self {
+            transform,
+            tint: [1.0, 1.0, 1.0, 1.0],
+        }
+    }
+
+    #[inline]
+    pub fn with_tint(mut self, tint: Vec4f) -> Self {
+        self.tint = tint;
+        self
+    }
+}
+
 pub struct GMesh {
     pub vertices: Vec<Vertex>,
     pub indices: Indices,
@@ -44,4 +77,38 @@ impl GMesh {
             indices,
         }
     }
-}
\ No newline at end of file
+
+    /// evaluate the morph targets of mesh on CPU then load it, e.g. `GMesh::morph(&mesh, GMesh::flat)`.
+    /// the indices only depend on the topology, changing weights only changes the vertices
+    pub fn morph(mesh: &Mesh, mesh_load: fn(&Mesh) -> GMesh) -> Self {
+        mesh_load(&mesh.morphed())
+    }
+}
+
+pub struct GSkinnedMesh {
+    pub vertices: Vec<SkinnedVertex>,
+    pub indices: Indices,
+}
+
+impl GSkinnedMesh {
+    #[inline]
+    pub fn new(vertices: Vec<SkinnedVertex>, indices: Indices) -> Self {
+        Self {
+            vertices,
+            indices,
+        }
+    }
+
+    /// triangle faces with a vertex for each face corner, like `MeshLoad::flat`
+    pub fn flat(mesh: &Mesh) -> Self {
+        let (vertices, indices) = flat_vertices(mesh, |vertex, v| {
+            let joints = mesh.joints.get(v).cloned().unwrap_or([0, 0, 0, 0]);
+            let weights = mesh.weights.get(v).cloned().unwrap_or([0.0, 0.0, 0.0, 0.0]);
+            SkinnedVertex::new(vertex, joints, weights)
+        });
+
+        Self::new(vertices, Indices::TriangleFace(indices))
+    }
+}
+
+use super::mesh_load::flat_vertices;
@@ -1,12 +1,57 @@
+use rmu::raw::Vec3f;
+
+#[derive(Debug,Clone,PartialEq)]
 pub struct Image {
     pub data: Vec<u8>,
     pub dimensions: (u32,u32),
     pub image_type: ImageType,
 }
 
-#[derive(Copy,Clone)]
+#[derive(Debug,Copy,Clone,PartialEq)]
 pub enum ImageType {
     U8,
     U8U8U8,
     U8U8U8U8,
+    /// rgb f32 in native byte order, for hdr images
+    F32F32F32,
+}
+
+impl Image {
+    /// hdr image of rgb pixels
+    pub fn hdr(pixels: &[Vec3f], dimensions: (u32,u32)) -> Self {
+        let mut data = Vec::with_capacity(pixels.len() * 12);
+        for pixel in pixels.iter() {
+            for value in pixel.iter() {
+                data.extend_from_slice(&value.to_ne_bytes());
+            }
+        }
+
+        Self {
+            data,
+            dimensions,
+            image_type: ImageType::F32F32F32,
+        }
+    }
+
+    /// rgb of pixels, u8 values are divided by 255
+    pub fn rgb_f32(&self) -> Vec<Vec3f> {
+        match self.image_type {
+            ImageType::F32F32F32 => self.data
+                .chunks_exact(12)
+                .map(|x| {
+                    let value = |i: usize| f32::from_ne_bytes([x[i], x[i + 1], x[i + 2], x[i + 3]]);
+                    [value(0), value(4), value(8)]
+                })
+                .collect(),
+            ImageType::U8 => self.data.iter().map(|x| [*x as f32 / 255.0; 3]).collect(),
+            ImageType::U8U8U8 => self.data
+                .chunks_exact(3)
+                .map(|x| [x[0] as f32 / 255.0, x[1] as f32 / 255.0, x[2] as f32 / 255.0])
+                .collect(),
+            ImageType::U8U8U8U8 => self.data
+                .chunks_exact(4)
+                .map(|x| [x[0] as f32 / 255.0, x[1] as f32 / 255.0, x[2] as f32 / 255.0])
+                .collect(),
+        }
+    }
 }
\ No newline at end of file
@@ -1,15 +1,19 @@
 use rmu::raw::{Vec3f,Vec4f};
+use crate::base::light as base;
+use crate::base::light::{LightType, LightUnit};
 
-#[derive(Copy,Clone)]
+/// light data packed for GPU, keep all field of `base::light::Light`
+/// and the conversion to physical units is done in shader
+#[derive(Debug,Copy,Clone,PartialEq)]
 pub struct Light {
-    pub color_flux: Vec4f, //w is luminous flux
-    pub position: Vec4f,
+    pub color_intensity: Vec4f, // w is intensity
+    pub position_range: Vec4f, // w is range
     pub direction_type: Vec4f, // w is light type
     // 0.0 is point light 1.0 is spot light 2.0 is parallel light
-    pub cut_off: f32,
-    pub outer_cut_off: f32,
-    pub linear: f32,
-    pub quadratic: f32,
+    pub inner_angle: f32,
+    pub outer_angle: f32,
+    pub unit: f32, // 0.0 is lumen 1.0 is candela 2.0 is lux
+    pub padding: f32,
 }
 
 impl Light {
@@ -18,44 +22,25 @@ impl Light {
         Self::point_light([1.0, 1.0, 1.0],[10.0,10.0,10.0])
     }
 
+    /// point light of 800 lumen
     pub fn point_light(color: Vec3f, position: Vec3f) -> Self {
-        Self {
-            color_flux: [color[0], color[1], color[2], 1.0],
-            position: [position[0], position[1], position[2], 0.0],
-            direction_type: [0.0, 0.0, 0.0, 0.0],
-            cut_off: Default::default(),
-            outer_cut_off: Default::default(),
-            linear: 0.09,
-            quadratic: 0.032,
-        }
+        base::Light::point(position, color, 800.0).into()
     }
 
+    /// parallel light of 10 lux
     pub fn parallel_light(color: Vec3f, position: Vec3f, direction: Vec3f) -> Self {
-        Self {
-            color_flux: [color[0], color[1], color[2], 1.0],
-            position: [position[0], position[1], position[2], 0.0],
-            direction_type: [direction[0],direction[1],direction[2],2.0],
-            cut_off: Default::default(),
-            outer_cut_off: Default::default(),
-            linear: 0.09,
-            quadratic: 0.032,
-        }
+        let mut light = base::Light::parallel(direction, color, 10.0);
+        light.position = position;
+        light.into()
     }
 
+    /// spot light of 800 lumen
     pub fn spot_light(color: Vec3f ,position: Vec3f, direction: Vec3f, phi: f32, dim: f32) -> Self {
-        Self {
-            color_flux: [color[0],color[1],color[2],1.0],
-            position: [position[0],position[1],position[2],0.0],
-            direction_type: [direction[0],direction[1],direction[2],1.0],
-            cut_off: phi.cos(),
-            outer_cut_off: (phi + dim).cos(),
-            linear: 0.09,
-            quadratic: 0.032,
-        }
+        base::Light::spot(position, direction, phi, phi + dim, color, 800.0).into()
     }
 
     pub fn position(&self) -> Vec3f {
-        [self.position[0],self.position[1], self.position[2]]
+        [self.position_range[0],self.position_range[1], self.position_range[2]]
     }
 
     pub fn direction(&self) -> Vec3f {
@@ -69,4 +54,64 @@ impl Light {
             false
         }
     }
-}
\ No newline at end of file
+}
+
+impl From<base::Light> for Light {
+    fn from(light: base::Light) -> Self {
+        let [r, g, b] = light.color;
+        let [x, y, z] = light.position;
+        let [dx, dy, dz] = light.direction;
+
+        let (light_type, inner_angle, outer_angle) = match light.light_type {
+            LightType::Point => (0.0, 0.0, 0.0),
+            LightType::Spot { inner_angle, outer_angle } => (1.0, inner_angle, outer_angle),
+            LightType::Parallel => (2.0, 0.0, 0.0),
+        };
+
+        let unit = match light.unit {
+            LightUnit::Lumen => 0.0,
+            LightUnit::Candela => 1.0,
+            LightUnit::Lux => 2.0,
+        };
+
+        Self {
+            color_intensity: [r, g, b, light.intensity],
+            position_range: [x, y, z, light.range],
+            direction_type: [dx, dy, dz, light_type],
+            inner_angle,
+            outer_angle,
+            unit,
+            padding: 0.0,
+        }
+    }
+}
+
+impl From<Light> for base::Light {
+    fn from(light: Light) -> Self {
+        let light_type = if light.direction_type[3] == 1.0 {
+            LightType::Spot { inner_angle: light.inner_angle, outer_angle: light.outer_angle }
+        } else if light.direction_type[3] == 2.0 {
+            LightType::Parallel
+        } else {
+            LightType::Point
+        };
+
+        let unit = if light.unit == 1.0 {
+            LightUnit::Candela
+        } else if light.unit == 2.0 {
+            LightUnit::Lux
+        } else {
+            LightUnit::Lumen
+        };
+
+        Self {
+            position: light.position(),
+            direction: light.direction(),
+            color: [light.color_intensity[0], light.color_intensity[1], light.color_intensity[2]],
+            intensity: light.color_intensity[3],
+            unit,
+            range: light.position_range[3],
+            light_type,
+        }
+    }
+}
@@ -122,43 +122,49 @@ impl MeshLoad for GMesh {
     }
 
     fn flat(mesh: &Mesh) -> Self {
-        let mut vertices: Vec<Vertex> = Vec::new();
-        let mut faces: Vec<Vec<u32>> = Vec::new();
+        let (vertices, indices) = flat_vertices(mesh, |vertex, _| vertex);
 
-        let mut index: u32 = 0;
-        for face in &mesh.faces {
-            let mut face_indices: Vec<u32> = Vec::new();
-            for attr in face {
-                let v = attr[0] as usize;
-                let n = attr[1] as usize;
-                let uv = attr[2] as usize;
+        Self::new(vertices, Indices::TriangleFace(indices))
+    }
+}
 
-                if v != 0 {
-                    let vertex = mesh.vertices[v - 1].into();
-                    let normal = if n != 0 {
-                        mesh.vertex_normals[n - 1].into()
-                    } else {
-                        [0.0, 0.0, 0.0]
-                    };
-                    let tex_coordinate = if uv != 0 {
-                        mesh.uv[uv - 1].into()
-                    } else {
-                        [0.0, 0.0]
-                    };
-
-                    vertices.push(Vertex::new(vertex, normal, tex_coordinate));
-                    face_indices.push(index);
-
-                    index = index + 1;
-                }
+/// a vertex for each face corner and the triangle list of faces, `vertex` makes the vertex of a corner
+/// from its position, normal and texture coordinate, and the index of the mesh vertex
+pub fn flat_vertices<T, F: Fn(Vertex, usize) -> T>(mesh: &Mesh, vertex: F) -> (Vec<T>, Vec<u32>) {
+    let mut vertices: Vec<T> = Vec::new();
+    let mut faces: Vec<Vec<u32>> = Vec::new();
+
+    let mut index: u32 = 0;
+    for face in &mesh.faces {
+        let mut face_indices: Vec<u32> = Vec::new();
+        for attr in face {
+            let v = attr[0] as usize;
+            let n = attr[1] as usize;
+            let uv = attr[2] as usize;
+
+            if v != 0 {
+                let position = mesh.vertices[v - 1].into();
+                let normal = if n != 0 {
+                    mesh.vertex_normals[n - 1].into()
+                } else {
+                    [0.0, 0.0, 0.0]
+                };
+                let tex_coordinate = if uv != 0 {
+                    mesh.uv[uv - 1].into()
+                } else {
+                    [0.0, 0.0]
+                };
+
+                vertices.push(vertex(Vertex::new(position, normal, tex_coordinate), v - 1));
+                face_indices.push(index);
+
+                index += 1;
             }
-            faces.push(face_indices);
         }
-
-        let indices = get_faces_indices(faces);
-
-        Self::new(vertices, Indices::TriangleFace(indices))
+        faces.push(face_indices);
     }
+
+    (vertices, get_faces_indices(faces))
 }
 
 //polygon faces to triangle list faces
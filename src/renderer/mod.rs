@@ -10,6 +10,11 @@ pub mod data;
 pub mod mesh_load;
 pub mod light;
 pub mod image;
+pub mod render_queue;
+pub mod capture;
+pub mod post_process;
+pub mod shadow;
+pub mod ssao;
 
 pub use renderer::*;
 pub use pipeline::*;
@@ -18,4 +23,9 @@ pub use canvas::*;
 
 pub use data::*;
 pub use light::*;
-pub use image::*;
\ No newline at end of file
+pub use image::*;
+pub use render_queue::*;
+pub use capture::*;
+pub use post_process::*;
+pub use shadow::*;
+pub use ssao::*;
\ No newline at end of file
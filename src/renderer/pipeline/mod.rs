@@ -1,7 +1,9 @@
 mod render_pass;
+mod render_graph;
 mod shader_buffer;
 
 pub mod shader;
 
 pub use render_pass::*;
+pub use render_graph::*;
 pub use shader_buffer::*;
\ No newline at end of file
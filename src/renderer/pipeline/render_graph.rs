@@ -0,0 +1,634 @@
+use super::render_pass::*;
+use std::collections::{BTreeSet, HashMap};
+
+/// size of a transient texture, relative sizes follow the frame when it is resized
+#[derive(Debug,Copy,Clone,PartialEq)]
+pub enum TargetSize {
+    Frame,
+    /// frame size multiplied by the factor, e.g. 0.5 for half resolution
+    Scaled(f32),
+    Fixed(u32,u32),
+}
+
+impl Default for TargetSize {
+    fn default() -> Self {
+        TargetSize::Frame
+    }
+}
+
+impl TargetSize {
+    pub fn dimensions(&self, frame_dimensions: (u32,u32)) -> (u32,u32) {
+        let (width, height) = frame_dimensions;
+        match self {
+            TargetSize::Frame => (width, height),
+            TargetSize::Scaled(factor) => (
+                ((width as f32 * factor).round() as u32).max(1),
+                ((height as f32 * factor).round() as u32).max(1)
+            ),
+            TargetSize::Fixed(width, height) => (*width, *height),
+        }
+    }
+}
+
+/// what a graph pass draws
+#[derive(Debug,Clone,PartialEq)]
+pub enum GraphPassKind {
+    /// opaque and alpha test objects in the render queue order, only objects whose material
+    /// uses the shader if it is given
+    Scene(Option<String>),
+    /// a screen quad, post processing
+    FullScreen,
+}
+
+#[derive(Debug,Clone,PartialEq)]
+pub struct GraphTarget {
+    pub name: String,
+    pub format: OutputFormat,
+    pub size: TargetSize,
+}
+
+/// # Graph Pass
+/// the textures read are bound to the shader by their names,
+/// the textures written are the outputs of shader with same names, a pass without outputs draws to the frame.
+/// a scene pass draws skinned meshes and objects with instances with the skinned and instanced shaders,
+/// they are not drawn by a pass without these shaders
+pub struct GraphPass<T> {
+    pub name: String,
+    pub shader: T,
+    pub skinned_shader: Option<T>,
+    pub instanced_shader: Option<T>,
+    pub pass_option: PassOption,
+    pub kind: GraphPassKind,
+    pub reads: Vec<String>,
+    pub writes: Vec<GraphTarget>,
+}
+
+impl<T> GraphPass<T> {
+    pub fn scene(name: &str, shader: T) -> Self {
+        Self::new(name, shader, GraphPassKind::Scene(None))
+    }
+
+    pub fn full_screen(name: &str, shader: T) -> Self {
+        Self::new(name, shader, GraphPassKind::FullScreen)
+    }
+
+    fn new(name: &str, shader: T, kind: GraphPassKind) -> Self {
+        Self {
+            name: name.to_string(),
+            shader,
+            skinned_shader: None,
+            instanced_shader: None,
+            pass_option: Default::default(),
+            kind,
+            reads: Vec::new(),
+            writes: Vec::new(),
+        }
+    }
+
+    /// draw only objects whose material uses the shader, for scene passes
+    pub fn with_shader_filter(mut self, shader_name: &str) -> Self {
+        if let GraphPassKind::Scene(_) = self.kind {
+            self.kind = GraphPassKind::Scene(Some(shader_name.to_string()));
+        }
+        self
+    }
+
+    /// shader of skinned meshes for scene passes
+    pub fn with_skinned_shader(mut self, shader: T) -> Self {
+        self.skinned_shader = Some(shader);
+        self
+    }
+
+    /// shader of objects with instances and batches for scene passes
+    pub fn with_instanced_shader(mut self, shader: T) -> Self {
+        self.instanced_shader = Some(shader);
+        self
+    }
+
+    pub fn read(mut self, name: &str) -> Self {
+        self.reads.push(name.to_string());
+        self
+    }
+
+    pub fn write(self, name: &str, format: OutputFormat) -> Self {
+        self.write_with_size(name, format, TargetSize::Frame)
+    }
+
+    pub fn write_with_size(mut self, name: &str, format: OutputFormat, size: TargetSize) -> Self {
+        self.writes.push(GraphTarget {
+            name: name.to_string(),
+            format,
+            size,
+        });
+        self
+    }
+
+    pub fn with_pass_option(mut self, pass_option: PassOption) -> Self {
+        self.pass_option = pass_option;
+        self
+    }
+
+    pub fn with_depth(mut self, z_test: ZTest, z_write: bool) -> Self {
+        self.pass_option.z_test = z_test;
+        self.pass_option.z_write = z_write;
+        self
+    }
+
+    pub fn is_frame_pass(&self) -> bool {
+        self.writes.is_empty()
+    }
+}
+
+#[derive(Debug,Clone,PartialEq)]
+pub enum RenderGraphErr {
+    /// two passes with same name
+    PassNameErr(String),
+    /// texture written by more than one pass
+    WriterErr(String),
+    /// texture read but neither written by a pass nor imported
+    ResourceErr { pass: String, resource: String },
+    /// passes in a dependency cycle
+    CycleErr(Vec<String>),
+}
+
+impl std::fmt::Display for RenderGraphErr {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            RenderGraphErr::PassNameErr(name) => write!(f, "pass {} is added twice", name),
+            RenderGraphErr::WriterErr(name) => write!(f, "texture {} is written by more than one pass", name),
+            RenderGraphErr::ResourceErr { pass, resource } => write!(f, "pass {} reads texture {} which is not written or imported", pass, resource),
+            RenderGraphErr::CycleErr(passes) => write!(f, "passes {} depend on each other", passes.join(", ")),
+        }
+    }
+}
+
+impl std::error::Error for RenderGraphErr {}
+
+/// # Compiled Graph
+/// execution order, lifetimes of transient textures and the physical texture (slot) of each texture,
+/// textures with same format and size whose lifetimes don't overlap share a slot.
+/// a texture no pass reads has a slot of its own, so it can be read by `read_target` after the frame
+#[derive(Debug,Clone,PartialEq)]
+pub struct CompiledGraph {
+    /// pass indices in execution order
+    pub order: Vec<usize>,
+    /// first and last position in `order` using a transient texture
+    pub lifetimes: HashMap<String,(usize,usize)>,
+    pub slots: Vec<(OutputFormat,TargetSize)>,
+    pub slot_of: HashMap<String,usize>,
+}
+
+impl CompiledGraph {
+    pub fn slot(&self, resource: &str) -> Option<usize> {
+        self.slot_of.get(resource).cloned()
+    }
+
+    pub fn lifetime(&self, resource: &str) -> Option<(usize,usize)> {
+        self.lifetimes.get(resource).cloned()
+    }
+
+    /// dimensions of slots for the frame size
+    pub fn slot_dimensions(&self, frame_dimensions: (u32,u32)) -> Vec<(u32,u32)> {
+        self.slots.iter().map(|(_, size)| size.dimensions(frame_dimensions)).collect()
+    }
+}
+
+/// # Render Graph
+/// passes declare the textures they read and write, the graph is ordered by these dependencies.
+/// passes drawing to the frame keep the order they are added.
+/// imported textures, e.g. textures from `update_texture`, can be read without a writer
+pub struct RenderGraph<T> {
+    passes: Vec<GraphPass<T>>,
+    imports: BTreeSet<String>,
+    compiled: Option<CompiledGraph>,
+}
+
+impl<T> RenderGraph<T> {
+    pub fn new() -> Self {
+        Self {
+            passes: Vec::new(),
+            imports: BTreeSet::new(),
+            compiled: None,
+        }
+    }
+
+    pub fn add_pass(&mut self, pass: GraphPass<T>) {
+        self.passes.push(pass);
+        self.compiled = None;
+    }
+
+    pub fn with_pass(mut self, pass: GraphPass<T>) -> Self {
+        self.add_pass(pass);
+        self
+    }
+
+    pub fn import(&mut self, name: &str) {
+        self.imports.insert(name.to_string());
+        self.compiled = None;
+    }
+
+    pub fn passes(&self) -> &[GraphPass<T>] {
+        self.passes.as_slice()
+    }
+
+    pub fn pass(&self, name: &str) -> Option<&GraphPass<T>> {
+        self.passes.iter().find(|x| x.name == name)
+    }
+
+    /// result of the last `compile`, it is cleared when the graph changes
+    pub fn compiled(&self) -> Option<&CompiledGraph> {
+        self.compiled.as_ref()
+    }
+
+    /// passes in execution order, empty if the graph is not compiled
+    pub fn ordered_passes(&self) -> Vec<&GraphPass<T>> {
+        match &self.compiled {
+            Some(compiled) => compiled.order.iter().map(|x| &self.passes[*x]).collect(),
+            None => Vec::new(),
+        }
+    }
+
+    pub fn compile(&mut self) -> Result<&CompiledGraph, RenderGraphErr> {
+        let compiled = self.build()?;
+        self.compiled = Some(compiled);
+        Ok(self.compiled.as_ref().unwrap())
+    }
+
+    fn build(&self) -> Result<CompiledGraph, RenderGraphErr> {
+        let mut pass_names = BTreeSet::new();
+        let mut writer: HashMap<&String,usize> = HashMap::new();
+        let mut targets: HashMap<&String,&GraphTarget> = HashMap::new();
+
+        for (i, pass) in self.passes.iter().enumerate() {
+            if !pass_names.insert(&pass.name) {
+                return Err(RenderGraphErr::PassNameErr(pass.name.clone()));
+            }
+
+            for target in pass.writes.iter() {
+                if writer.insert(&target.name, i).is_some() || self.imports.contains(&target.name) {
+                    return Err(RenderGraphErr::WriterErr(target.name.clone()));
+                }
+                targets.insert(&target.name, target);
+            }
+        }
+
+        /* edges from the writer to the readers, and between frame passes */
+        let mut dependents: Vec<BTreeSet<usize>> = vec![BTreeSet::new(); self.passes.len()];
+        let mut in_degree = vec![0usize; self.passes.len()];
+
+        let mut add_edge = |from: usize, to: usize, dependents: &mut Vec<BTreeSet<usize>>| {
+            if from != to && dependents[from].insert(to) {
+                in_degree[to] += 1;
+            }
+        };
+
+        let mut last_frame_pass = None;
+
+        for (i, pass) in self.passes.iter().enumerate() {
+            for resource in pass.reads.iter() {
+                match writer.get(resource) {
+                    Some(from) => add_edge(*from, i, &mut dependents),
+                    None if self.imports.contains(resource) => (),
+                    None => return Err(RenderGraphErr::ResourceErr {
+                        pass: pass.name.clone(),
+                        resource: resource.clone(),
+                    }),
+                }
+            }
+
+            if pass.is_frame_pass() {
+                if let Some(from) = last_frame_pass {
+                    add_edge(from, i, &mut dependents);
+                }
+                last_frame_pass = Some(i);
+            }
+        }
+
+        /* the ready pass added first runs first, so the order is stable */
+        let mut ready: BTreeSet<usize> = (0..self.passes.len()).filter(|x| in_degree[*x] == 0).collect();
+        let mut order = Vec::with_capacity(self.passes.len());
+
+        while let Some(i) = ready.iter().next().cloned() {
+            ready.remove(&i);
+            order.push(i);
+
+            for to in dependents[i].iter() {
+                in_degree[*to] -= 1;
+                if in_degree[*to] == 0 {
+                    ready.insert(*to);
+                }
+            }
+        }
+
+        if order.len() < self.passes.len() {
+            let cycle = (0..self.passes.len())
+                .filter(|x| !order.contains(x))
+                .map(|x| self.passes[x].name.clone())
+                .collect();
+            return Err(RenderGraphErr::CycleErr(cycle));
+        }
+
+        /* lifetime from the writer to the last reader */
+        let mut lifetimes: HashMap<String,(usize,usize)> = HashMap::new();
+
+        for (position, i) in order.iter().enumerate() {
+            let pass = &self.passes[*i];
+
+            for target in pass.writes.iter() {
+                lifetimes.insert(target.name.clone(), (position, position));
+            }
+
+            for resource in pass.reads.iter() {
+                if let Some(lifetime) = lifetimes.get_mut(resource) {
+                    lifetime.1 = position;
+                }
+            }
+        }
+
+        let read: BTreeSet<&String> = self.passes.iter().flat_map(|x| x.reads.iter()).collect();
+
+        /* alias textures by first use, a slot is free after the last use of its texture */
+        let mut resources: Vec<(&String,(usize,usize))> = lifetimes.iter().map(|(name, lifetime)| (name, *lifetime)).collect();
+        resources.sort_by(|a, b| a.1.cmp(&b.1).then_with(|| a.0.cmp(b.0)));
+
+        let mut slots: Vec<(OutputFormat,TargetSize)> = Vec::new();
+        let mut slot_end: Vec<usize> = Vec::new();
+        let mut slot_of = HashMap::new();
+
+        for (name, (first, last)) in resources {
+            let target = targets[name];
+
+            let output = !read.contains(name);
+
+            let free = (0..slots.len()).find(|x| {
+                !output && slots[*x] == (target.format, target.size) && slot_end[*x] < first
+            });
+
+            let slot = match free {
+                Some(slot) => slot,
+                None => {
+                    slots.push((target.format, target.size));
+                    slot_end.push(0);
+                    slots.len() - 1
+                },
+            };
+
+            /* the slot of a output is never free */
+            slot_end[slot] = if output { usize::MAX } else { last };
+            slot_of.insert(name.clone(), slot);
+        }
+
+        Ok(CompiledGraph {
+            order,
+            lifetimes,
+            slots,
+            slot_of,
+        })
+    }
+
+    /// graphviz description of passes and textures, e.g. `dot -Tsvg graph.dot`
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph render_graph {\n    rankdir=LR;\n");
+
+        let order: HashMap<usize,usize> = match &self.compiled {
+            Some(compiled) => compiled.order.iter().enumerate().map(|(position, i)| (*i, position)).collect(),
+            None => HashMap::new(),
+        };
+
+        for (i, pass) in self.passes.iter().enumerate() {
+            let label = match order.get(&i) {
+                Some(position) => format!("{}: {}", position, pass.name),
+                None => pass.name.clone(),
+            };
+            dot.push_str(&format!("    \"pass:{}\" [shape=box, label=\"{}\"];\n", escape(&pass.name), escape(&label)));
+        }
+
+        let mut resources: BTreeSet<String> = self.imports.clone();
+        for pass in self.passes.iter() {
+            resources.extend(pass.reads.iter().cloned());
+        }
+
+        for pass in self.passes.iter() {
+            for target in pass.writes.iter() {
+                resources.remove(&target.name);
+
+                let slot = match self.compiled.as_ref().and_then(|x| x.slot(&target.name)) {
+                    Some(slot) => format!("\\nslot {}", slot),
+                    None => String::new(),
+                };
+                dot.push_str(&format!(
+                    "    \"texture:{}\" [shape=ellipse, label=\"{}\\n{:?} {:?}{}\"];\n",
+                    escape(&target.name), escape(&target.name), target.format, target.size, slot
+                ));
+            }
+        }
+
+        for name in resources.iter() {
+            dot.push_str(&format!("    \"texture:{}\" [shape=ellipse, style=dashed, label=\"{}\"];\n", escape(name), escape(name)));
+        }
+
+        if self.passes.iter().any(|x| x.is_frame_pass()) {
+            dot.push_str("    \"frame\" [shape=doublecircle];\n");
+        }
+
+        for pass in self.passes.iter() {
+            for resource in pass.reads.iter() {
+                dot.push_str(&format!("    \"texture:{}\" -> \"pass:{}\";\n", escape(resource), escape(&pass.name)));
+            }
+
+            for target in pass.writes.iter() {
+                dot.push_str(&format!("    \"pass:{}\" -> \"texture:{}\";\n", escape(&pass.name), escape(&target.name)));
+            }
+
+            if pass.is_frame_pass() {
+                dot.push_str(&format!("    \"pass:{}\" -> \"frame\";\n", escape(&pass.name)));
+            }
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+impl<T> RenderGraph<T> {
+    /// graph of a render pass chain of a shader, the textures passed by name between passes become dependencies.
+    /// scene passes only draw objects whose material uses the shader
+    pub fn from_render_pass(render_pass: RenderPass<T>, shader_name: &str) -> Self {
+        let mut graph = Self::new();
+        let mut written: Vec<String> = Vec::new();
+        let mut next = Some(Box::new(render_pass));
+        let mut index = 0;
+
+        while let Some(render_pass) = next {
+            let RenderPass { shader, pass_option, render_pass_type, next: rest } = *render_pass;
+            let name = format!("{}#{}", shader_name, index);
+
+            let mut pass = match render_pass_type {
+                RenderPassType::Pass => GraphPass::scene(&name, shader),
+                RenderPassType::TargetPass((target, format)) => GraphPass::scene(&name, shader).write(&target, format),
+                RenderPassType::MultipleRenderPass(output) => output
+                    .iter()
+                    .fold(GraphPass::scene(&name, shader), |pass, (target, format)| pass.write(target, *format)),
+                RenderPassType::PostPressingPass => GraphPass::full_screen(&name, shader),
+                RenderPassType::TargetPostPressingPass((target, format)) => GraphPass::full_screen(&name, shader).write(&target, format),
+            }.with_shader_filter(shader_name).with_pass_option(pass_option);
+
+            /* a pass could read every texture written before it */
+            pass.reads = written.clone();
+            written.extend(pass.writes.iter().map(|x| x.name.clone()));
+
+            graph.add_pass(pass);
+            next = rest;
+            index += 1;
+        }
+
+        graph
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(graph: &RenderGraph<u32>) -> Vec<&str> {
+        graph.ordered_passes().iter().map(|x| x.name.as_str()).collect()
+    }
+
+    /* added out of order, the reads decide the order */
+    fn deferred_graph() -> RenderGraph<u32> {
+        RenderGraph::new()
+            .with_pass(GraphPass::full_screen("tone_mapping", 3).read("lit"))
+            .with_pass(GraphPass::full_screen("lighting", 2).read("albedo").read("normal").read("environment").write("lit", OutputFormat::Vec4F16))
+            .with_pass(GraphPass::scene("gbuffer", 1).write("albedo", OutputFormat::Vec4U8).write("normal", OutputFormat::Vec4F16))
+            .with_pass(GraphPass::full_screen("bloom", 4).read("lit").write("bloom", OutputFormat::Vec4F16))
+            .with_pass(GraphPass::full_screen("composite", 5).read("bloom"))
+    }
+
+    #[test]
+    fn order() {
+        let mut graph = deferred_graph();
+        graph.import("environment");
+        graph.compile().unwrap();
+
+        assert_eq!(names(&graph), vec!["gbuffer", "lighting", "tone_mapping", "bloom", "composite"]);
+    }
+
+    #[test]
+    fn frame_passes_keep_order() {
+        let mut graph = RenderGraph::new()
+            .with_pass(GraphPass::full_screen("b", 0u32))
+            .with_pass(GraphPass::full_screen("a", 0))
+            .with_pass(GraphPass::full_screen("c", 0));
+        graph.compile().unwrap();
+
+        assert_eq!(names(&graph), vec!["b", "a", "c"]);
+    }
+
+    #[test]
+    fn errors() {
+        let mut graph = deferred_graph();
+        assert_eq!(graph.compile().err(), Some(RenderGraphErr::ResourceErr {
+            pass: "lighting".to_string(),
+            resource: "environment".to_string(),
+        }));
+
+        let mut graph = RenderGraph::new()
+            .with_pass(GraphPass::full_screen("a", 0u32).read("y").write("x", OutputFormat::U8))
+            .with_pass(GraphPass::full_screen("b", 0).read("x").write("y", OutputFormat::U8))
+            .with_pass(GraphPass::full_screen("c", 0).read("y"));
+        assert_eq!(graph.compile().err(), Some(RenderGraphErr::CycleErr(vec!["a".to_string(), "b".to_string(), "c".to_string()])));
+        assert!(graph.compiled().is_none());
+
+        let mut graph = RenderGraph::new()
+            .with_pass(GraphPass::full_screen("a", 0u32).write("x", OutputFormat::U8))
+            .with_pass(GraphPass::full_screen("b", 0).write("x", OutputFormat::U8));
+        assert_eq!(graph.compile().err(), Some(RenderGraphErr::WriterErr("x".to_string())));
+
+        let mut graph = RenderGraph::new()
+            .with_pass(GraphPass::full_screen("a", 0u32))
+            .with_pass(GraphPass::full_screen("a", 0));
+        assert_eq!(graph.compile().err(), Some(RenderGraphErr::PassNameErr("a".to_string())));
+    }
+
+    #[test]
+    fn aliasing() {
+        let mut graph = deferred_graph();
+        graph.import("environment");
+        let compiled = graph.compile().unwrap().clone();
+
+        assert_eq!(compiled.lifetime("normal"), Some((0, 1)));
+        assert_eq!(compiled.lifetime("bloom"), Some((3, 4)));
+
+        /* normal is free after lighting, bloom has same format and size */
+        assert_eq!(compiled.slot("normal"), compiled.slot("bloom"));
+        assert_ne!(compiled.slot("lit"), compiled.slot("bloom"));
+        assert_ne!(compiled.slot("albedo"), compiled.slot("normal"));
+        assert_eq!(compiled.slots.len(), 3);
+    }
+
+    #[test]
+    fn unread_targets_are_not_aliased() {
+        let mut graph = RenderGraph::new()
+            .with_pass(GraphPass::full_screen("a", 0u32).write("debug", OutputFormat::Vec4U8))
+            .with_pass(GraphPass::full_screen("b", 0).write("x", OutputFormat::Vec4U8))
+            .with_pass(GraphPass::full_screen("c", 0).read("x").write("y", OutputFormat::Vec4U8))
+            .with_pass(GraphPass::full_screen("d", 0).read("y"));
+        let compiled = graph.compile().unwrap().clone();
+
+        let debug = compiled.slot("debug").unwrap();
+        assert!(["x", "y"].iter().all(|x| compiled.slot(x) != Some(debug)));
+        assert_eq!(compiled.slots.len(), 3);
+    }
+
+    #[test]
+    fn size_aliasing() {
+        let mut graph = RenderGraph::new()
+            .with_pass(GraphPass::full_screen("a", 0u32).write_with_size("half", OutputFormat::Vec4U8, TargetSize::Scaled(0.5)))
+            .with_pass(GraphPass::full_screen("b", 0).read("half").write("full", OutputFormat::Vec4U8))
+            .with_pass(GraphPass::full_screen("c", 0).read("full").write_with_size("half2", OutputFormat::Vec4U8, TargetSize::Scaled(0.5)))
+            .with_pass(GraphPass::full_screen("d", 0).read("half2"));
+        let compiled = graph.compile().unwrap().clone();
+
+        assert_eq!(compiled.slot("half"), compiled.slot("half2"));
+        assert_ne!(compiled.slot("half"), compiled.slot("full"));
+        assert_eq!(compiled.slot_dimensions((801, 600))[compiled.slot("half").unwrap()], (401, 300));
+    }
+
+    #[test]
+    fn dot() {
+        let mut graph = deferred_graph();
+        graph.import("environment");
+        graph.compile().unwrap();
+        let dot = graph.to_dot();
+
+        assert!(dot.starts_with("digraph render_graph {"));
+        assert!(dot.contains("\"pass:gbuffer\" [shape=box, label=\"0: gbuffer\"];"));
+        assert!(dot.contains("\"texture:environment\" [shape=ellipse, style=dashed, label=\"environment\"];"));
+        assert!(dot.contains("\"pass:gbuffer\" -> \"texture:albedo\";"));
+        assert!(dot.contains("\"texture:lit\" -> \"pass:tone_mapping\";"));
+        assert!(dot.contains("\"pass:composite\" -> \"frame\";"));
+        assert!(dot.contains("slot"));
+        assert!(dot.trim_end().ends_with('}'));
+    }
+
+    #[test]
+    fn from_render_pass() {
+        let render_pass = RenderPass::multiple_render_pass(1u32, vec![("a".to_string(), OutputFormat::Vec4U8)], Box::new(RenderPass {
+            shader: 2,
+            pass_option: Default::default(),
+            render_pass_type: RenderPassType::PostPressingPass,
+            next: None,
+        }));
+        let mut graph = RenderGraph::from_render_pass(render_pass, "Deferred");
+        graph.compile().unwrap();
+
+        assert_eq!(names(&graph), vec!["Deferred#0", "Deferred#1"]);
+        assert_eq!(graph.passes()[0].kind, GraphPassKind::Scene(Some("Deferred".to_string())));
+        assert_eq!(graph.passes()[1].reads, vec!["a".to_string()]);
+    }
+}
@@ -39,7 +39,7 @@ impl<T> RenderPass<T> {
     }
 }
 
-#[derive(Copy,Clone)]
+#[derive(Debug,Copy,Clone,PartialEq,Eq,Hash)]
 pub enum OutputFormat {
     U8,
     Vec2U8,
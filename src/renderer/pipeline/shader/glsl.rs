@@ -19,6 +19,32 @@ pub fn light_lib(light_count: u16) -> String {
     ) 
 }
 
+pub fn skinned_vert_lib(joint_count: u16) -> String {
+    String::from(
+        format!("#define SKINNING\n#define JOINTS_MAX_NUMBER {}\n{}",
+                joint_count,
+                include_str!("glsl/vert_lib.glsl")
+        )
+    )
+}
+
+pub fn instanced_vert_lib() -> String {
+    String::from(
+        format!("#define INSTANCING\n{}",
+                include_str!("glsl/vert_lib.glsl")
+        )
+    )
+}
+
+/// common output and texture sampling of material fragment shaders, `oit` outputs weighted blended transparency targets
+pub fn frag_lib(oit: bool) -> String {
+    if oit {
+        String::from(format!("#define OIT\n{}", include_str!("glsl/frag_lib.glsl")))
+    } else {
+        String::from(include_str!("glsl/frag_lib.glsl"))
+    }
+}
+
 pub fn base_vert() -> String {
     String::from(include_str!("glsl/base_vert.glsl"))
 }
@@ -55,10 +81,60 @@ pub fn scene() -> String {
     String::from(include_str!("glsl/scene.glsl"))
 }
 
+pub fn oit_composite() -> String {
+    String::from(include_str!("glsl/oit_composite.glsl"))
+}
+
 pub fn shadow_map_vert() -> String {
     String::from(include_str!("glsl/shadow_map_vert.glsl"))
 }
 
 pub fn shadow_map_frag() -> String {
     String::from(include_str!("glsl/shadow_map_frag.glsl"))
-}
\ No newline at end of file
+}
+
+pub fn environment_vert() -> String {
+    String::from(include_str!("glsl/environment_vert.glsl"))
+}
+
+/// passes of image based lighting, `pass` is `EQUIRECTANGULAR`, `IRRADIANCE`, `PREFILTER`, `BRDF_LUT` or `SKYBOX`
+pub fn environment(pass: &str) -> String {
+    String::from(format!("#define {}\n{}", pass, include_str!("glsl/environment.glsl")))
+}
+
+pub fn point_shadow_vert() -> String {
+    String::from(include_str!("glsl/point_shadow_vert.glsl"))
+}
+
+pub fn point_shadow_frag() -> String {
+    String::from(include_str!("glsl/point_shadow_frag.glsl"))
+}
+
+/// `SSAO` for the occlusion or `SSAO_BLUR` for the blur by depth
+pub fn ssao(pass: &str, max_samples: usize) -> String {
+    String::from(format!("#define {}\n#define SSAO_MAX_SAMPLES {}\n{}", pass, max_samples, include_str!("glsl/ssao.glsl")))
+}
+
+/// light library reading `Lights` as a storage block, the number of lights is not limited
+pub fn light_storage_lib() -> String {
+    String::from(format!("#define LIGHTS_STORAGE\n{}", include_str!("glsl/light_lib.glsl")))
+}
+
+/// fragment shader writing the g-buffer, `shading_model` is `COOK_TORRANCE_BRDF`, `BLINN_PHONG_BRDF` or `PURE_COLOR`
+pub fn gbuffer(shading_model: &str) -> String {
+    String::from(format!("#define {}\n{}", shading_model, include_str!("glsl/gbuffer.glsl")))
+}
+
+pub fn deferred_vert() -> String {
+    String::from(include_str!("glsl/deferred_vert.glsl"))
+}
+
+pub fn deferred_lighting() -> String {
+    String::from(include_str!("glsl/deferred_lighting.glsl"))
+}
+
+/// full screen effect, `effect` is `BLOOM_PREFILTER`, `BLOOM_DOWNSAMPLE`, `BLOOM_UPSAMPLE`, `BLOOM_COMPOSITE`,
+/// `TONE_MAPPING`, `FXAA`, `VIGNETTE`, `COLOR_GRADING` or `COPY`
+pub fn post_process(effect: &str) -> String {
+    String::from(format!("#define {}\n{}", effect, include_str!("glsl/post_process.glsl")))
+}
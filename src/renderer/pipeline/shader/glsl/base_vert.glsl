@@ -2,13 +2,39 @@ in layout(location = 0) vec3 position;
 in layout(location = 1) vec3 normal;
 in layout(location = 2) vec2 tex_coordinate;
 
+#ifdef SKINNING
+in layout(location = 3) uvec4 joints;
+in layout(location = 4) vec4 weights;
+#endif
+
+#ifdef INSTANCING
+in layout(location = 5) mat4 instance_transform;
+in layout(location = 9) vec4 instance_tint;
+#endif
+
 out vec3 frag_pos;
 out vec3 v_normal;
+out vec4 v_tint;
 
 void main() {
-    vec4 pos = transform * vec4(position,1.0);
+#ifdef INSTANCING
+    mat4 model = transform * instance_transform;
+    v_tint = instance_tint;
+#else
+    mat4 model = transform;
+    v_tint = vec4(1.0);
+#endif
+
+#ifdef SKINNING
+    mat4 skin = skin_matrix(joints, weights);
+
+    vec4 pos = model * skin * vec4(position,1.0);
+    v_normal = normalize(mat3(skin) * normal);
+#else
+    vec4 pos = model * vec4(position,1.0);
+    v_normal = normal;
+#endif
     gl_Position = project * view * pos;
     
-    v_normal = normal;
     frag_pos = vec3(pos);
 }
\ No newline at end of file
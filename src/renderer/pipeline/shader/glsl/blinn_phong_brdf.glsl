@@ -9,14 +9,14 @@ uniform BlinnPhongBRDF material;
 
 in vec3 v_normal;
 in vec3 frag_pos;
-
-out vec4 color_out;
+in vec4 v_tint;
 
 void main() {
 
     vec3 f_normal = normalize(v_normal);
     vec3 view_direction = normalize(view_position - frag_pos);
     vec3 f_color = vec3(0.0);
+    float occlusion = ambient_occlusion();
 
     for(int i = 0; i < lights_count; i++) {
         vec3 light_direction = normalize(light_direction(light[i], frag_pos));
@@ -25,12 +25,12 @@ void main() {
 
         float attenuation =  attenuation(light[i], frag_pos);
 
-        vec3 ambient = color * material.ambient;
+        vec3 ambient = color * material.ambient * occlusion;
         vec3 specular = color * pow(max(dot(f_normal, halfway_direction), 0.0), material.shininess);
         vec3 diffuse = color * max(dot(f_normal, light_direction),0.0);
-        float visibility = visibility(shadow[i].mvp * vec4(frag_pos,1.0),shadow[i].tex,0.0);
+        float visibility = shadow_visibility(i, frag_pos, f_normal);
         f_color = f_color + (ambient + visibility * (diffuse + specular)) * attenuation;
     }
 
-    color_out = hdr(vec4(f_color,1.0));
+    write_color(hdr(vec4(f_color * v_tint.rgb, v_tint.a)));
 }
\ No newline at end of file
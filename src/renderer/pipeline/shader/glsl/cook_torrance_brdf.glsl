@@ -11,8 +11,7 @@ uniform CookTorranceBRDF material;
 
 in vec3 v_normal;
 in vec3 frag_pos;
-
-out vec4 color_out;
+in vec4 v_tint;
 
 float GGX_distribution(vec3 normal, vec3 halfway_direction, float roughness) {
     float a = roughness * roughness;
@@ -37,9 +36,9 @@ float GGX_Schlick(float NV, float roughness) {
     return nom / denom;
 }
 
-float geometry_smith(vec3 normal, vec3 view_position, vec3 light_direction, float roughness)
+float geometry_smith(vec3 normal, vec3 view_direction, vec3 light_direction, float roughness)
 {
-    float NV = max(dot(normal,view_position),0.0);
+    float NV = max(dot(normal,view_direction),0.0);
     float NL = max(dot(normal,light_direction), 0.0);
     float ggx2 = GGX_Schlick(NV, roughness);
     float ggx1 = GGX_Schlick(NL, roughness);
@@ -54,11 +53,11 @@ vec3 fresnel_schlick(float cos_theta, vec3 f) {
 void main() {
     vec3 f_normal = normalize(v_normal);
     vec3 view_direction = normalize(view_position - frag_pos);
-    vec3 f_color = vec3(0.0);
+    vec3 f_color = environment_ambient(f_normal, view_direction, material.albedo, material.metallic, material.roughness, material.ao * ambient_occlusion());
 
     for(int i = 0; i < lights_count; i++) {
         vec3 light_direction = light_direction(light[i], frag_pos);
-        vec3 halfway_direction = normalize( view_position + light_direction);
+        vec3 halfway_direction = normalize(view_direction + light_direction);
 
         vec3 f = mix(vec3(0.04),material.albedo, material.metallic);
 
@@ -66,24 +65,23 @@ void main() {
         vec3 radiance = light_color(light[i], light_direction) * attenuation;
 
         float NDF = GGX_distribution(f_normal, halfway_direction, material.roughness);
-        float G = geometry_smith(f_normal, view_position, light_direction, material.roughness);
+        float G = geometry_smith(f_normal, view_direction, light_direction, material.roughness);
         vec3 F = fresnel_schlick(max(dot(halfway_direction, view_direction), 0.0), f);
 
         vec3 kd = vec3(1.0) - F;
         kd *= 1.0 - material.metallic;
 
         vec3 nominator = NDF * G * F;
-        float denominator = 4 * max(dot(f_normal,view_position), 0.0) * max(dot(f_normal, light_direction), 0.0) + 0.001;
+        float denominator = 4 * max(dot(f_normal,view_direction), 0.0) * max(dot(f_normal, light_direction), 0.0) + 0.001;
         vec3 specular = nominator / denominator;
     
         float NL = max(dot(f_normal, light_direction), 0.0);
 
         vec3 lo = (kd * material.albedo / PI + specular) * radiance * NL ;
 
-        float visibility = visibility(shadow[i].mvp * vec4(frag_pos,1.0),shadow[i].tex,0.0);
+        float visibility = shadow_visibility(i, frag_pos, f_normal);
 
-        vec3 ambient = vec3(0.03) * material.albedo * material.ao;
-        f_color = f_color + ambient + lo *visibility;
+        f_color = f_color + lo * visibility;
     }
-    color_out = hdr(vec4(f_color,1.0));
+    write_color(hdr(vec4(f_color * v_tint.rgb, v_tint.a)));
 }
\ No newline at end of file
@@ -0,0 +1,116 @@
+#define PI 3.14159265358979
+
+#define UNLIT 1
+#define BLINN_PHONG 2
+#define COOK_TORRANCE 3
+
+uniform sampler2D g_albedo;
+uniform sampler2D g_normal;
+uniform sampler2D g_material;
+uniform sampler2D g_depth;
+
+in vec2 v_tex_coordinate;
+flat in mat4 inverse_view_project;
+
+out vec4 color_out;
+
+float GGX_distribution(vec3 normal, vec3 halfway_direction, float roughness) {
+    float a = roughness * roughness;
+    float a2 = a * a;
+    float NH = max(dot(normal,halfway_direction), 0.0);
+    float denominator = (NH * NH * (a2 - 1.0) + 1.0);
+
+    return a2 / (PI * denominator * denominator);
+}
+
+float GGX_Schlick(float NV, float roughness) {
+    float r = (roughness + 1.0);
+    float k = (r * r) / 8.0;
+
+    return NV / (NV * (1.0 - k) + k);
+}
+
+vec3 fresnel_schlick(float cos_theta, vec3 f) {
+    return f + (1 - f) * pow(1.0 - cos_theta, 5.0);
+}
+
+vec3 cook_torrance(vec3 albedo, vec3 f_normal, vec3 frag_pos, vec4 property) {
+    float roughness = property.r;
+    float metallic = property.g;
+    float ao = property.b;
+
+    vec3 view_direction = normalize(view_position - frag_pos);
+    vec3 f = mix(vec3(0.04), albedo, metallic);
+    vec3 f_color = environment_ambient(f_normal, view_direction, albedo, metallic, roughness, ao * ambient_occlusion());
+
+    for(int i = 0; i < lights_count; i++) {
+        vec3 light_direction = light_direction(light[i], frag_pos);
+        vec3 halfway_direction = normalize(view_direction + light_direction);
+        vec3 radiance = light_color(light[i], light_direction) * attenuation(light[i], frag_pos);
+
+        float NV = max(dot(f_normal, view_direction), 0.0);
+        float NL = max(dot(f_normal, light_direction), 0.0);
+
+        float NDF = GGX_distribution(f_normal, halfway_direction, roughness);
+        float G = GGX_Schlick(NV, roughness) * GGX_Schlick(NL, roughness);
+        vec3 F = fresnel_schlick(max(dot(halfway_direction, view_direction), 0.0), f);
+
+        vec3 kd = (vec3(1.0) - F) * (1.0 - metallic);
+        vec3 specular = NDF * G * F / (4 * NV * NL + 0.001);
+
+        f_color = f_color + (kd * albedo / PI + specular) * radiance * NL * shadow_visibility(i, frag_pos, f_normal);
+    }
+
+    return f_color;
+}
+
+vec3 blinn_phong(vec3 diffuse, vec3 f_normal, float shininess, vec3 frag_pos, vec4 property) {
+    vec3 specular = property.rgb;
+    float ambient = property.a * ambient_occlusion();
+
+    vec3 view_direction = normalize(view_position - frag_pos);
+    vec3 f_color = vec3(0.0);
+
+    for(int i = 0; i < lights_count; i++) {
+        vec3 light_direction = normalize(light_direction(light[i], frag_pos));
+        vec3 color = light_color(light[i], light_direction);
+        vec3 halfway_direction = normalize(light_direction + view_direction);
+
+        vec3 l_ambient = color * ambient;
+        vec3 l_specular = color * specular * pow(max(dot(f_normal, halfway_direction), 0.0), shininess);
+        vec3 l_diffuse = color * diffuse * max(dot(f_normal, light_direction), 0.0);
+        float visibility = shadow_visibility(i, frag_pos, f_normal);
+        f_color = f_color + (l_ambient + visibility * (l_diffuse + l_specular)) * attenuation(light[i], frag_pos);
+    }
+
+    return f_color;
+}
+
+void main() {
+    float depth = texture(g_depth, v_tex_coordinate).r;
+
+    // background keeps the clear color
+    if (depth >= 1.0) {
+        discard;
+    }
+
+    vec4 albedo = texture(g_albedo, v_tex_coordinate);
+    vec4 normal = texture(g_normal, v_tex_coordinate);
+    vec4 property = texture(g_material, v_tex_coordinate);
+    int shading_model = int(round(albedo.a * 255.0));
+
+    vec4 position = inverse_view_project * vec4(vec3(v_tex_coordinate, depth) * 2.0 - 1.0, 1.0);
+    vec3 frag_pos = position.xyz / position.w;
+    vec3 f_normal = normalize(normal.xyz);
+
+    // depth of the scene for forward and transparent objects drawn after lighting
+    gl_FragDepth = depth;
+
+    if (shading_model == COOK_TORRANCE) {
+        color_out = hdr(vec4(cook_torrance(albedo.rgb, f_normal, frag_pos, property), 1.0));
+    } else if (shading_model == BLINN_PHONG) {
+        color_out = hdr(vec4(blinn_phong(albedo.rgb, f_normal, normal.w, frag_pos, property), 1.0));
+    } else {
+        color_out = vec4(albedo.rgb, 1.0);
+    }
+}
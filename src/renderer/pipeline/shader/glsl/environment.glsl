@@ -0,0 +1,168 @@
+#define PI 3.14159265358979
+
+in vec2 v_position;
+
+out vec4 color_out;
+
+#if defined(SKYBOX)
+uniform mat4 inverse_view_project;
+#else
+// basis of the cube face drawn by the full screen quad
+uniform vec3 face_forward;
+uniform vec3 face_right;
+uniform vec3 face_up;
+#endif
+
+vec3 direction() {
+#if defined(SKYBOX)
+    vec4 position = inverse_view_project * vec4(v_position, 1.0, 1.0);
+    return normalize(position.xyz / position.w);
+#else
+    return normalize(face_forward + v_position.x * face_right + v_position.y * face_up);
+#endif
+}
+
+float radical_inverse(uint bits) {
+    bits = (bits << 16u) | (bits >> 16u);
+    bits = ((bits & 0x55555555u) << 1u) | ((bits & 0xAAAAAAAAu) >> 1u);
+    bits = ((bits & 0x33333333u) << 2u) | ((bits & 0xCCCCCCCCu) >> 2u);
+    bits = ((bits & 0x0F0F0F0Fu) << 4u) | ((bits & 0xF0F0F0F0u) >> 4u);
+    bits = ((bits & 0x00FF00FFu) << 8u) | ((bits & 0xFF00FF00u) >> 8u);
+    return float(bits) * 2.3283064365386963e-10;
+}
+
+vec2 hammersley(uint i, uint count) {
+    return vec2(float(i) / float(count), radical_inverse(i));
+}
+
+// halfway vector of ggx distribution around the normal
+vec3 importance_sample_ggx(vec2 xi, vec3 normal, float roughness) {
+    float a = roughness * roughness;
+    float phi = 2.0 * PI * xi.x;
+    float cos_theta = sqrt((1.0 - xi.y) / (1.0 + (a * a - 1.0) * xi.y));
+    float sin_theta = sqrt(1.0 - cos_theta * cos_theta);
+
+    vec3 h = vec3(cos(phi) * sin_theta, sin(phi) * sin_theta, cos_theta);
+    vec3 up = abs(normal.z) < 0.999 ? vec3(0.0, 0.0, 1.0) : vec3(1.0, 0.0, 0.0);
+    vec3 tangent = normalize(cross(up, normal));
+    vec3 bitangent = cross(normal, tangent);
+
+    return normalize(tangent * h.x + bitangent * h.y + normal * h.z);
+}
+
+#if defined(EQUIRECTANGULAR)
+// z is up and the first row of image is the top of sky
+uniform sampler2D equirectangular;
+
+void main() {
+    vec3 d = direction();
+    vec2 uv = vec2(atan(d.y, d.x) / (2.0 * PI) + 0.5, 0.5 - asin(clamp(d.z, -1.0, 1.0)) / PI);
+    color_out = vec4(texture(equirectangular, uv).rgb, 1.0);
+}
+
+#elif defined(IRRADIANCE)
+uniform samplerCube environment;
+
+// cosine weighted integral over the hemisphere of normal
+void main() {
+    vec3 normal = direction();
+    vec3 up = abs(normal.z) < 0.999 ? vec3(0.0, 0.0, 1.0) : vec3(1.0, 0.0, 0.0);
+    vec3 right = normalize(cross(up, normal));
+    up = cross(normal, right);
+
+    float delta = 0.025;
+    vec3 sum = vec3(0.0);
+    float count = 0.0;
+
+    for(float phi = 0.0; phi < 2.0 * PI; phi += delta) {
+        for(float theta = 0.0; theta < 0.5 * PI; theta += delta) {
+            vec3 tangent_sample = vec3(sin(theta) * cos(phi), sin(theta) * sin(phi), cos(theta));
+            vec3 sample_direction = tangent_sample.x * right + tangent_sample.y * up + tangent_sample.z * normal;
+            sum += textureLod(environment, sample_direction, 0.0).rgb * cos(theta) * sin(theta);
+            count += 1.0;
+        }
+    }
+
+    color_out = vec4(PI * sum / count, 1.0);
+}
+
+#elif defined(PREFILTER)
+#define SAMPLE_COUNT 1024u
+
+uniform samplerCube environment;
+uniform float roughness;
+
+// ggx filtered environment with the view direction equal to normal
+void main() {
+    vec3 normal = direction();
+    vec3 sum = vec3(0.0);
+    float weight = 0.0;
+
+    for(uint i = 0u; i < SAMPLE_COUNT; i++) {
+        vec3 h = importance_sample_ggx(hammersley(i, SAMPLE_COUNT), normal, roughness);
+        vec3 l = normalize(2.0 * dot(normal, h) * h - normal);
+        float NL = max(dot(normal, l), 0.0);
+
+        if(NL > 0.0) {
+            sum += textureLod(environment, l, 0.0).rgb * NL;
+            weight += NL;
+        }
+    }
+
+    color_out = vec4(sum / max(weight, 0.0001), 1.0);
+}
+
+#elif defined(BRDF_LUT)
+#define SAMPLE_COUNT 1024u
+
+float geometry_schlick_ibl(float NV, float roughness) {
+    float k = roughness * roughness / 2.0;
+    return NV / (NV * (1.0 - k) + k);
+}
+
+// scale and bias of f0 by NV along x and roughness along y
+void main() {
+    vec2 uv = v_position * 0.5 + 0.5;
+    float NV = max(uv.x, 0.001);
+    float roughness = uv.y;
+
+    vec3 v = vec3(sqrt(1.0 - NV * NV), 0.0, NV);
+    vec3 normal = vec3(0.0, 0.0, 1.0);
+    float a = 0.0;
+    float b = 0.0;
+
+    for(uint i = 0u; i < SAMPLE_COUNT; i++) {
+        vec3 h = importance_sample_ggx(hammersley(i, SAMPLE_COUNT), normal, roughness);
+        vec3 l = normalize(2.0 * dot(v, h) * h - v);
+
+        float NL = max(l.z, 0.0);
+        float NH = max(h.z, 0.0);
+        float VH = max(dot(v, h), 0.0);
+
+        if(NL > 0.0) {
+            float g = geometry_schlick_ibl(NV, roughness) * geometry_schlick_ibl(NL, roughness);
+            float g_visibility = g * VH / (NH * NV);
+            float fc = pow(1.0 - VH, 5.0);
+            a += (1.0 - fc) * g_visibility;
+            b += fc * g_visibility;
+        }
+    }
+
+    color_out = vec4(a / float(SAMPLE_COUNT), b / float(SAMPLE_COUNT), 0.0, 1.0);
+}
+
+#else
+uniform samplerCube environment;
+uniform bool hdr_enable;
+uniform float gamma;
+
+void main() {
+    vec3 color = textureLod(environment, direction(), 0.0).rgb;
+
+    if(hdr_enable) {
+        color = pow(color / (color + vec3(1.0)), vec3(1.0 / gamma));
+    }
+
+    color_out = vec4(color, 1.0);
+}
+#endif
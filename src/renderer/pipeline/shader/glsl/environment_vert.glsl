@@ -0,0 +1,9 @@
+in layout(location = 0) vec2 position;
+
+out vec2 v_position;
+
+// on the far plane so that the skybox is behind the scene
+void main() {
+    gl_Position = vec4(position, 1.0, 1.0);
+    v_position = position;
+}
@@ -0,0 +1,34 @@
+uniform float opacity;
+uniform float alpha_cutoff;
+
+#ifdef OIT
+out vec4 oit_accum;
+out vec4 oit_revealage;
+#else
+out vec4 color_out;
+#endif
+
+void write_color(vec4 color) {
+    color.a *= opacity;
+
+    if (color.a < alpha_cutoff) {
+        discard;
+    }
+
+#ifdef OIT
+    /* weighted blended order independent transparency, both targets are additive blended,
+       revealage is stored as -log(1 - alpha) and resolved by exp(-x) */
+    float alpha = clamp(color.a, 0.0, 0.999);
+    float weight = clamp(alpha * 1000.0 * pow(1.0 - gl_FragCoord.z * 0.9, 3.0), 0.01, 3000.0);
+
+    oit_accum = vec4(color.rgb * alpha * weight, alpha * weight);
+    oit_revealage = vec4(-log(1.0 - alpha), 0.0, 0.0, 0.0);
+#else
+    color_out = color;
+#endif
+}
+
+// read a sampled texture of the material, `mip_bias` is its uniform `<name>_mip_bias`
+vec4 sample_texture(sampler2D tex, vec2 uv, float mip_bias) {
+    return texture(tex, uv, mip_bias);
+}
@@ -0,0 +1,67 @@
+uniform float opacity;
+uniform float alpha_cutoff;
+
+#define UNLIT 1.0
+#define BLINN_PHONG 2.0
+#define COOK_TORRANCE 3.0
+
+#if defined(COOK_TORRANCE_BRDF)
+struct CookTorranceBRDF {
+    vec3 albedo;
+    float roughness;
+    float metallic;
+    float ao;
+};
+
+uniform CookTorranceBRDF material;
+#elif defined(BLINN_PHONG_BRDF)
+struct BlinnPhongBRDF {
+    vec3 ambient;
+    vec3 diffuse;
+    vec3 specular;
+    float shininess;
+};
+
+uniform BlinnPhongBRDF material;
+#else
+struct PureColor {
+    vec3 color;
+};
+
+uniform PureColor material;
+#endif
+
+in vec3 v_normal;
+in vec3 frag_pos;
+in vec4 v_tint;
+
+// albedo and shading model, normal and shininess, roughness metallic ao or specular and ambient, depth
+out vec4 g_albedo;
+out vec4 g_normal;
+out vec4 g_material;
+out vec4 g_depth;
+
+void main() {
+    if (v_tint.a * opacity < alpha_cutoff) {
+        discard;
+    }
+
+    vec3 f_normal = normalize(v_normal);
+
+#if defined(COOK_TORRANCE_BRDF)
+    g_albedo = vec4(material.albedo * v_tint.rgb, COOK_TORRANCE / 255.0);
+    g_normal = vec4(f_normal, 0.0);
+    g_material = vec4(material.roughness, material.metallic, material.ao, 0.0);
+#elif defined(BLINN_PHONG_BRDF)
+    // ambient is stored as its average
+    g_albedo = vec4(material.diffuse * v_tint.rgb, BLINN_PHONG / 255.0);
+    g_normal = vec4(f_normal, material.shininess);
+    g_material = vec4(material.specular, dot(material.ambient, vec3(1.0 / 3.0)));
+#else
+    g_albedo = vec4(material.color * v_tint.rgb, UNLIT / 255.0);
+    g_normal = vec4(f_normal, 0.0);
+    g_material = vec4(0.0);
+#endif
+
+    g_depth = vec4(gl_FragCoord.z);
+}
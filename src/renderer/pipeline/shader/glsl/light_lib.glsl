@@ -1,15 +1,17 @@
 struct Light {
-    vec4 color_flux;
-    vec4 position;
+    vec4 color_intensity;
+    vec4 position_range;
     vec4 direction_type;
-    float cut_off;
-    float outer_cut_off;
-    float linear;
-    float quadratic;
+    float inner_angle;
+    float outer_angle;
+    float unit;
+    float padding;
 };
 
-#define l_color color_flux.rgb
-#define l_position position.xyz
+#define l_color color_intensity.rgb
+#define l_intensity color_intensity.w
+#define l_position position_range.xyz
+#define l_range position_range.w
 #define l_direction direction_type.xyz
 #define l_type direction_type.w
 
@@ -17,16 +19,65 @@ struct Light {
 #define Spot  1.0
 #define Parallel 2.0
 
+#define Lumen 0.0
+#define Candela 1.0
+#define Lux 2.0
+
+#define LIGHT_PI 3.14159265358979
+
+// light space matrices of cascades, layer_count_bias is
+// (first layer in shadow_map or -1.0 without shadow, number of cascades, bias, normal bias),
+// cube_far is (layer in point_shadow_map or -1.0 without shadow, far plane of cube faces)
+struct ShadowData {
+    mat4 light_space_0;
+    mat4 light_space_1;
+    mat4 light_space_2;
+    mat4 light_space_3;
+    vec4 layer_count_bias;
+    vec4 cube_far;
+};
+
+#ifdef LIGHTS_STORAGE
+// same buffer as the uniform block, without the maximum number of lights
+layout (std430) buffer Lights{
+    Light light[];
+};
+
+layout (std430) buffer Shadows{
+    ShadowData shadow[];
+};
+#else
 layout (std140) uniform Lights{
     Light[LIGHTS_MAX_NUMBER] light;
 };
 
-struct Shadow {
-    sampler2D tex;
-    mat4 mvp;
+layout (std140) uniform Shadows{
+    ShadowData[LIGHTS_MAX_NUMBER] shadow;
 };
+#endif
+
+#define SHADOW_HARD 0
+#define SHADOW_PCF 1
+#define SHADOW_POISSON 2
+
+uniform sampler2DArrayShadow shadow_map;
+// distance to light divided by far plane
+uniform samplerCubeArrayShadow point_shadow_map;
+uniform int shadow_filter;
+// pcf radius or poisson spread in texels
+uniform float shadow_filter_size;
+uniform int shadow_samples;
+// image based lighting of cook torrance materials, see `environment_ambient`
+uniform bool environment_enable;
+uniform samplerCube irradiance_map;
+uniform samplerCube prefiltered_map;
+uniform sampler2D brdf_lut;
+// mipmap level of prefiltered_map for roughness 1.0
+uniform float prefiltered_lod;
+// screen space ambient occlusion of the ambient term, see `ambient_occlusion`
+uniform bool ssao_enable;
+uniform sampler2D ssao_map;
 
-uniform Shadow[LIGHTS_MAX_NUMBER] shadow;
 uniform int lights_count;
 uniform vec3 view_position;
 uniform bool hdr_enable;
@@ -40,34 +91,194 @@ vec3 light_direction(Light light, vec3 frag_position) {
     }
 }
 
+// luminous intensity in candela of point light and spot light, illuminance in lux of parallel light
+float luminous_intensity(Light light) {
+    if(light.unit == Lumen) {
+        if(light.l_type == Spot) {
+            return light.l_intensity / max(2.0 * LIGHT_PI * (1.0 - cos(light.outer_angle)), 1e-6);
+        } else if(light.l_type == Point) {
+            return light.l_intensity / (4.0 * LIGHT_PI);
+        }
+    }
+    return light.l_intensity;
+}
+
 vec3 light_color(Light light, vec3 direction) {
+    vec3 color = light.l_color * luminous_intensity(light);
     if(light.l_type == Spot) {
         float theta = dot(-direction,normalize(light.l_direction));
-        float epsilon = light.cut_off - light.outer_cut_off;
-        float intensity = clamp( (theta - light.outer_cut_off) / epsilon, 0.0, 1.0);
-        return light.l_color * intensity;
+        float cut_off = cos(light.inner_angle);
+        float outer_cut_off = cos(light.outer_angle);
+        float epsilon = max(cut_off - outer_cut_off, 1e-4);
+        float intensity = clamp( (theta - outer_cut_off) / epsilon, 0.0, 1.0);
+        return color * intensity;
     } else {
-        return light.l_color;
+        return color;
     }
 }
 
-float visibility(vec4 position, sampler2D shadow_map, float bias) {
-    vec3 frag_pos = position.xyz / position.w;
-    frag_pos = frag_pos * 0.5 + 0.5;
-    float closet_depth = texture(shadow_map, frag_pos.xy).r;
-    float current_depth = frag_pos.z;
-    return current_depth > closet_depth?0.0:1.0;
+const vec2 poisson_disk[16] = vec2[](
+    vec2(-0.94201624, -0.39906216), vec2( 0.94558609, -0.76890725),
+    vec2(-0.09418410, -0.92938870), vec2( 0.34495938,  0.29387760),
+    vec2(-0.91588581,  0.45771432), vec2(-0.81544232, -0.87912464),
+    vec2(-0.38277543,  0.27676845), vec2( 0.97484398,  0.75648379),
+    vec2( 0.44323325, -0.97511554), vec2( 0.53742981, -0.47373420),
+    vec2(-0.26496911, -0.41893023), vec2( 0.79197514,  0.19090188),
+    vec2(-0.24188840,  0.99706507), vec2(-0.81409955,  0.91437590),
+    vec2( 0.19984126,  0.78641367), vec2( 0.14383161, -0.14100790)
+);
+
+mat4 light_space(ShadowData data, int cascade) {
+    if(cascade == 0) {
+        return data.light_space_0;
+    } else if(cascade == 1) {
+        return data.light_space_1;
+    } else if(cascade == 2) {
+        return data.light_space_2;
+    } else {
+        return data.light_space_3;
+    }
+}
+
+// depth comparison of 4 texels with linear filtering
+float shadow_compare(vec3 coordinate, float layer, vec2 offset) {
+    return texture(shadow_map, vec4(coordinate.xy + offset, layer, coordinate.z));
+}
+
+float filtered_shadow(vec3 coordinate, float layer) {
+    vec2 texel = 1.0 / vec2(textureSize(shadow_map, 0).xy);
+
+    if(shadow_filter == SHADOW_PCF) {
+        int radius = int(shadow_filter_size);
+        float sum = 0.0;
+        for(int x = -radius; x <= radius; x++) {
+            for(int y = -radius; y <= radius; y++) {
+                sum += shadow_compare(coordinate, layer, vec2(x, y) * texel);
+            }
+        }
+        return sum / float((2 * radius + 1) * (2 * radius + 1));
+    } else if(shadow_filter == SHADOW_POISSON) {
+        // the disk is rotated per fragment so that banding becomes noise
+        float angle = 6.2831853 * fract(sin(dot(coordinate.xy, vec2(12.9898, 78.233))) * 43758.5453);
+        mat2 rotation = mat2(cos(angle), sin(angle), -sin(angle), cos(angle));
+        int samples = clamp(shadow_samples, 1, 16);
+        float sum = 0.0;
+        for(int i = 0; i < samples; i++) {
+            sum += shadow_compare(coordinate, layer, rotation * poisson_disk[i] * shadow_filter_size * texel);
+        }
+        return sum / float(samples);
+    } else {
+        return shadow_compare(coordinate, layer, vec2(0.0));
+    }
 }
 
+const vec3 cube_offsets[20] = vec3[](
+    vec3( 1.0,  1.0,  1.0), vec3( 1.0, -1.0,  1.0), vec3(-1.0, -1.0,  1.0), vec3(-1.0,  1.0,  1.0),
+    vec3( 1.0,  1.0, -1.0), vec3( 1.0, -1.0, -1.0), vec3(-1.0, -1.0, -1.0), vec3(-1.0,  1.0, -1.0),
+    vec3( 1.0,  1.0,  0.0), vec3( 1.0, -1.0,  0.0), vec3(-1.0, -1.0,  0.0), vec3(-1.0,  1.0,  0.0),
+    vec3( 1.0,  0.0,  1.0), vec3(-1.0,  0.0,  1.0), vec3( 1.0,  0.0, -1.0), vec3(-1.0,  0.0, -1.0),
+    vec3( 0.0,  1.0,  1.0), vec3( 0.0, -1.0,  1.0), vec3( 0.0, -1.0, -1.0), vec3( 0.0,  1.0, -1.0)
+);
+
+// distance of the fragment to light compared with the distance in the cube map
+float point_shadow_visibility(ShadowData data, vec3 light_position, vec3 position) {
+    vec3 direction = position - light_position;
+    float distance = length(direction);
+    float current = distance / data.cube_far.y - data.layer_count_bias.z;
+
+    if(current >= 1.0) {
+        return 1.0;
+    }
+
+    if(shadow_filter == SHADOW_HARD) {
+        return texture(point_shadow_map, vec4(direction, data.cube_far.x), current);
+    }
+
+    // offsets of filter size texels at the distance of fragment
+    float texel = 2.0 * distance / float(textureSize(point_shadow_map, 0).x);
+    float radius = max(shadow_filter_size, 1.0) * texel;
+    int samples = shadow_filter == SHADOW_POISSON ? clamp(shadow_samples, 1, 20) : 20;
+    float sum = 0.0;
+    for(int i = 0; i < samples; i++) {
+        sum += texture(point_shadow_map, vec4(direction + cube_offsets[i] * radius, data.cube_far.x), current);
+    }
+    return sum / float(samples);
+}
+
+// 1.0 is lit and 0.0 is in shadow, the first cascade containing the fragment is used
+float shadow_visibility(int index, vec3 frag_position, vec3 normal) {
+    ShadowData data = shadow[index];
+    float layer = data.layer_count_bias.x;
+
+    if(data.cube_far.x >= 0.0) {
+        return point_shadow_visibility(data, light[index].l_position, frag_position + normal * data.layer_count_bias.w);
+    }
+
+    if(layer < 0.0) {
+        return 1.0;
+    }
+
+    vec3 position = frag_position + normal * data.layer_count_bias.w;
+    int count = int(data.layer_count_bias.y);
+
+    for(int cascade = 0; cascade < count; cascade++) {
+        vec4 light_position = light_space(data, cascade) * vec4(position, 1.0);
+        vec3 coordinate = light_position.xyz / light_position.w * 0.5 + 0.5;
+
+        if(all(greaterThanEqual(coordinate, vec3(0.0))) && all(lessThanEqual(coordinate, vec3(1.0)))) {
+            coordinate.z -= data.layer_count_bias.z;
+            return filtered_shadow(coordinate, layer + float(cascade));
+        }
+    }
+
+    return 1.0;
+}
+
+// inverse square falloff windowed to zero at the range of light
 float attenuation(Light light, vec3 frag_position) {
     if(light.l_type != Parallel) {
         float distance = length(light.l_position - frag_position);
-        return 1.0f / (1.0 + light.linear * distance + light.quadratic * distance * distance);
+        float falloff = 1.0 / max(distance * distance, 0.0001);
+        if(light.l_range > 0.0) {
+            float window = clamp(1.0 - pow(distance / light.l_range, 4.0), 0.0, 1.0);
+            falloff = falloff * window * window;
+        }
+        return falloff;
     } else {
         return 1.0;
     }
 }
 
+// diffuse irradiance and split sum specular of the environment, a constant ambient without environment
+vec3 environment_ambient(vec3 normal, vec3 view_direction, vec3 albedo, float metallic, float roughness, float ao) {
+    if(!environment_enable) {
+        return vec3(0.03) * albedo * ao;
+    }
+
+    float NV = max(dot(normal, view_direction), 0.0);
+    vec3 f = mix(vec3(0.04), albedo, metallic);
+    vec3 F = f + (max(vec3(1.0 - roughness), f) - f) * pow(1.0 - NV, 5.0);
+    vec3 kd = (vec3(1.0) - F) * (1.0 - metallic);
+
+    vec3 diffuse = texture(irradiance_map, normal).rgb * albedo;
+
+    vec3 reflection = reflect(-view_direction, normal);
+    vec3 prefiltered = textureLod(prefiltered_map, reflection, roughness * prefiltered_lod).rgb;
+    vec2 brdf = texture(brdf_lut, vec2(NV, roughness)).rg;
+    vec3 specular = prefiltered * (F * brdf.x + brdf.y);
+
+    return (kd * diffuse + specular) * ao;
+}
+
+// ambient visibility of the fragment on screen, 1.0 without ambient occlusion
+float ambient_occlusion() {
+    if(!ssao_enable) {
+        return 1.0;
+    }
+
+    return texture(ssao_map, gl_FragCoord.xy / vec2(textureSize(ssao_map, 0))).r;
+}
+
 vec4 hdr(vec4 color) {
     if(hdr_enable) {
         vec3 hdr_color = color.rgb;
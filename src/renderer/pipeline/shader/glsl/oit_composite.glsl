@@ -0,0 +1,17 @@
+uniform sampler2D oit_accum;
+uniform sampler2D oit_revealage;
+
+in vec2 v_tex_coordinate;
+
+out vec4 f_color;
+
+void main() {
+    vec4 accum = texture(oit_accum, v_tex_coordinate);
+    float revealage = exp(-texture(oit_revealage, v_tex_coordinate).r);
+
+    if (revealage >= 0.999) {
+        discard;
+    }
+
+    f_color = vec4(accum.rgb / max(accum.a, 0.00001), 1.0 - revealage);
+}
\ No newline at end of file
@@ -0,0 +1,9 @@
+uniform vec3 light_position;
+uniform float far;
+
+in vec3 world_position;
+
+// linear distance to light, compared with the distance of fragments in lighting shaders
+void main() {
+    gl_FragDepth = length(world_position - light_position) / far;
+}
@@ -0,0 +1,27 @@
+in layout(location = 0) vec3 position;
+
+#ifdef SKINNING
+in layout(location = 3) uvec4 joints;
+in layout(location = 4) vec4 weights;
+#endif
+
+#ifdef INSTANCING
+in layout(location = 5) mat4 instance_transform;
+#endif
+
+out vec3 world_position;
+
+void main() {
+#ifdef INSTANCING
+    mat4 model = transform * instance_transform;
+#else
+    mat4 model = transform;
+#endif
+
+#ifdef SKINNING
+    model = model * skin_matrix(joints, weights);
+#endif
+    vec4 world = model * vec4(position,1.0);
+    world_position = world.xyz;
+    gl_Position = project * view * world;
+}
@@ -0,0 +1,182 @@
+uniform sampler2D source;
+// size of a texel of source
+uniform vec2 texel_size;
+
+in vec2 v_tex_coordinate;
+
+out vec4 color_out;
+
+#if defined(BLOOM_PREFILTER)
+uniform float threshold;
+
+// soft knee around the threshold
+void main() {
+    vec3 color = texture(source, v_tex_coordinate).rgb;
+    float brightness = max(color.r, max(color.g, color.b));
+    float knee = threshold * 0.5;
+    float soft = clamp(brightness - threshold + knee, 0.0, 2.0 * knee);
+    soft = soft * soft / (4.0 * knee + 0.00001);
+    float contribution = max(soft, brightness - threshold) / max(brightness, 0.00001);
+    color_out = vec4(color * contribution, 1.0);
+}
+
+#elif defined(BLOOM_DOWNSAMPLE)
+// dual filter downsample
+void main() {
+    vec2 offset = texel_size * 0.5;
+    vec3 sum = texture(source, v_tex_coordinate).rgb * 4.0;
+    sum += texture(source, v_tex_coordinate - offset).rgb;
+    sum += texture(source, v_tex_coordinate + offset).rgb;
+    sum += texture(source, v_tex_coordinate + vec2(offset.x, -offset.y)).rgb;
+    sum += texture(source, v_tex_coordinate - vec2(offset.x, -offset.y)).rgb;
+    color_out = vec4(sum / 8.0, 1.0);
+}
+
+#elif defined(BLOOM_UPSAMPLE)
+// dual filter upsample
+void main() {
+    vec2 offset = texel_size * 0.5;
+    vec3 sum = texture(source, v_tex_coordinate + vec2(-offset.x * 2.0, 0.0)).rgb;
+    sum += texture(source, v_tex_coordinate + vec2(-offset.x, offset.y)).rgb * 2.0;
+    sum += texture(source, v_tex_coordinate + vec2(0.0, offset.y * 2.0)).rgb;
+    sum += texture(source, v_tex_coordinate + vec2(offset.x, offset.y)).rgb * 2.0;
+    sum += texture(source, v_tex_coordinate + vec2(offset.x * 2.0, 0.0)).rgb;
+    sum += texture(source, v_tex_coordinate + vec2(offset.x, -offset.y)).rgb * 2.0;
+    sum += texture(source, v_tex_coordinate + vec2(0.0, -offset.y * 2.0)).rgb;
+    sum += texture(source, v_tex_coordinate + vec2(-offset.x, -offset.y)).rgb * 2.0;
+    color_out = vec4(sum / 12.0, 1.0);
+}
+
+#elif defined(BLOOM_COMPOSITE)
+uniform sampler2D bloom;
+uniform float intensity;
+
+void main() {
+    vec4 color = texture(source, v_tex_coordinate);
+    color_out = vec4(color.rgb + texture(bloom, v_tex_coordinate).rgb * intensity, color.a);
+}
+
+#elif defined(TONE_MAPPING)
+#define REINHARD 0
+#define ACES 1
+#define FILMIC 2
+
+uniform int tone_mapping;
+uniform float exposure;
+uniform float gamma;
+
+vec3 aces(vec3 x) {
+    return clamp((x * (2.51 * x + 0.03)) / (x * (2.43 * x + 0.59) + 0.14), 0.0, 1.0);
+}
+
+vec3 uncharted2(vec3 x) {
+    float a = 0.15;
+    float b = 0.50;
+    float c = 0.10;
+    float d = 0.20;
+    float e = 0.02;
+    float f = 0.30;
+    return ((x * (a * x + c * b) + d * e) / (x * (a * x + b) + d * f)) - e / f;
+}
+
+void main() {
+    vec4 color = texture(source, v_tex_coordinate);
+    vec3 hdr_color = color.rgb * exposure;
+    vec3 mapped;
+
+    if (tone_mapping == ACES) {
+        mapped = aces(hdr_color);
+    } else if (tone_mapping == FILMIC) {
+        mapped = uncharted2(hdr_color * 2.0) / uncharted2(vec3(11.2));
+    } else {
+        mapped = hdr_color / (hdr_color + vec3(1.0));
+    }
+
+    color_out = vec4(pow(mapped, vec3(1.0 / gamma)), color.a);
+}
+
+#elif defined(FXAA)
+#define FXAA_REDUCE_MIN (1.0 / 128.0)
+#define FXAA_REDUCE_MUL (1.0 / 8.0)
+#define FXAA_SPAN_MAX 8.0
+
+// luma of display colors, fxaa is after tone mapping
+void main() {
+    vec3 luma = vec3(0.299, 0.587, 0.114);
+    float luma_nw = dot(texture(source, v_tex_coordinate + vec2(-1.0, -1.0) * texel_size).rgb, luma);
+    float luma_ne = dot(texture(source, v_tex_coordinate + vec2( 1.0, -1.0) * texel_size).rgb, luma);
+    float luma_sw = dot(texture(source, v_tex_coordinate + vec2(-1.0,  1.0) * texel_size).rgb, luma);
+    float luma_se = dot(texture(source, v_tex_coordinate + vec2( 1.0,  1.0) * texel_size).rgb, luma);
+    vec4 color_m = texture(source, v_tex_coordinate);
+    float luma_m = dot(color_m.rgb, luma);
+
+    float luma_min = min(luma_m, min(min(luma_nw, luma_ne), min(luma_sw, luma_se)));
+    float luma_max = max(luma_m, max(max(luma_nw, luma_ne), max(luma_sw, luma_se)));
+
+    vec2 direction = vec2(
+        -((luma_nw + luma_ne) - (luma_sw + luma_se)),
+         ((luma_nw + luma_sw) - (luma_ne + luma_se))
+    );
+
+    float direction_reduce = max((luma_nw + luma_ne + luma_sw + luma_se) * 0.25 * FXAA_REDUCE_MUL, FXAA_REDUCE_MIN);
+    float reciprocal_min = 1.0 / (min(abs(direction.x), abs(direction.y)) + direction_reduce);
+    direction = clamp(direction * reciprocal_min, vec2(-FXAA_SPAN_MAX), vec2(FXAA_SPAN_MAX)) * texel_size;
+
+    vec3 a = 0.5 * (
+        texture(source, v_tex_coordinate + direction * (1.0 / 3.0 - 0.5)).rgb +
+        texture(source, v_tex_coordinate + direction * (2.0 / 3.0 - 0.5)).rgb
+    );
+    vec3 b = a * 0.5 + 0.25 * (
+        texture(source, v_tex_coordinate + direction * -0.5).rgb +
+        texture(source, v_tex_coordinate + direction * 0.5).rgb
+    );
+
+    float luma_b = dot(b, luma);
+
+    if (luma_b < luma_min || luma_b > luma_max) {
+        color_out = vec4(a, color_m.a);
+    } else {
+        color_out = vec4(b, color_m.a);
+    }
+}
+
+#elif defined(VIGNETTE)
+uniform float intensity;
+uniform float smoothness;
+
+void main() {
+    vec4 color = texture(source, v_tex_coordinate);
+    float distance = length(v_tex_coordinate - vec2(0.5)) * 1.41421356;
+    float vignette = 1.0 - intensity * smoothstep(1.0 - smoothness, 1.0, distance);
+    color_out = vec4(color.rgb * vignette, color.a);
+}
+
+#elif defined(COLOR_GRADING)
+uniform sampler2D lut;
+uniform float lut_size;
+uniform float contribution;
+
+vec3 lut_color(vec3 color) {
+    float slice = color.b * (lut_size - 1.0);
+    float slice_0 = floor(slice);
+    float slice_1 = min(slice_0 + 1.0, lut_size - 1.0);
+
+    float x = (color.r * (lut_size - 1.0) + 0.5) / (lut_size * lut_size);
+    float y = (color.g * (lut_size - 1.0) + 0.5) / lut_size;
+
+    vec3 color_0 = texture(lut, vec2(x + slice_0 / lut_size, y)).rgb;
+    vec3 color_1 = texture(lut, vec2(x + slice_1 / lut_size, y)).rgb;
+    return mix(color_0, color_1, slice - slice_0);
+}
+
+void main() {
+    vec4 color = texture(source, v_tex_coordinate);
+    vec3 graded = lut_color(clamp(color.rgb, 0.0, 1.0));
+    color_out = vec4(mix(color.rgb, graded, contribution), color.a);
+}
+
+#else
+void main() {
+    color_out = texture(source, v_tex_coordinate);
+}
+#endif
@@ -4,8 +4,8 @@ struct PureColor {
 
 uniform PureColor material;
 
-out vec4 color_out;
+in vec4 v_tint;
 
 void main() {
-    color_out = vec4(material.color,1.0);
+    write_color(vec4(material.color,1.0) * v_tint);
 }
\ No newline at end of file
@@ -1,5 +1,23 @@
 in layout(location = 0) vec3 position;
 
+#ifdef SKINNING
+in layout(location = 3) uvec4 joints;
+in layout(location = 4) vec4 weights;
+#endif
+
+#ifdef INSTANCING
+in layout(location = 5) mat4 instance_transform;
+#endif
+
 void main() {
-    gl_Position = project * view * transform * vec4(position,1.0);
-}
\ No newline at end of file
+#ifdef INSTANCING
+    mat4 model = transform * instance_transform;
+#else
+    mat4 model = transform;
+#endif
+
+#ifdef SKINNING
+    model = model * skin_matrix(joints, weights);
+#endif
+    gl_Position = project * view * model * vec4(position,1.0);
+}
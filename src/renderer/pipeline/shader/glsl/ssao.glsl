@@ -0,0 +1,97 @@
+// depth and normals are read from the g-buffer, `SSAO` writes the occlusion and `SSAO_BLUR` blurs it by depth
+uniform sampler2D g_depth;
+uniform mat4 camera_view;
+uniform mat4 camera_project;
+
+in vec2 v_tex_coordinate;
+flat in mat4 inverse_view_project;
+
+out float occlusion_out;
+
+// distance to the camera plane of a depth in the perspective projection
+float linear_depth(float depth) {
+    return camera_project[3][2] / (depth * 2.0 - 1.0 + camera_project[2][2]);
+}
+
+#if defined(SSAO)
+struct SsaoSample {
+    vec4 position;
+};
+
+layout (std140) uniform SsaoKernel {
+    SsaoSample kernel[SSAO_MAX_SAMPLES];
+};
+
+uniform sampler2D g_normal;
+uniform sampler2D noise;
+uniform int samples;
+uniform float radius;
+uniform float bias;
+uniform float intensity;
+
+vec3 view_space(vec2 coordinate, float depth) {
+    vec4 position = inverse_view_project * vec4(vec3(coordinate, depth) * 2.0 - 1.0, 1.0);
+    return (camera_view * vec4(position.xyz / position.w, 1.0)).xyz;
+}
+
+void main() {
+    float depth = texture(g_depth, v_tex_coordinate).r;
+
+    if (depth >= 1.0) {
+        occlusion_out = 1.0;
+        return;
+    }
+
+    vec3 position = view_space(v_tex_coordinate, depth);
+    vec3 normal = normalize(mat3(camera_view) * texture(g_normal, v_tex_coordinate).xyz);
+
+    // the hemisphere is rotated around the normal by the tiled noise
+    vec2 noise_scale = vec2(textureSize(g_depth, 0)) / vec2(textureSize(noise, 0));
+    vec3 random = texture(noise, v_tex_coordinate * noise_scale).xyz;
+    vec3 tangent = normalize(random - normal * dot(random, normal));
+    vec3 bitangent = cross(normal, tangent);
+    mat3 tbn = mat3(tangent, bitangent, normal);
+
+    float occlusion = 0.0;
+
+    for (int i = 0; i < samples; i++) {
+        vec3 sample_position = position + tbn * kernel[i].position.xyz * radius;
+
+        vec4 offset = camera_project * vec4(sample_position, 1.0);
+        offset.xy = offset.xy / offset.w * 0.5 + 0.5;
+
+        float scene_depth = view_space(offset.xy, texture(g_depth, offset.xy).r).z;
+
+        // geometry far behind the sample doesn't occlude
+        float range = smoothstep(0.0, 1.0, radius / max(abs(position.z - scene_depth), 0.0001));
+        occlusion += (scene_depth >= sample_position.z + bias ? 1.0 : 0.0) * range;
+    }
+
+    occlusion_out = clamp(1.0 - intensity * occlusion / float(max(samples, 1)), 0.0, 1.0);
+}
+#elif defined(SSAO_BLUR)
+uniform sampler2D ssao_raw;
+uniform int blur_radius;
+
+void main() {
+    vec2 texel = 1.0 / vec2(textureSize(ssao_raw, 0));
+    float center = linear_depth(texture(g_depth, v_tex_coordinate).r);
+
+    float occlusion = 0.0;
+    float weights = 0.0;
+
+    // texels across an edge of depth are ignored
+    for (int x = -blur_radius; x <= blur_radius; x++) {
+        for (int y = -blur_radius; y <= blur_radius; y++) {
+            vec2 coordinate = v_tex_coordinate + vec2(x, y) * texel;
+            float depth = linear_depth(texture(g_depth, coordinate).r);
+            float weight = exp(-abs(center - depth) / (center * 0.05 + 0.0001));
+
+            occlusion += texture(ssao_raw, coordinate).r * weight;
+            weights += weight;
+        }
+    }
+
+    occlusion_out = occlusion / max(weights, 0.0001);
+}
+#endif
@@ -3,4 +3,38 @@ layout(std140) uniform Camera {
     mat4 view;
 };
 
-uniform mat4 transform;
\ No newline at end of file
+uniform mat4 transform;
+
+#ifdef SKINNING
+layout(std140) uniform Skin {
+    mat4 joint_matrices[JOINTS_MAX_NUMBER];
+};
+
+// linear blend skinning like `skin_vertices`, joints out of range or without matrix are ignored,
+// the weights are normalized and a vertex without weight keeps its bind pose
+mat4 skin_matrix(uvec4 joints, vec4 weights) {
+    mat4 skin = mat4(0.0);
+    float total_weight = 0.0;
+
+    for (int i = 0; i < 4; i++) {
+        if (weights[i] == 0.0 || joints[i] >= uint(JOINTS_MAX_NUMBER)) {
+            continue;
+        }
+
+        // padding of the skin block is zero
+        mat4 joint_matrix = joint_matrices[joints[i]];
+        if (joint_matrix[3][3] == 0.0) {
+            continue;
+        }
+
+        skin += weights[i] * joint_matrix;
+        total_weight += weights[i];
+    }
+
+    if (total_weight == 0.0) {
+        return mat4(1.0);
+    }
+
+    return skin / total_weight;
+}
+#endif
@@ -0,0 +1,242 @@
+use super::image::Image;
+
+#[derive(Debug,Copy,Clone,PartialEq)]
+pub enum ToneMapping {
+    Reinhard,
+    /// filmic curve fitted to aces
+    Aces,
+    /// uncharted 2 filmic curve
+    Filmic,
+}
+
+#[derive(Debug,Clone,PartialEq)]
+pub enum PostEffect {
+    /// bright parts over the threshold are blurred by `iterations` half size dual filter passes and added
+    Bloom { threshold: f32, intensity: f32, iterations: u32 },
+    /// hdr color to display color, the gamma of renderer is applied after tone mapping
+    ToneMapping { operator: ToneMapping, exposure: f32 },
+    Fxaa,
+    /// darken the corners, `smoothness` is the width of the falloff in (0,1]
+    Vignette { intensity: f32, smoothness: f32 },
+    /// lookup table of `n` x `n` slices side by side, the image is `n * n` x `n`,
+    /// red along the slice, green from the first row and blue by slice
+    ColorGrading { lut: Image, contribution: f32 },
+}
+
+impl PostEffect {
+    pub fn bloom() -> Self {
+        PostEffect::Bloom { threshold: 1.0, intensity: 0.5, iterations: 5 }
+    }
+
+    pub fn tone_mapping(operator: ToneMapping) -> Self {
+        PostEffect::ToneMapping { operator, exposure: 1.0 }
+    }
+
+    pub fn vignette() -> Self {
+        PostEffect::Vignette { intensity: 0.3, smoothness: 0.5 }
+    }
+
+    pub fn color_grading(lut: Image) -> Self {
+        PostEffect::ColorGrading { lut, contribution: 1.0 }
+    }
+}
+
+/// # Post Process Stack
+/// named effects applied in order to the hdr frame, tone mapping is usually before fxaa, vignette and color grading.
+/// the stack counts changes so that backends know when to upload lookup tables again
+#[derive(Debug,Clone,PartialEq,Default)]
+pub struct PostProcessStack {
+    effects: Vec<(String,PostEffect)>,
+    version: u64,
+}
+
+impl PostProcessStack {
+    pub fn new() -> Self {
+        Self {
+            effects: Vec::new(),
+            version: 0,
+        }
+    }
+
+    pub fn with_effect(mut self, name: &str, effect: PostEffect) -> Self {
+        self.push(name, effect);
+        self
+    }
+
+    /// add the effect at the end, an effect with same name is replaced in place
+    pub fn push(&mut self, name: &str, effect: PostEffect) {
+        match self.position(name) {
+            Some(i) => self.effects[i].1 = effect,
+            None => self.effects.push((name.to_string(), effect)),
+        }
+        self.version += 1;
+    }
+
+    /// insert the effect before `index`, an effect with same name is moved
+    pub fn insert(&mut self, index: usize, name: &str, effect: PostEffect) {
+        self.remove(name);
+        let index = index.min(self.effects.len());
+        self.effects.insert(index, (name.to_string(), effect));
+        self.version += 1;
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<PostEffect> {
+        let i = self.position(name)?;
+        self.version += 1;
+        Some(self.effects.remove(i).1)
+    }
+
+    /// move the effect to `index`, false if it is not in the stack
+    pub fn move_to(&mut self, name: &str, index: usize) -> bool {
+        match self.position(name) {
+            Some(i) => {
+                let effect = self.effects.remove(i);
+                let index = index.min(self.effects.len());
+                self.effects.insert(index, effect);
+                self.version += 1;
+                true
+            },
+            None => false,
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.effects.clear();
+        self.version += 1;
+    }
+
+    pub fn get(&self, name: &str) -> Option<&PostEffect> {
+        self.effects.iter().find(|(x, _)| x == name).map(|(_, effect)| effect)
+    }
+
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut PostEffect> {
+        let i = self.position(name)?;
+        self.version += 1;
+        Some(&mut self.effects[i].1)
+    }
+
+    pub fn position(&self, name: &str) -> Option<usize> {
+        self.effects.iter().position(|(x, _)| x == name)
+    }
+
+    pub fn effects(&self) -> &[(String,PostEffect)] {
+        self.effects.as_slice()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.effects.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.effects.len()
+    }
+
+    /// the scene is rendered without the tone mapping of forward shaders
+    pub fn has_tone_mapping(&self) -> bool {
+        self.effects.iter().any(|(_, effect)| match effect {
+            PostEffect::ToneMapping { .. } => true,
+            _ => false,
+        })
+    }
+
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(stack: &PostProcessStack) -> Vec<&str> {
+        stack.effects().iter().map(|(name, _)| name.as_str()).collect()
+    }
+
+    fn stack() -> PostProcessStack {
+        PostProcessStack::new()
+            .with_effect("bloom", PostEffect::bloom())
+            .with_effect("tone", PostEffect::tone_mapping(ToneMapping::Aces))
+            .with_effect("fxaa", PostEffect::Fxaa)
+    }
+
+    #[test]
+    fn push_replaces_in_place() {
+        let mut stack = stack();
+        assert_eq!(names(&stack), vec!["bloom", "tone", "fxaa"]);
+        assert!(stack.has_tone_mapping());
+
+        stack.push("tone", PostEffect::tone_mapping(ToneMapping::Reinhard));
+        assert_eq!(names(&stack), vec!["bloom", "tone", "fxaa"]);
+        assert_eq!(stack.get("tone"), Some(&PostEffect::tone_mapping(ToneMapping::Reinhard)));
+
+        stack.push("vignette", PostEffect::vignette());
+        assert_eq!(names(&stack), vec!["bloom", "tone", "fxaa", "vignette"]);
+    }
+
+    #[test]
+    fn insert_moves() {
+        let mut stack = stack();
+
+        stack.insert(0, "fxaa", PostEffect::Fxaa);
+        assert_eq!(names(&stack), vec!["fxaa", "bloom", "tone"]);
+
+        stack.insert(1, "vignette", PostEffect::vignette());
+        assert_eq!(names(&stack), vec!["fxaa", "vignette", "bloom", "tone"]);
+
+        /* the index is clamped to the end */
+        stack.insert(10, "bloom", PostEffect::bloom());
+        assert_eq!(names(&stack), vec!["fxaa", "vignette", "tone", "bloom"]);
+    }
+
+    #[test]
+    fn move_and_remove() {
+        let mut stack = stack();
+
+        assert!(stack.move_to("bloom", 2));
+        assert_eq!(names(&stack), vec!["tone", "fxaa", "bloom"]);
+        assert!(stack.move_to("fxaa", 0));
+        assert_eq!(names(&stack), vec!["fxaa", "tone", "bloom"]);
+        assert!(!stack.move_to("vignette", 0));
+
+        assert_eq!(stack.remove("tone"), Some(PostEffect::tone_mapping(ToneMapping::Aces)));
+        assert_eq!(stack.remove("tone"), None);
+        assert_eq!(names(&stack), vec!["fxaa", "bloom"]);
+        assert!(!stack.has_tone_mapping());
+
+        stack.clear();
+        assert!(stack.is_empty());
+    }
+
+    #[test]
+    fn version() {
+        let mut stack = PostProcessStack::new();
+        let mut version = stack.version();
+        let mut changed = |stack: &PostProcessStack| {
+            let result = stack.version() > version;
+            version = stack.version();
+            result
+        };
+
+        stack.push("bloom", PostEffect::bloom());
+        assert!(changed(&stack));
+        stack.push("bloom", PostEffect::bloom());
+        assert!(changed(&stack));
+        stack.insert(0, "fxaa", PostEffect::Fxaa);
+        assert!(changed(&stack));
+        assert!(stack.move_to("fxaa", 1));
+        assert!(changed(&stack));
+        stack.get_mut("fxaa");
+        assert!(changed(&stack));
+        stack.remove("fxaa");
+        assert!(changed(&stack));
+        stack.clear();
+        assert!(changed(&stack));
+
+        /* nothing changed */
+        stack.get("bloom");
+        assert!(!stack.move_to("fxaa", 0));
+        assert!(stack.remove("fxaa").is_none());
+        stack.get_mut("fxaa");
+        assert!(!changed(&stack));
+    }
+}
@@ -0,0 +1,302 @@
+use crate::base::material::BlendMode;
+use std::collections::{BTreeSet, HashMap};
+use std::cmp::Ordering;
+use std::ops::Range;
+
+/// group of draws in a frame, groups are drawn in this order
+#[derive(Debug,Copy,Clone,PartialEq,Eq,PartialOrd,Ord,Hash)]
+pub enum RenderPhase {
+    Opaque,
+    AlphaTest,
+    Transparent,
+}
+
+impl From<BlendMode> for RenderPhase {
+    fn from(blend_mode: BlendMode) -> Self {
+        match blend_mode {
+            BlendMode::Opaque => RenderPhase::Opaque,
+            BlendMode::AlphaTest { .. } => RenderPhase::AlphaTest,
+            BlendMode::AlphaBlend | BlendMode::Additive => RenderPhase::Transparent,
+        }
+    }
+}
+
+#[derive(Debug,Clone)]
+pub struct DrawItem {
+    pub object_name: String,
+    pub material_name: String,
+    /// parent of a material instance, or the material itself
+    pub parent_name: String,
+    /// shader name of the material
+    pub shader_name: String,
+    pub mesh_name: String,
+    pub phase: RenderPhase,
+    /// distance to camera along the view direction, see `view_depth`
+    pub depth: f32,
+    /// built by `RenderQueue::sort`
+    pub key: u64,
+}
+
+/// # Render Queue
+/// draws of a frame sorted by key, the order only depends on the pushed items so that
+/// the frame is same between runs.
+///
+/// key of opaque and alpha test items: `phase | shader | parent | material | mesh | depth` from front to back,
+/// items with same shader and material are adjacent to minimize program and uniform changes,
+/// instances of a material follow each other.
+/// key of transparent items: `phase | depth` from back to front.
+/// ranks out of their bits are clamped, then the names are compared before the mesh and depth
+/// so that the items of a material stay adjacent. items with same key are ordered by object name.
+pub struct RenderQueue {
+    items: Vec<DrawItem>,
+    material_ranges: HashMap<(RenderPhase,String),Range<usize>>,
+}
+
+const PHASE_BITS   : u32 = 2;
+const SHADER_BITS  : u32 = 8;
+const PARENT_BITS  : u32 = 12;
+const MATERIAL_BITS: u32 = 12;
+const MESH_BITS    : u32 = 14;
+const DEPTH_BITS   : u32 = 64 - PHASE_BITS - SHADER_BITS - PARENT_BITS - MATERIAL_BITS - MESH_BITS;
+
+impl RenderQueue {
+    pub fn new() -> Self {
+        Self {
+            items: Vec::new(),
+            material_ranges: HashMap::new(),
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.items.clear();
+        self.material_ranges.clear();
+    }
+
+    pub fn push(&mut self, object_name: &str, material_name: &str, parent_name: &str, shader_name: &str, mesh_name: &str, phase: RenderPhase, depth: f32) {
+        self.items.push(DrawItem {
+            object_name: object_name.to_string(),
+            material_name: material_name.to_string(),
+            parent_name: parent_name.to_string(),
+            shader_name: shader_name.to_string(),
+            mesh_name: mesh_name.to_string(),
+            phase,
+            depth,
+            key: 0,
+        });
+    }
+
+    /// build keys and sort items, call it after all items are pushed
+    pub fn sort(&mut self) {
+        let shader_ranks = ranks(self.items.iter().map(|x| &x.shader_name));
+        let parent_ranks = ranks(self.items.iter().map(|x| &x.parent_name));
+        let material_ranks = ranks(self.items.iter().map(|x| &x.material_name));
+        let mesh_ranks = ranks(self.items.iter().map(|x| &x.mesh_name));
+
+        for item in self.items.iter_mut() {
+            let phase = item.phase as u64;
+            let depth = ordered_depth(item.depth);
+
+            item.key = match item.phase {
+                RenderPhase::Transparent => (phase << (64 - PHASE_BITS)) | (!depth >> PHASE_BITS),
+                RenderPhase::Opaque | RenderPhase::AlphaTest => {
+                    let shader = rank_bits(shader_ranks[&item.shader_name], SHADER_BITS);
+                    let parent = rank_bits(parent_ranks[&item.parent_name], PARENT_BITS);
+                    let material = rank_bits(material_ranks[&item.material_name], MATERIAL_BITS);
+                    let mesh = rank_bits(mesh_ranks[&item.mesh_name], MESH_BITS);
+
+                    (phase << (64 - PHASE_BITS))
+                        | (shader << (DEPTH_BITS + MESH_BITS + MATERIAL_BITS + PARENT_BITS))
+                        | (parent << (DEPTH_BITS + MESH_BITS + MATERIAL_BITS))
+                        | (material << (DEPTH_BITS + MESH_BITS))
+                        | (mesh << DEPTH_BITS)
+                        | (depth >> (64 - DEPTH_BITS))
+                },
+            };
+        }
+
+        self.items.sort_by(|a, b| {
+            let group = match (a.phase, b.phase) {
+                (RenderPhase::Transparent, _) | (_, RenderPhase::Transparent) => Ordering::Equal,
+                _ => (a.key >> (DEPTH_BITS + MESH_BITS)).cmp(&(b.key >> (DEPTH_BITS + MESH_BITS)))
+                    .then_with(|| names(a).cmp(&names(b))),
+            };
+
+            group
+                .then_with(|| a.key.cmp(&b.key))
+                .then_with(|| names(a).cmp(&names(b)))
+                .then_with(|| a.mesh_name.cmp(&b.mesh_name))
+                .then_with(|| a.object_name.cmp(&b.object_name))
+        });
+
+        /* transparent items of a material are not adjacent, they are drawn one by one */
+        self.material_ranges.clear();
+
+        let mut start = 0;
+        for i in 1..=self.items.len() {
+            let end_of_run = i == self.items.len()
+                || self.items[i].phase != self.items[start].phase
+                || self.items[i].material_name != self.items[start].material_name;
+
+            if end_of_run {
+                let item = &self.items[start];
+                if item.phase != RenderPhase::Transparent {
+                    self.material_ranges.insert((item.phase, item.material_name.clone()), start..i);
+                }
+                start = i;
+            }
+        }
+    }
+
+    pub fn items(&self) -> &[DrawItem] {
+        self.items.as_slice()
+    }
+
+    pub fn phase_items(&self, phase: RenderPhase) -> &[DrawItem] {
+        let start = self.items.iter().position(|x| x.phase == phase).unwrap_or(self.items.len());
+        let end = self.items[start..].iter().position(|x| x.phase != phase).map(|x| x + start).unwrap_or(self.items.len());
+        &self.items[start..end]
+    }
+
+    /// opaque or alpha test items of a material, in the queue order
+    pub fn material_items(&self, phase: RenderPhase, material_name: &str) -> &[DrawItem] {
+        match self.material_ranges.get(&(phase, material_name.to_string())) {
+            Some(range) => &self.items[range.clone()],
+            None => &[],
+        }
+    }
+
+    /// materials of opaque and alpha test items in the queue order
+    pub fn materials(&self) -> Vec<(RenderPhase,&String)> {
+        let mut materials: Vec<(usize,RenderPhase,&String)> = self.material_ranges
+            .iter()
+            .map(|((phase, name), range)| (range.start, *phase, name))
+            .collect();
+
+        materials.sort();
+        materials.into_iter().map(|(_, phase, name)| (phase, name)).collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+}
+
+/// rank of names in alphabetical order, same names have same rank
+fn ranks<'a, I: Iterator<Item = &'a String>>(names: I) -> HashMap<String,u64> {
+    let names: BTreeSet<&String> = names.collect();
+    names.into_iter().enumerate().map(|(i, name)| (name.clone(), i as u64)).collect()
+}
+
+/// ranks out of the bits are clamped, the order is kept by the name comparison in `RenderQueue::sort`
+fn rank_bits(rank: u64, bits: u32) -> u64 {
+    rank.min((1 << bits) - 1)
+}
+
+/// names compared after the ranks in keys
+fn names(item: &DrawItem) -> (&String, &String, &String) {
+    (&item.shader_name, &item.parent_name, &item.material_name)
+}
+
+/// map f32 to u64 keeping the order, the high 32 bits are used
+fn ordered_depth(depth: f32) -> u64 {
+    let depth = if depth.is_nan() { 0.0 } else { depth };
+    let bits = depth.to_bits();
+    let ordered = if bits >> 31 == 1 { !bits } else { bits | 0x8000_0000 };
+    (ordered as u64) << 32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn order(queue: &RenderQueue) -> Vec<&str> {
+        queue.items().iter().map(|x| x.object_name.as_str()).collect()
+    }
+
+    fn push_all(queue: &mut RenderQueue, items: &[(&str, &str, &str, RenderPhase, f32)]) {
+        for (object, material, mesh, phase, depth) in items.iter() {
+            queue.push(object, material, material, "shader", mesh, *phase, *depth);
+        }
+        queue.sort();
+    }
+
+    #[test]
+    fn order_does_not_depend_on_push_order() {
+        let mut items = vec![
+            ("a", "red", "cube", RenderPhase::Opaque, 3.0),
+            ("b", "red", "cube", RenderPhase::Opaque, 1.0),
+            ("c", "blue", "cube", RenderPhase::Opaque, 2.0),
+            ("d", "leaf", "plane", RenderPhase::AlphaTest, 1.0),
+            ("e", "glass", "cube", RenderPhase::Transparent, 1.0),
+            ("f", "glass", "cube", RenderPhase::Transparent, 5.0),
+            ("g", "red", "cube", RenderPhase::Opaque, 1.0),
+        ];
+
+        let mut queue = RenderQueue::new();
+        push_all(&mut queue, &items);
+        let expected: Vec<String> = order(&queue).into_iter().map(String::from).collect();
+        assert_eq!(expected, vec!["c", "b", "g", "a", "d", "f", "e"]);
+
+        for _ in 0..items.len() {
+            items.rotate_left(1);
+            let mut queue = RenderQueue::new();
+            push_all(&mut queue, &items);
+            assert_eq!(order(&queue), expected);
+        }
+
+        items.reverse();
+        queue.clear();
+        push_all(&mut queue, &items);
+        assert_eq!(order(&queue), expected);
+    }
+
+    #[test]
+    fn materials_and_phases() {
+        let mut queue = RenderQueue::new();
+        push_all(&mut queue, &[
+            ("a", "red", "cube", RenderPhase::Opaque, 3.0),
+            ("b", "blue", "cube", RenderPhase::Opaque, 1.0),
+            ("c", "leaf", "plane", RenderPhase::AlphaTest, 1.0),
+            ("d", "glass", "cube", RenderPhase::Transparent, 1.0),
+        ]);
+
+        assert_eq!(queue.materials(), vec![
+            (RenderPhase::Opaque, &"blue".to_string()),
+            (RenderPhase::Opaque, &"red".to_string()),
+            (RenderPhase::AlphaTest, &"leaf".to_string()),
+        ]);
+        assert_eq!(queue.material_items(RenderPhase::Opaque, "red").len(), 1);
+        assert!(queue.material_items(RenderPhase::Transparent, "glass").is_empty());
+        assert_eq!(queue.phase_items(RenderPhase::Transparent).len(), 1);
+    }
+
+    #[test]
+    fn clamped_ranks_keep_materials_adjacent() {
+        /* more materials than the key bits, the meshes of clamped materials would interleave them */
+        let count = (1 << MATERIAL_BITS) + 100;
+
+        let mut queue = RenderQueue::new();
+        for i in 0..count {
+            let material = format!("material{:05}", i);
+            for mesh in ["cube", "plane"].iter() {
+                queue.push(&format!("{}_{}", material, mesh), &material, &material, "shader", mesh, RenderPhase::Opaque, i as f32);
+            }
+        }
+        queue.sort();
+
+        let mut total = 0;
+        for (phase, material) in queue.materials() {
+            let items = queue.material_items(phase, material);
+            assert!(items.iter().all(|x| x.material_name == *material));
+            total += items.len();
+        }
+        assert_eq!(total, count * 2);
+
+        let materials: Vec<&String> = queue.items().iter().map(|x| &x.material_name).collect();
+        assert!(materials.windows(2).all(|x| x[0] <= x[1]));
+    }
+}
@@ -2,9 +2,14 @@ use crate::base::camera::Camera;
 use super::light::Light;
 use super::pipeline::*;
 use super::image::Image;
-use super::data::{Object,GMesh};
-use rmu::raw::Vec4f;
+use super::capture::CaptureErr;
+use super::ssao::SsaoConfig;
+use super::data::{Object,GMesh,GSkinnedMesh,Instance};
+use rmu::raw::{Vec4f,Mat4f};
 use crate::base::material::Material;
+use crate::base::material_definition::{MaterialDefinition, MaterialErr};
+use crate::base::material_instance::MaterialInstance;
+use crate::base::{Vertex, SkinnedVertex};
 
 /// Renderer for 3d scene
 pub trait Renderer: RenderProdure + RendererManager {
@@ -12,6 +17,29 @@ pub trait Renderer: RenderProdure + RendererManager {
     fn set_antialiasing(&mut self,enable: bool);
     fn set_hdr(&mut self, enable: bool);
     fn set_gamma(&mut self, gamma: f32);
+    /// draw alpha blend objects with weighted blended order independent transparency instead of sorting
+    fn set_order_independent_transparency(&mut self, enable: bool);
+    /// equirectangular hdr image drawn as skybox, it lights cook torrance materials as ambient
+    fn set_environment(&mut self, image: &Image);
+    /// screen space ambient occlusion of the ambient of blinn phong and cook torrance materials, `None` disables it
+    fn set_ssao(&mut self, config: Option<SsaoConfig>);
+    /// read back the current frame, or the last presented frame after `swap_buffer`,
+    /// as RGBA image with rows from top to bottom
+    fn read_frame(&mut self) -> Image;
+    /// read back a render target or texture by name, rows from top to bottom
+    fn read_target(&mut self, name: &str) -> Option<Image>;
+
+    /// save the current frame as png or ppm by the extension of path
+    fn capture(&mut self, path: &str) -> Result<(),CaptureErr> {
+        self.read_frame().save(path)
+    }
+
+    fn capture_target(&mut self, name: &str, path: &str) -> Result<(),CaptureErr> {
+        match self.read_target(name) {
+            Some(image) => image.save(path),
+            None => Err(CaptureErr::TargetErr(name.to_string())),
+        }
+    }
 }
 
 pub trait RenderProdure {
@@ -24,14 +52,41 @@ pub trait RenderProdure {
 pub trait RendererManager {
     fn update_camera(&mut self, camera: &Camera);
     fn update_mesh(&mut self, name: &str, mesh: &GMesh);
-    fn update_material(&mut self, name: &str, material: &Material);
+    fn remove_mesh(&mut self, name: &str);
+    /// re-upload the vertices of a mesh and keep its indices, for morph target weights changed
+    fn update_mesh_vertices(&mut self, name: &str, vertices: &[Vertex]);
+    /// mesh with joint indices and weights, shares the name space with `update_mesh`
+    fn update_skinned_mesh(&mut self, name: &str, mesh: &GSkinnedMesh);
+    /// re-upload the vertices of a skinned mesh and keep its indices, see `update_mesh_vertices`
+    fn update_skinned_mesh_vertices(&mut self, name: &str, vertices: &[SkinnedVertex]);
+    /// joint matrices of a object with skinned mesh, see `Skeleton::joint_matrices`
+    fn update_skin(&mut self, name: &str, joint_matrices: &[Mat4f]);
+    /// definition materials of its shader are validated against, it replaces the one with same shader.
+    /// bulidin materials have definitions
+    fn update_material_definition(&mut self, definition: &MaterialDefinition);
+    /// the material is not updated if it doesn't match the definition of its shader,
+    /// parameters it doesn't set take the defaults of the definition
+    fn update_material(&mut self, name: &str, material: &Material) -> Result<(), MaterialErr>;
+    /// material of the parent with the overrides of instance, it is updated with the parent.
+    /// instances share the name space of materials and are removed by `remove_material`
+    fn update_material_instance(&mut self, name: &str, instance: &MaterialInstance) -> Result<(), MaterialErr>;
+    /// instances of a removed material are not drawn until it is updated again
+    fn remove_material(&mut self, name: &str);
     fn update_object(&mut self, name: &str, object: &mut Object);
+    fn remove_object(&mut self, name: &str);
+    /// draw the object once for each instance, objects sharing mesh and material are instanced without this
+    /// an empty slice is the same as `remove_instances`
+    fn update_instances(&mut self, name: &str, instances: &[Instance]);
+    fn remove_instances(&mut self, name: &str);
     //
     fn update_light(&mut self, name: &str, light: &Light);
     fn remove_light(&mut self, name: &str);
     //
     fn update_texture(&mut self, name: &str, image: &Image);
     fn remove_texture(&mut self, name: &str);
+    /// square faces of same size in the order +x, -x, +y, -y, +z, -z
+    fn update_cubemap(&mut self, name: &str, faces: &[Image;6]);
+    fn remove_cubemap(&mut self, name: &str);
 }
 
 pub trait RenderPassRenderer<S,P> {
@@ -0,0 +1,396 @@
+use super::light::Light;
+use crate::base::camera::{Camera, camera};
+use crate::base::transform::inverse;
+use rmu::raw::{Vec3f, Vec4f, Mat4f};
+use rmu::vector::Vector3;
+use rmu::matrix::Matrix4x4;
+
+/// maximum number of cascades of a parallel light
+pub const MAX_CASCADES: usize = 4;
+
+#[derive(Debug,Copy,Clone,PartialEq)]
+pub enum ShadowFilter {
+    /// one comparison with bilinear filtering
+    Hard,
+    /// `(2 * radius + 1)^2` comparisons in a square of texels
+    Pcf { radius: u32 },
+    /// up to 16 comparisons in a rotated poisson disk, `spread` is the radius in texels
+    Poisson { samples: u32, spread: f32 },
+}
+
+/// # Shadow Config
+/// the bias is in light depth, the normal bias moves the surface along its normal in world units.
+/// cascades of parallel lights split the camera frustum up to `max_distance`,
+/// `split_lambda` blends uniform (0.0) and logarithmic (1.0) split distances
+#[derive(Debug,Copy,Clone,PartialEq)]
+pub struct ShadowConfig {
+    pub map_size: u32,
+    pub bias: f32,
+    pub normal_bias: f32,
+    pub filter: ShadowFilter,
+    pub cascade_count: u32,
+    pub split_lambda: f32,
+    pub max_distance: f32,
+}
+
+impl Default for ShadowConfig {
+    fn default() -> Self {
+        Self {
+            map_size: 1024,
+            bias: 0.002,
+            normal_bias: 0.02,
+            filter: ShadowFilter::Pcf { radius: 1 },
+            cascade_count: 4,
+            split_lambda: 0.75,
+            max_distance: 100.0,
+        }
+    }
+}
+
+/// light space matrices of the shadow maps of a light, one per cascade,
+/// with the camera distance each cascade ends at
+#[derive(Debug,Clone,PartialEq)]
+pub struct LightShadow {
+    pub matrices: Vec<Mat4f>,
+    pub splits: Vec<f32>,
+}
+
+/// six faces of the cube map of a point light, the depth is the distance to light divided by `far`
+#[derive(Debug,Clone,PartialEq)]
+pub struct PointShadow {
+    pub position: Vec3f,
+    pub far: f32,
+    /// light space matrices in the order of cube map faces +x, -x, +y, -y, +z, -z
+    pub matrices: [Mat4f;6],
+}
+
+/// shadow maps of spot lights and cascades of parallel lights, point lights use `point_shadow`
+pub fn light_shadow(light: &Light, camera: &Camera, config: &ShadowConfig) -> Option<LightShadow> {
+    match light.direction_type[3] as u32 {
+        1 => {
+            let far = if light.position_range[3] > 0.0 { light.position_range[3] } else { camera.far };
+            Some(LightShadow {
+                matrices: vec![spot_matrix(light.position(), light.direction(), light.outer_angle, far)],
+                splits: vec![far],
+            })
+        },
+        2 => {
+            let near = camera.near;
+            let far = camera.far.min(config.max_distance).max(near);
+            let count = (config.cascade_count as usize).max(1).min(MAX_CASCADES);
+            let splits = cascade_splits(near, far, count, config.split_lambda);
+
+            let mut start = near;
+            let matrices = splits
+                .iter()
+                .map(|end| {
+                    let corners = frustum_corners(camera, start, *end);
+                    start = *end;
+                    cascade_matrix(&corners, light.direction(), config.map_size, config.max_distance)
+                })
+                .collect();
+
+            Some(LightShadow { matrices, splits })
+        },
+        _ => None,
+    }
+}
+
+/// cube map shadow of a point light, the far plane is the range of light or `max_distance`
+pub fn point_shadow(light: &Light, config: &ShadowConfig) -> Option<PointShadow> {
+    if light.direction_type[3] as u32 != 0 {
+        return None;
+    }
+
+    let far = if light.position_range[3] > 0.0 { light.position_range[3] } else { config.max_distance };
+    let position = light.position();
+
+    Some(PointShadow {
+        position,
+        far,
+        matrices: cube_face_matrices(position, (far * 0.001).max(0.05), far),
+    })
+}
+
+/// 90 degree perspective views of the cube map faces, oriented the way cube maps are sampled by direction
+pub fn cube_face_matrices(position: Vec3f, near: f32, far: f32) -> [Mat4f;6] {
+    let faces = [
+        ([ 1.0, 0.0, 0.0], [0.0,-1.0, 0.0]),
+        ([-1.0, 0.0, 0.0], [0.0,-1.0, 0.0]),
+        ([ 0.0, 1.0, 0.0], [0.0, 0.0, 1.0]),
+        ([ 0.0,-1.0, 0.0], [0.0, 0.0,-1.0]),
+        ([ 0.0, 0.0, 1.0], [0.0,-1.0, 0.0]),
+        ([ 0.0, 0.0,-1.0], [0.0,-1.0, 0.0]),
+    ];
+
+    let project = crate::base::camera::perspective(std::f32::consts::FRAC_PI_2, far, near, 1.0);
+    let look_from = Vector3::from(position);
+    let mut matrices = [crate::base::transform::IDENTITY;6];
+
+    for (i, (direction, up)) in faces.iter().enumerate() {
+        let view = camera(look_from, look_from + Vector3::from(*direction), Vector3::from(*up));
+        matrices[i] = (Matrix4x4::from(project) * Matrix4x4::from(view)).into();
+    }
+
+    matrices
+}
+
+/// far distances of `count` cascades between near and far
+pub fn cascade_splits(near: f32, far: f32, count: usize, lambda: f32) -> Vec<f32> {
+    (1..=count)
+        .map(|i| {
+            let p = i as f32 / count as f32;
+            let logarithmic = near * (far / near).powf(p);
+            let uniform = near + (far - near) * p;
+            lambda * logarithmic + (1.0 - lambda) * uniform
+        })
+        .collect()
+}
+
+/// world space corners of the camera frustum between two view distances, near plane first
+pub fn frustum_corners(camera: &Camera, near: f32, far: f32) -> [Vec3f;8] {
+    let view_project: Mat4f = (Matrix4x4::from(camera.project()) * Matrix4x4::from(camera.view())).into();
+    let inverse_view_project = inverse(view_project).unwrap_or(crate::base::transform::IDENTITY);
+
+    let mut corners = [[0.0;3];8];
+    let t_near = (near - camera.near) / (camera.far - camera.near);
+    let t_far = (far - camera.near) / (camera.far - camera.near);
+
+    for (i, (x, y)) in [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)].iter().enumerate() {
+        let a = transform_point(&inverse_view_project, [*x, *y, -1.0]);
+        let b = transform_point(&inverse_view_project, [*x, *y, 1.0]);
+
+        for k in 0..3 {
+            corners[i][k] = a[k] + (b[k] - a[k]) * t_near;
+            corners[i + 4][k] = a[k] + (b[k] - a[k]) * t_far;
+        }
+    }
+
+    corners
+}
+
+/// orthogonal light space matrix of the bounding sphere of corners, the sphere keeps the size stable when
+/// the camera turns and the origin is snapped to texels so that shadow edges don't shimmer.
+/// casters up to `pullback` behind the sphere are included
+pub fn cascade_matrix(corners: &[Vec3f;8], direction: Vec3f, map_size: u32, pullback: f32) -> Mat4f {
+    let mut center = [0.0f32;3];
+    for corner in corners.iter() {
+        for k in 0..3 {
+            center[k] += corner[k] / 8.0;
+        }
+    }
+
+    let radius = corners
+        .iter()
+        .map(|x| (Vector3::from(*x) - Vector3::from(center)).length())
+        .fold(0.0f32, f32::max);
+    let radius = (radius * 16.0).ceil() / 16.0;
+
+    let direction = Vector3::from(direction).normalized();
+    let look_at = Vector3::from(center);
+    let look_from = look_at - (radius + pullback) * direction;
+
+    let view = camera(look_from, look_at, up_vector(direction));
+    let project = ortho_box(-radius, radius, -radius, radius, 0.0, 2.0 * radius + pullback);
+    let mut matrix: Mat4f = (Matrix4x4::from(project) * Matrix4x4::from(view)).into();
+
+    let origin = transform_point(&matrix, [0.0, 0.0, 0.0]);
+    let texels = map_size as f32 / 2.0;
+    matrix[3][0] += ((origin[0] * texels).round() - origin[0] * texels) / texels;
+    matrix[3][1] += ((origin[1] * texels).round() - origin[1] * texels) / texels;
+
+    matrix
+}
+
+/// perspective light space matrix of the cone of a spot light
+pub fn spot_matrix(position: Vec3f, direction: Vec3f, outer_angle: f32, far: f32) -> Mat4f {
+    let direction = Vector3::from(direction).normalized();
+    let look_from = Vector3::from(position);
+    let view = camera(look_from, look_from + direction, up_vector(direction));
+
+    let fov = (outer_angle * 2.0).max(0.01).min(std::f32::consts::PI - 0.01);
+    let near = (far * 0.001).max(0.05);
+    let project = crate::base::camera::perspective(fov, far, near, 1.0);
+
+    (Matrix4x4::from(project) * Matrix4x4::from(view)).into()
+}
+
+/// orthogonal projection of a box in view space, the view looks to -z
+pub fn ortho_box(left: f32, right: f32, bottom: f32, top: f32, near: f32, far: f32) -> Mat4f {
+    [[2.0 / (right - left), 0.0, 0.0, 0.0]
+    ,[0.0, 2.0 / (top - bottom), 0.0, 0.0]
+    ,[0.0, 0.0, -2.0 / (far - near), 0.0]
+    ,[-(right + left) / (right - left), -(top + bottom) / (top - bottom), -(far + near) / (far - near), 1.0]]
+}
+
+/* any up vector not parallel to the direction */
+fn up_vector(direction: Vector3) -> Vector3 {
+    if direction[2].abs() < 0.99 {
+        Vector3::new(0.0, 0.0, 1.0)
+    } else {
+        Vector3::new(0.0, 1.0, 0.0)
+    }
+}
+
+fn transform_point(m: &Mat4f, p: Vec3f) -> Vec3f {
+    let mut v = [0.0f32;4];
+    for row in 0..4 {
+        v[row] = m[0][row] * p[0] + m[1][row] * p[1] + m[2][row] * p[2] + m[3][row];
+    }
+    [v[0] / v[3], v[1] / v[3], v[2] / v[3]]
+}
+
+/// shadow data of a light packed for GPU, in the order of lights in the light buffer
+#[derive(Debug,Copy,Clone,PartialEq)]
+pub struct ShadowData {
+    pub light_space_0: Mat4f,
+    pub light_space_1: Mat4f,
+    pub light_space_2: Mat4f,
+    pub light_space_3: Mat4f,
+    /// first layer in the shadow map array (-1.0 without shadow), number of cascades, bias, normal bias
+    pub layer_count_bias: Vec4f,
+    /// layer in the cube map array of point lights (-1.0 without shadow), far plane of faces
+    pub cube_far: Vec4f,
+}
+
+impl ShadowData {
+    /// light without shadow
+    pub fn none() -> Self {
+        let identity = crate::base::transform::IDENTITY;
+        Self {
+            light_space_0: identity,
+            light_space_1: identity,
+            light_space_2: identity,
+            light_space_3: identity,
+            layer_count_bias: [-1.0, 0.0, 0.0, 0.0],
+            cube_far: [-1.0, 0.0, 0.0, 0.0],
+        }
+    }
+
+    pub fn point(shadow: &PointShadow, cube: u32, config: &ShadowConfig) -> Self {
+        let mut data = Self::none();
+        data.layer_count_bias = [-1.0, 0.0, config.bias, config.normal_bias];
+        data.cube_far = [cube as f32, shadow.far, 0.0, 0.0];
+        data
+    }
+
+    pub fn new(shadow: &LightShadow, layer: u32, config: &ShadowConfig) -> Self {
+        let mut data = Self::none();
+        let count = shadow.matrices.len().min(MAX_CASCADES);
+
+        for (i, matrix) in shadow.matrices.iter().take(count).enumerate() {
+            match i {
+                0 => data.light_space_0 = *matrix,
+                1 => data.light_space_1 = *matrix,
+                2 => data.light_space_2 = *matrix,
+                _ => data.light_space_3 = *matrix,
+            }
+        }
+
+        data.layer_count_bias = [layer as f32, count as f32, config.bias, config.normal_bias];
+        data
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: &[f32], b: &[f32]) -> bool {
+        a.len() == b.len() && a.iter().zip(b.iter()).all(|(x, y)| (x - y).abs() < 1e-4)
+    }
+
+    fn inside(matrix: &Mat4f, point: Vec3f) -> bool {
+        transform_point(matrix, point).iter().all(|x| *x >= -1.001 && *x <= 1.001)
+    }
+
+    #[test]
+    fn splits() {
+        assert!(close(&cascade_splits(1.0, 5.0, 4, 0.0), &[2.0, 3.0, 4.0, 5.0]));
+        assert!(close(&cascade_splits(1.0, 16.0, 4, 1.0), &[2.0, 4.0, 8.0, 16.0]));
+        assert!(close(&cascade_splits(1.0, 16.0, 2, 0.5), &[(4.0 + 8.5) / 2.0, 16.0]));
+
+        let splits = cascade_splits(0.1, 100.0, 4, 0.75);
+        assert!(splits.windows(2).all(|x| x[0] < x[1]));
+        assert!((splits[3] - 100.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn corners() {
+        /* the camera is at (10,0,0) and looks to -x */
+        let camera = Camera::default();
+        let corners = frustum_corners(&camera, 1.0, 10.0);
+
+        for corner in corners[..4].iter() {
+            assert!((corner[0] - 9.0).abs() < 1e-2, "{:?}", corner);
+        }
+        for corner in corners[4..].iter() {
+            assert!(corner[0].abs() < 1e-2, "{:?}", corner);
+        }
+
+        /* the far plane is wider by the ratio of distances */
+        let near_width = (corners[1][1] - corners[0][1]).abs();
+        let far_width = (corners[5][1] - corners[4][1]).abs();
+        assert!((far_width / near_width - 10.0).abs() < 1e-2);
+    }
+
+    #[test]
+    fn cascades_cover_their_slice() {
+        let camera = Camera::default();
+        let config = ShadowConfig::default();
+        let light = Light::parallel_light([1.0;3], [0.0;3], [0.0, 0.3, -1.0]);
+        let shadow = light_shadow(&light, &camera, &config).unwrap();
+
+        assert_eq!(shadow.matrices.len(), config.cascade_count as usize);
+        assert!(close(&shadow.splits, &cascade_splits(camera.near, config.max_distance, 4, config.split_lambda)));
+
+        let mut start = camera.near;
+        for (matrix, end) in shadow.matrices.iter().zip(shadow.splits.iter()) {
+            for corner in frustum_corners(&camera, start, *end).iter() {
+                assert!(inside(matrix, *corner), "{:?}", transform_point(matrix, *corner));
+            }
+            start = *end;
+        }
+
+        /* a caster between the receiver and the light is nearer */
+        let matrix = &shadow.matrices[0];
+        let receiver = transform_point(matrix, [9.0, 0.0, 0.0]);
+        let caster = transform_point(matrix, [9.0, -0.3, 1.0]);
+        assert!(caster[2] < receiver[2]);
+    }
+
+    #[test]
+    fn cascade_matrix_snaps_to_texels() {
+        let corners = frustum_corners(&Camera::default(), 1.0, 10.0);
+        let matrix = cascade_matrix(&corners, [0.0, 0.3, -1.0], 1024, 100.0);
+
+        let origin = transform_point(&matrix, [0.0, 0.0, 0.0]);
+        for k in 0..2 {
+            let texels = origin[k] * 512.0;
+            assert!((texels - texels.round()).abs() < 1e-2, "{:?}", origin);
+        }
+    }
+
+    #[test]
+    fn spot_and_point() {
+        let camera = Camera::default();
+        let config = ShadowConfig::default();
+
+        let spot = Light::spot_light([1.0;3], [0.0, 0.0, 10.0], [0.0, 0.0, -1.0], 0.3, 0.1);
+        let shadow = light_shadow(&spot, &camera, &config).unwrap();
+        let center = transform_point(&shadow.matrices[0], [0.0, 0.0, 0.0]);
+        assert!(close(&center[..2], &[0.0, 0.0]) && center[2] > -1.0 && center[2] < 1.0, "{:?}", center);
+
+        let point = Light::point_light([1.0;3], [1.0, 2.0, 3.0]);
+        assert!(light_shadow(&point, &camera, &config).is_none());
+        assert!(point_shadow(&spot, &config).is_none());
+
+        /* a point along the direction of each face is in the center of the face */
+        let shadow = point_shadow(&point, &config).unwrap();
+        let directions = [[1.0, 0.0, 0.0], [-1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, -1.0, 0.0], [0.0, 0.0, 1.0], [0.0, 0.0, -1.0]];
+        for (matrix, d) in shadow.matrices.iter().zip(directions.iter()) {
+            let q = transform_point(matrix, [1.0 + 5.0 * d[0], 2.0 + 5.0 * d[1], 3.0 + 5.0 * d[2]]);
+            assert!(close(&q[..2], &[0.0, 0.0]) && q[2] > -1.0 && q[2] < 1.0, "{:?}", q);
+        }
+    }
+}
@@ -0,0 +1,107 @@
+use rmu::raw::Vec3f;
+
+/// maximum number of samples of the ssao kernel
+pub const SSAO_MAX_SAMPLES: usize = 64;
+
+/// # Ssao Config
+/// screen space ambient occlusion, `radius` is the radius of the sample hemisphere in world units,
+/// occlusion is scaled by `intensity` and `bias` avoids self occlusion of flat surfaces.
+/// the occlusion is blurred by `blur_radius` texels weighted by depth to keep edges
+#[derive(Debug,Copy,Clone,PartialEq)]
+pub struct SsaoConfig {
+    pub radius: f32,
+    pub intensity: f32,
+    pub samples: u32,
+    pub bias: f32,
+    pub blur_radius: u32,
+}
+
+impl Default for SsaoConfig {
+    fn default() -> Self {
+        Self {
+            radius: 0.5,
+            intensity: 1.0,
+            samples: 32,
+            bias: 0.025,
+            blur_radius: 2,
+        }
+    }
+}
+
+/// points in the hemisphere around +z, more of them close to the center
+pub fn ssao_kernel(samples: usize) -> Vec<Vec3f> {
+    let samples = samples.max(1).min(SSAO_MAX_SAMPLES);
+    let mut random = Random::new(0x9e37_79b9);
+
+    (0..samples)
+        .map(|i| {
+            let x = random.next() * 2.0 - 1.0;
+            let y = random.next() * 2.0 - 1.0;
+            let z = random.next();
+            let length = (x * x + y * y + z * z).sqrt().max(0.0001);
+
+            let t = i as f32 / samples as f32;
+            let scale = (0.1 + 0.9 * t * t) * random.next();
+            [x / length * scale, y / length * scale, z / length * scale]
+        })
+        .collect()
+}
+
+/// random rotations around the normal in the xy plane, tiled over the screen
+pub fn ssao_noise(size: usize) -> Vec<Vec3f> {
+    let mut random = Random::new(0x85eb_ca6b);
+
+    (0..size * size)
+        .map(|_| [random.next() * 2.0 - 1.0, random.next() * 2.0 - 1.0, 0.0])
+        .collect()
+}
+
+/* xorshift, the kernel and noise are the same every run */
+struct Random(u32);
+
+impl Random {
+    fn new(seed: u32) -> Self {
+        Random(seed.max(1))
+    }
+
+    fn next(&mut self) -> f32 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 17;
+        self.0 ^= self.0 << 5;
+        (self.0 >> 8) as f32 / (1u32 << 24) as f32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn kernel_in_hemisphere() {
+        let kernel = ssao_kernel(SSAO_MAX_SAMPLES);
+
+        assert_eq!(kernel.len(), SSAO_MAX_SAMPLES);
+
+        for [x, y, z] in kernel {
+            assert!(z >= 0.0);
+            assert!((x * x + y * y + z * z).sqrt() <= 1.0);
+        }
+    }
+
+    #[test]
+    fn kernel_samples_clamped() {
+        assert_eq!(ssao_kernel(0).len(), 1);
+        assert_eq!(ssao_kernel(16).len(), 16);
+        assert_eq!(ssao_kernel(SSAO_MAX_SAMPLES + 1).len(), SSAO_MAX_SAMPLES);
+    }
+
+    #[test]
+    fn deterministic() {
+        assert_eq!(ssao_kernel(32), ssao_kernel(32));
+        assert_eq!(ssao_noise(4), ssao_noise(4));
+
+        let noise = ssao_noise(4);
+        assert_eq!(noise.len(), 16);
+        assert!(noise.iter().all(|[x, y, z]| x.abs() <= 1.0 && y.abs() <= 1.0 && *z == 0.0));
+    }
+}
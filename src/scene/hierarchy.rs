@@ -0,0 +1,281 @@
+use super::object::*;
+use super::scene::{Scene, SceneErr};
+use crate::base::transform::{inverse, Transform, IDENTITY};
+use rmu::matrix::Matrix4x4;
+use rmu::raw::Mat4f;
+use std::collections::VecDeque;
+
+/// # Hierarchy
+/// world matrix of a object is `parent world * parent inverse * local transform`,
+/// the cached world matrix is recomputed when the object or one of its ancestors is dirty
+impl Scene {
+    pub fn set_transform(&mut self, name: &str, transform: Transform) -> Result<(),SceneErr> {
+        match self.data.get_mut(name) {
+            Some(object) => {
+                object.set_transform(transform);
+                Ok(())
+            },
+            None => Err(SceneErr::NoExitObject),
+        }
+    }
+
+    /// mark a object dirty after changing its transform through `data` directly
+    pub fn mark_dirty(&mut self, name: &str) {
+        if let Some(object) = self.data.get_mut(name) {
+            object.dirty = true;
+        }
+    }
+
+    /// recompute all dirty world matrices, descendants of a dirty object are recomputed too
+    pub fn update_world_transforms(&mut self) {
+        for root in self.objects.clone() {
+            self.update_subtree(&root, IDENTITY, false);
+        }
+    }
+
+    /// world matrix of a object, only the dirty part of its ancestor chain is recomputed
+    pub fn world_transform(&mut self, name: &str) -> Option<Mat4f> {
+        let chain = self.ancestor_chain(name)?;
+
+        if let Some(index) = chain.iter().position(|x| self.data[x].dirty) {
+            let parent_world = if index == 0 {
+                IDENTITY
+            } else {
+                self.data[&chain[index - 1]].world_transform
+            };
+            self.update_subtree(&chain[index], parent_world, true);
+        }
+
+        self.data.get(name).map(|object| object.world_transform)
+    }
+
+    /// move a object under a new parent, `None` make it a root object.
+    /// if `keep_world` is true the world transform of the object is unchanged,
+    /// otherwise its local transform is kept.
+    pub fn reparent(&mut self, name: &str, parent: Option<&str>, keep_world: bool) -> Result<(),SceneErr> {
+        if !self.data.contains_key(name) {
+            return Err(SceneErr::NoExitObject);
+        }
+
+        if let Some(parent) = parent {
+            match self.data.get(parent) {
+                Some(parent_object) => match &parent_object.sub_objects {
+                    SubObject::Atomic(PrimitiveObject::Data(_)) => return Err(SceneErr::Atomic),
+                    _ => (),
+                },
+                None => return Err(SceneErr::NoExitObject),
+            }
+
+            if self.is_ancestor(name, parent) {
+                return Err(SceneErr::Cycle);
+            }
+        }
+
+        let world = if keep_world {
+            self.world_transform(name)
+        } else {
+            None
+        };
+
+        self.detach(name);
+        self.attach(name, parent);
+
+        let parent_inverse = match world {
+            Some(world) => {
+                let parent_world = match parent {
+                    Some(parent) => self.world_transform(parent).unwrap_or(IDENTITY),
+                    None => IDENTITY,
+                };
+                let object = &self.data[name];
+                match (inverse(parent_world), inverse(object.transform.transform().into())) {
+                    (Some(parent_world_inverse), Some(local_inverse)) =>
+                        (Matrix4x4::from(parent_world_inverse) * Matrix4x4::from(world) * Matrix4x4::from(local_inverse)).into(),
+                    _ => IDENTITY,
+                }
+            },
+            None => IDENTITY,
+        };
+
+        let object = self.data.get_mut(name).unwrap();
+        object.parent_inverse = parent_inverse;
+        object.dirty = true;
+
+        Ok(())
+    }
+
+    /// true if `ancestor` is `name` or one of the parents of `name`
+    pub fn is_ancestor(&self, ancestor: &str, name: &str) -> bool {
+        let mut current = Some(name.to_string());
+
+        while let Some(node) = current {
+            if node == ancestor {
+                return true;
+            }
+            current = self.data.get(&node).and_then(|object| object.parent.clone());
+        }
+
+        false
+    }
+
+    /// depth first traversal over all root objects
+    pub fn depth_first(&self) -> DepthFirst<'_> {
+        DepthFirst {
+            scene: self,
+            stack: self.objects.iter().rev().cloned().collect(),
+        }
+    }
+
+    /// depth first traversal of a object and its descendants
+    pub fn depth_first_from(&self, name: &str) -> DepthFirst<'_> {
+        DepthFirst {
+            scene: self,
+            stack: if self.data.contains_key(name) { vec![name.to_string()] } else { Vec::new() },
+        }
+    }
+
+    /// breadth first traversal over all root objects
+    pub fn breadth_first(&self) -> BreadthFirst<'_> {
+        BreadthFirst {
+            scene: self,
+            queue: self.objects.iter().cloned().collect(),
+        }
+    }
+
+    /// breadth first traversal of a object and its descendants
+    pub fn breadth_first_from(&self, name: &str) -> BreadthFirst<'_> {
+        let mut queue = VecDeque::new();
+        if self.data.contains_key(name) {
+            queue.push_back(name.to_string());
+        }
+
+        BreadthFirst {
+            scene: self,
+            queue,
+        }
+    }
+
+    fn update_subtree(&mut self, name: &str, parent_world: Mat4f, parent_changed: bool) {
+        let (world, node_names) = match self.data.get_mut(name) {
+            Some(object) => {
+                if object.dirty || parent_changed {
+                    object.world_transform = (Matrix4x4::from(parent_world) * object.local_transform()).into();
+                    object.dirty = false;
+                    (Some(object.world_transform), object.node_names().to_vec())
+                } else {
+                    (None, object.node_names().to_vec())
+                }
+            },
+            None => return,
+        };
+
+        let changed = world.is_some();
+        let world = world.unwrap_or(self.data[name].world_transform);
+
+        for node_name in node_names {
+            self.update_subtree(&node_name, world, changed);
+        }
+    }
+
+    /// names from the root object down to `name`
+    fn ancestor_chain(&self, name: &str) -> Option<Vec<String>> {
+        let mut chain = Vec::new();
+        let mut current = Some(name.to_string());
+
+        while let Some(node) = current {
+            current = self.data.get(&node)?.parent.clone();
+            chain.push(node);
+        }
+
+        chain.reverse();
+        Some(chain)
+    }
+
+    /// remove a object from its parent's node names or from the root objects
+    pub(crate) fn detach(&mut self, name: &str) {
+        let parent = self.data.get(name).and_then(|object| object.parent.clone());
+
+        match parent {
+            Some(parent) => {
+                if let Some(parent_object) = self.data.get_mut(&parent) {
+                    let empty = match &mut parent_object.sub_objects {
+                        SubObject::Discreteness { node_names } => {
+                            node_names.retain(|x| x != name);
+                            node_names.is_empty()
+                        },
+                        SubObject::Atomic(_) => false,
+                    };
+
+                    if empty {
+                        parent_object.sub_objects = SubObject::Atomic(PrimitiveObject::Empty);
+                    }
+                }
+            },
+            None => self.objects.retain(|x| x != name),
+        }
+
+        if let Some(object) = self.data.get_mut(name) {
+            object.parent = None;
+        }
+    }
+
+    /// add a detached object to the node names of `parent` or to the root objects
+    pub(crate) fn attach(&mut self, name: &str, parent: Option<&str>) {
+        match parent {
+            Some(parent) => {
+                if let Some(parent_object) = self.data.get_mut(parent) {
+                    match &mut parent_object.sub_objects {
+                        SubObject::Discreteness { node_names } => node_names.push(name.to_string()),
+                        SubObject::Atomic(_) => {
+                            parent_object.sub_objects = SubObject::Discreteness { node_names: vec![name.to_string()] };
+                        },
+                    }
+                }
+            },
+            None => self.objects.push(name.to_string()),
+        }
+
+        if let Some(object) = self.data.get_mut(name) {
+            object.parent = parent.map(|x| x.to_string());
+        }
+    }
+}
+
+/// iterator of objects in depth first order
+pub struct DepthFirst<'a> {
+    scene: &'a Scene,
+    stack: Vec<String>,
+}
+
+impl<'a> Iterator for DepthFirst<'a> {
+    type Item = &'a Object;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(name) = self.stack.pop() {
+            if let Some(object) = self.scene.data.get(&name) {
+                self.stack.extend(object.node_names().iter().rev().cloned());
+                return Some(object);
+            }
+        }
+        None
+    }
+}
+
+/// iterator of objects in breadth first order
+pub struct BreadthFirst<'a> {
+    scene: &'a Scene,
+    queue: VecDeque<String>,
+}
+
+impl<'a> Iterator for BreadthFirst<'a> {
+    type Item = &'a Object;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(name) = self.queue.pop_front() {
+            if let Some(object) = self.scene.data.get(&name) {
+                self.queue.extend(object.node_names().iter().cloned());
+                return Some(object);
+            }
+        }
+        None
+    }
+}
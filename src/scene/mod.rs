@@ -2,6 +2,12 @@
 pub mod object;
 
 mod scene;
-pub use scene::Scene;
+mod hierarchy;
+mod scene_file;
+mod sync;
+pub use scene::{Scene, SceneErr};
+pub use hierarchy::*;
+pub use scene_file::*;
+pub use sync::SceneSync;
 
 
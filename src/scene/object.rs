@@ -1,5 +1,7 @@
-use crate::base::transform::Transform;
+use crate::base::transform::{Transform, IDENTITY};
 use crate::base::mesh::Mesh;
+use rmu::raw::Mat4f;
+use rmu::matrix::Matrix4x4;
 
 #[derive(Clone)]
 pub enum PrimitiveObject {
@@ -18,6 +20,21 @@ pub struct Object {
     pub name: String,
     pub parent: Option<String>,
     pub transform: Transform,
+    /// applied between the parent world matrix and the local transform,
+    /// keeps the world transform when the object is reparented
+    pub parent_inverse: Mat4f,
+    /// cached world matrix, valid when `dirty` is false
+    pub world_transform: Mat4f,
+    pub dirty: bool,
+    pub tags: Vec<String>,
+    /// material name of the mesh data
+    pub material: Option<String>,
+    /// name of the mesh data in the `AssetManager`, `None` if the mesh is not an asset yet
+    pub mesh_asset: Option<String>,
+    /// changed by `mesh_mut`, `SceneSync` uploads the mesh again when its version changed
+    pub mesh_version: u64,
+    /// set by `Scene::insert_object`, tells apart a object from a removed object of the same name
+    pub id: u64,
     pub sub_objects: SubObject,
 }
 
@@ -28,6 +45,14 @@ impl Object {
             name,
             parent: None,
             transform: Transform::new(),
+            parent_inverse: IDENTITY,
+            world_transform: IDENTITY,
+            dirty: true,
+            tags: Vec::new(),
+            material: None,
+            mesh_asset: None,
+            mesh_version: 0,
+            id: 0,
             sub_objects: SubObject::Atomic(PrimitiveObject::Empty),
         }
 
@@ -38,22 +63,105 @@ impl Object {
             name,
             parent: None,
             transform: Transform::new(),
+            parent_inverse: IDENTITY,
+            world_transform: IDENTITY,
+            dirty: true,
+            tags: Vec::new(),
+            material: None,
+            mesh_asset: None,
+            mesh_version: 0,
+            id: 0,
             sub_objects: SubObject::Atomic(primitive_object),
         }
     }
 
+    /// local matrix relative to the parent, including the parent inverse
+    pub fn local_transform(&self) -> Matrix4x4 {
+        Matrix4x4::from(self.parent_inverse) * self.transform.transform()
+    }
+
+    pub fn set_transform(&mut self, transform: Transform) {
+        self.transform = transform;
+        self.dirty = true;
+    }
+
+    pub fn with_parent(mut self, parent: &str) -> Self {
+        self.parent = Some(parent.to_string());
+        self
+    }
+
+    pub fn with_material(mut self, material: &str) -> Self {
+        self.material = Some(material.to_string());
+        self
+    }
+
+    pub fn with_mesh_asset(mut self, mesh_asset: &str) -> Self {
+        self.mesh_asset = Some(mesh_asset.to_string());
+        self
+    }
+
+    /// mesh data to edit, the mesh is no longer the asset. morph weights can be set without it
+    pub fn mesh_mut(&mut self) -> Option<&mut Mesh> {
+        match &mut self.sub_objects {
+            SubObject::Atomic(PrimitiveObject::Data(mesh)) => {
+                self.mesh_asset = None;
+                self.mesh_version += 1;
+                Some(mesh)
+            },
+            _ => None,
+        }
+    }
+
+    pub fn with_tag(mut self, tag: &str) -> Self {
+        self.add_tag(tag);
+        self
+    }
+
+    pub fn add_tag(&mut self, tag: &str) {
+        if !self.has_tag(tag) {
+            self.tags.push(tag.to_string());
+        }
+    }
+
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|x| x == tag)
+    }
+
+    /// node names of sub objects, empty for an atomic object
+    pub fn node_names(&self) -> &[String] {
+        match &self.sub_objects {
+            SubObject::Atomic(_) => &[],
+            SubObject::Discreteness { node_names } => node_names.as_slice(),
+        }
+    }
 }
 
+use serde_derive::{Serialize,Deserialize};
+
 use crate::base::light::Light;
-#[derive(Clone)]
+#[derive(Clone,Serialize,Deserialize)]
 pub struct LightObject {
     pub name: String,
     pub light: Light,
 }
 
 use crate::base::camera::Camera;
-#[derive(Clone)]
+#[derive(Clone,Serialize,Deserialize)]
 pub struct CameraObject {
     pub name: String,
     pub camera: Camera,
+}
+
+use crate::base::material::Material;
+#[derive(Clone,Serialize,Deserialize)]
+pub struct MaterialObject {
+    pub name: String,
+    pub material: Material,
+}
+
+use crate::base::material_instance::MaterialInstance;
+#[derive(Clone,Serialize,Deserialize)]
+pub struct MaterialInstanceObject {
+    pub name: String,
+    pub instance: MaterialInstance,
 }
\ No newline at end of file
@@ -1,5 +1,8 @@
 use super::object::*;
 use crate::base::camera::Camera;
+use crate::base::light::Light;
+use crate::base::material::Material;
+use crate::base::material_instance::MaterialInstance;
 use std::collections::HashMap;
 //
 #[derive(Clone)]
@@ -9,16 +12,37 @@ pub struct Scene {
     pub meshes: Vec<String>,
     pub lights: Vec<LightObject>,
     pub cameras: Vec<CameraObject>,
+    pub materials: Vec<MaterialObject>,
+    pub material_instances: Vec<MaterialInstanceObject>,
     pub data: HashMap<String,Object>,
+    /// id of the next inserted object
+    next_id: u64,
 }
 
-#[derive(Clone,Copy)]
+#[derive(Debug,Clone,Copy,PartialEq)]
 pub enum SceneErr {
     ExistObject,
     NoExitObject,
     Atomic,
+    /// the new parent is the object itself or one of its descendants
+    Cycle,
 }
 
+use std::fmt;
+
+impl fmt::Display for SceneErr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SceneErr::ExistObject => write!(f, "a object with the same name already exists"),
+            SceneErr::NoExitObject => write!(f, "object does not exist"),
+            SceneErr::Atomic => write!(f, "can't add sub objects to a object with mesh data"),
+            SceneErr::Cycle => write!(f, "object can't be a descendant of itself"),
+        }
+    }
+}
+
+impl std::error::Error for SceneErr {}
+
 impl Scene {
     pub fn new(name: String) -> Self {
         Scene {
@@ -27,56 +51,167 @@ impl Scene {
             meshes: Vec::new(),
             lights: Vec::new(),
             cameras: Vec::new(),
+            materials: Vec::new(),
+            material_instances: Vec::new(),
             data: HashMap::new(),
+            next_id: 0,
         }
     }
 
+    /// insert a object under its `parent`, or as a root object if it has no parent.
+    /// the node names of a discreteness object must be existing objects, they are moved under it
     pub fn insert_object(&mut self, object: Object) -> Result<(),SceneErr> {
         if self.data.contains_key(&object.name) {
             //exit a same name object
-            Err(SceneErr::ExistObject)
+            return Err(SceneErr::ExistObject);
+        }
+
+        if let Some(parent) = &object.parent {
+            match self.data.get(parent) {
+                //can‘t insert to parent which is  not a empty atomic object
+                Some(Object { sub_objects: SubObject::Atomic(PrimitiveObject::Data(_)), .. }) => return Err(SceneErr::Atomic),
+                Some(_) => (),
+                None => return Err(SceneErr::NoExitObject),
+            }
+        }
+
+        /* every node is checked before the scene is changed, so moving them can't fail */
+        let node_names = object.node_names();
+        for (i, node_name) in node_names.iter().enumerate() {
+            if !self.data.contains_key(node_name) {
+                return Err(SceneErr::NoExitObject);
+            }
+
+            if node_names[..i].contains(node_name) {
+                return Err(SceneErr::ExistObject);
+            }
+
+            if let Some(parent) = &object.parent {
+                if self.is_ancestor(node_name, parent) {
+                    return Err(SceneErr::Cycle);
+                }
+            }
+        }
+
+        let name = object.name.clone();
+        let parent = object.parent.clone();
+        let node_names = object.node_names().to_vec();
+
+        let mut object = object;
+        object.parent = None;
+        object.dirty = true;
+        object.id = self.next_id;
+        self.next_id += 1;
+        if let SubObject::Discreteness { node_names } = &mut object.sub_objects {
+            node_names.clear();
+        }
+
+        if let SubObject::Atomic(PrimitiveObject::Data(_)) = &object.sub_objects {
+            self.meshes.push(name.clone());
+        }
+
+        self.data.insert(name.clone(), object);
+        self.attach(&name, parent.as_deref());
+
+        for node_name in node_names {
+            self.reparent(&node_name, Some(&name), false)?;
+        }
+
+        Ok(())
+    }
+
+    /// remove a object, its descendants are removed too if `recursive` is true,
+    /// otherwise its sub objects are reattached to its parent keeping their world transform
+    pub fn remove_object(&mut self, name: &str, recursive: bool) -> Result<Object,SceneErr> {
+        let parent = match self.data.get(name) {
+            Some(object) => object.parent.clone(),
+            None => return Err(SceneErr::NoExitObject),
+        };
+
+        let node_names = self.data[name].node_names().to_vec();
+
+        if recursive {
+            let descendants: Vec<String> = self.depth_first_from(name)
+                .skip(1)
+                .map(|object| object.name.clone())
+                .collect();
+
+            for descendant in descendants {
+                self.data.remove(&descendant);
+                self.meshes.retain(|x| *x != descendant);
+            }
         } else {
-            match &object.sub_objects {
-                SubObject::Atomic(primitive_object) => {
-                    if let Some(parent) = object.parent.clone() {
-                        if let Some(parent_object) = self.data.get_mut(&parent) {
-                            match &mut parent_object.sub_objects {
-                                SubObject::Atomic(prim) => match prim {
-                                    PrimitiveObject::Empty => {
-                                        parent_object.sub_objects = SubObject::Discreteness{ node_names: vec![parent.clone()]};
-                                        self.data.insert(object.name.clone(), object);
-                                        Ok(())
-                                    },
-                                    //can‘t insert to parent which is  not a empty atomic object
-                                    _ => Err(SceneErr::Atomic),
-                                }
-                                SubObject::Discreteness {node_names} => {
-                                    match primitive_object {
-                                        PrimitiveObject::Empty => (),
-                                        PrimitiveObject::Data(_) => self.meshes.push(object.name.clone()),
-                                    }
-                                    node_names.push(object.name.clone());
-                                    self.data.insert(object.name.clone(), object);
-                                    Ok(())
-                                },
-                            }
-                        } else {
-                            Err(SceneErr::NoExitObject)
-                        }
-                    } else {
-                        match primitive_object {
-                            PrimitiveObject::Empty => (),
-                            PrimitiveObject::Data(_) => self.meshes.push(object.name.clone()),
-                        }
-                        self.objects.push(object.name.clone());
-                        self.data.insert(object.name.clone(), object);
-                        Ok(())
-                    }
-                },
-                //can't insert  a discreteness object
-                _ => Err(SceneErr::Atomic)
+            for node_name in node_names.iter() {
+                self.reparent(node_name, parent.as_deref(), true)?;
+            }
+        }
+
+        self.detach(name);
+        self.meshes.retain(|x| x != name);
+
+        let mut object = self.data.remove(name).unwrap();
+        object.parent = parent;
+        Ok(object)
+    }
+
+    pub fn rename_object(&mut self, name: &str, new_name: &str) -> Result<(),SceneErr> {
+        if name == new_name {
+            return if self.data.contains_key(name) { Ok(()) } else { Err(SceneErr::NoExitObject) };
+        }
+
+        if self.data.contains_key(new_name) {
+            return Err(SceneErr::ExistObject);
+        }
+
+        let mut object = match self.data.remove(name) {
+            Some(object) => object,
+            None => return Err(SceneErr::NoExitObject),
+        };
+
+        object.name = new_name.to_string();
+
+        let rename = |x: &mut String| if x == name { *x = new_name.to_string() };
+
+        match &object.parent {
+            Some(parent) => if let Some(parent_object) = self.data.get_mut(parent) {
+                if let SubObject::Discreteness { node_names } = &mut parent_object.sub_objects {
+                    node_names.iter_mut().for_each(rename);
+                }
+            },
+            None => self.objects.iter_mut().for_each(rename),
+        }
+
+        for node_name in object.node_names() {
+            if let Some(sub_object) = self.data.get_mut(node_name) {
+                sub_object.parent = Some(new_name.to_string());
             }
         }
+
+        self.meshes.iter_mut().for_each(rename);
+        self.data.insert(new_name.to_string(), object);
+
+        Ok(())
+    }
+
+    pub fn get_object(&self, name: &str) -> Option<&Object> {
+        self.data.get(name)
+    }
+
+    pub fn children_of(&self, name: &str) -> Result<Vec<&Object>,SceneErr> {
+        match self.data.get(name) {
+            Some(object) => Ok(object.node_names().iter().filter_map(|x| self.data.get(x)).collect()),
+            None => Err(SceneErr::NoExitObject),
+        }
+    }
+
+    /// objects whose name start with `prefix`, in depth first order
+    pub fn find_by_prefix(&self, prefix: &str) -> Vec<&Object> {
+        self.depth_first().filter(|object| object.name.starts_with(prefix)).collect()
+    }
+
+    /// objects with the tag, in depth first order
+    pub fn find_by_tag(&self, tag: &str) -> Vec<&Object> {
+        self.depth_first().filter(|object| object.has_tag(tag)).collect()
     }
 
     pub fn get_camera(&self, name: String) -> Option<Camera> {
@@ -95,4 +230,201 @@ impl Scene {
     pub fn add_camera(&mut self,name: String, camera: Camera) {
         self.cameras.push(CameraObject { name, camera});
     }
-}
\ No newline at end of file
+
+    pub fn add_light(&mut self, name: String, light: Light) {
+        self.lights.push(LightObject { name, light });
+    }
+
+    pub fn remove_light(&mut self, name: &str) {
+        self.lights.retain(|x| x.name != name);
+    }
+
+    pub fn add_material(&mut self, name: String, material: Material) {
+        self.materials.push(MaterialObject { name, material });
+    }
+
+    pub fn remove_material(&mut self, name: &str) {
+        self.materials.retain(|x| x.name != name);
+    }
+
+    pub fn add_material_instance(&mut self, name: String, instance: MaterialInstance) {
+        self.material_instances.push(MaterialInstanceObject { name, instance });
+    }
+
+    pub fn remove_material_instance(&mut self, name: &str) {
+        self.material_instances.retain(|x| x.name != name);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::base::transform::Transform;
+    use rmu::raw::Mat4f;
+
+    /* root > a > b > c > d, and a sibling e under a */
+    fn deep_scene() -> Scene {
+        let mut scene = Scene::new("test".to_string());
+        scene.insert_object(Object::new("root".to_string()).with_tag("level")).unwrap();
+        scene.insert_object(Object::new("a".to_string()).with_parent("root").with_tag("level")).unwrap();
+        scene.insert_object(Object::new("b".to_string()).with_parent("a").with_tag("level")).unwrap();
+        scene.insert_object(Object::new("c".to_string()).with_parent("b")).unwrap();
+        scene.insert_object(Object::new("d".to_string()).with_parent("c").with_tag("leaf")).unwrap();
+        scene.insert_object(Object::new("a_e".to_string()).with_parent("a").with_tag("leaf")).unwrap();
+        scene
+    }
+
+    fn names(objects: Vec<&Object>) -> Vec<&str> {
+        objects.into_iter().map(|x| x.name.as_str()).collect()
+    }
+
+    fn depth_first(scene: &Scene) -> Vec<&str> {
+        scene.depth_first().map(|x| x.name.as_str()).collect()
+    }
+
+    fn close(a: Mat4f, b: Mat4f) -> bool {
+        a.iter().flatten().zip(b.iter().flatten()).all(|(x, y)| (x - y).abs() < 1e-4)
+    }
+
+    #[test]
+    fn insert() {
+        let scene = deep_scene();
+
+        assert_eq!(scene.objects, vec!["root".to_string()]);
+        assert_eq!(depth_first(&scene), vec!["root", "a", "b", "c", "d", "a_e"]);
+        assert_eq!(scene.get_object("d").unwrap().parent.as_deref(), Some("c"));
+    }
+
+    #[test]
+    fn insert_with_node_names() {
+        let mut scene = deep_scene();
+        let group = Object {
+            sub_objects: SubObject::Discreteness { node_names: vec!["c".to_string(), "a_e".to_string()] },
+            ..Object::new("group".to_string()).with_parent("root")
+        };
+
+        scene.insert_object(group).unwrap();
+
+        assert_eq!(names(scene.children_of("group").unwrap()), vec!["c", "a_e"]);
+        assert_eq!(names(scene.children_of("b").unwrap()), Vec::<&str>::new());
+        assert_eq!(depth_first(&scene), vec!["root", "a", "b", "group", "c", "d", "a_e"]);
+    }
+
+    #[test]
+    fn insert_errors_leave_scene_unchanged() {
+        let mut scene = deep_scene();
+        let before = depth_first(&scene).iter().map(|x| x.to_string()).collect::<Vec<_>>();
+
+        assert_eq!(scene.insert_object(Object::new("c".to_string())), Err(SceneErr::ExistObject));
+        assert_eq!(scene.insert_object(Object::new("x".to_string()).with_parent("missing")), Err(SceneErr::NoExitObject));
+
+        /* "a" is an ancestor of the new parent "d" */
+        let cycle = Object {
+            sub_objects: SubObject::Discreteness { node_names: vec!["a_e".to_string(), "a".to_string()] },
+            ..Object::new("x".to_string()).with_parent("d")
+        };
+        assert_eq!(scene.insert_object(cycle), Err(SceneErr::Cycle));
+
+        let missing = Object {
+            sub_objects: SubObject::Discreteness { node_names: vec!["a_e".to_string(), "missing".to_string()] },
+            ..Object::new("x".to_string())
+        };
+        assert_eq!(scene.insert_object(missing), Err(SceneErr::NoExitObject));
+
+        let duplicate = Object {
+            sub_objects: SubObject::Discreteness { node_names: vec!["a_e".to_string(), "a_e".to_string()] },
+            ..Object::new("x".to_string())
+        };
+        assert_eq!(scene.insert_object(duplicate), Err(SceneErr::ExistObject));
+
+        assert!(scene.get_object("x").is_none());
+        assert_eq!(depth_first(&scene), before);
+    }
+
+    #[test]
+    fn remove_recursive() {
+        let mut scene = deep_scene();
+
+        let removed = scene.remove_object("b", true).unwrap();
+
+        assert_eq!(removed.parent.as_deref(), Some("a"));
+        assert_eq!(depth_first(&scene), vec!["root", "a", "a_e"]);
+        assert!(scene.get_object("c").is_none());
+        assert!(scene.get_object("d").is_none());
+        assert_eq!(scene.remove_object("b", true).err(), Some(SceneErr::NoExitObject));
+    }
+
+    #[test]
+    fn remove_reattach() {
+        let mut scene = deep_scene();
+        let mut transform = Transform::new();
+        transform.translate(1.0, 2.0, 3.0);
+        scene.set_transform("b", transform).unwrap();
+        let mut transform = Transform::new();
+        transform.translate(0.0, 4.0, 0.0);
+        scene.set_transform("c", transform).unwrap();
+        let world = scene.world_transform("c").unwrap();
+
+        scene.remove_object("b", false).unwrap();
+
+        assert_eq!(scene.get_object("c").unwrap().parent.as_deref(), Some("a"));
+        assert_eq!(depth_first(&scene), vec!["root", "a", "a_e", "c", "d"]);
+        assert!(close(scene.world_transform("c").unwrap(), world));
+    }
+
+    #[test]
+    fn rename() {
+        let mut scene = deep_scene();
+
+        scene.rename_object("c", "c2").unwrap();
+
+        assert!(scene.get_object("c").is_none());
+        assert_eq!(scene.get_object("d").unwrap().parent.as_deref(), Some("c2"));
+        assert_eq!(names(scene.children_of("b").unwrap()), vec!["c2"]);
+        assert_eq!(depth_first(&scene), vec!["root", "a", "b", "c2", "d", "a_e"]);
+
+        scene.rename_object("root", "top").unwrap();
+        assert_eq!(scene.objects, vec!["top".to_string()]);
+
+        assert_eq!(scene.rename_object("d", "d"), Ok(()));
+        assert_eq!(scene.rename_object("d", "a"), Err(SceneErr::ExistObject));
+        assert_eq!(scene.rename_object("missing", "x"), Err(SceneErr::NoExitObject));
+        assert_eq!(scene.rename_object("missing", "missing"), Err(SceneErr::NoExitObject));
+    }
+
+    #[test]
+    fn reparent() {
+        let mut scene = deep_scene();
+
+        scene.reparent("d", Some("root"), false).unwrap();
+        assert_eq!(names(scene.children_of("root").unwrap()), vec!["a", "d"]);
+        assert_eq!(names(scene.children_of("c").unwrap()), Vec::<&str>::new());
+
+        scene.reparent("c", None, true).unwrap();
+        assert_eq!(scene.objects, vec!["root".to_string(), "c".to_string()]);
+
+        assert_eq!(scene.reparent("a", Some("b"), false), Err(SceneErr::Cycle));
+        assert_eq!(scene.reparent("a", Some("a"), false), Err(SceneErr::Cycle));
+        assert_eq!(scene.reparent("missing", Some("a"), false), Err(SceneErr::NoExitObject));
+        assert_eq!(scene.reparent("a", Some("missing"), false), Err(SceneErr::NoExitObject));
+    }
+
+    #[test]
+    fn children_of() {
+        let scene = deep_scene();
+
+        assert_eq!(names(scene.children_of("a").unwrap()), vec!["b", "a_e"]);
+        assert_eq!(names(scene.children_of("d").unwrap()), Vec::<&str>::new());
+        assert_eq!(scene.children_of("missing").err(), Some(SceneErr::NoExitObject));
+    }
+
+    #[test]
+    fn find() {
+        let scene = deep_scene();
+
+        assert_eq!(names(scene.find_by_prefix("a")), vec!["a", "a_e"]);
+        assert_eq!(names(scene.find_by_prefix("missing")), Vec::<&str>::new());
+        assert_eq!(names(scene.find_by_tag("level")), vec!["root", "a", "b"]);
+        assert_eq!(names(scene.find_by_tag("leaf")), vec!["d", "a_e"]);
+    }
+}
@@ -0,0 +1,414 @@
+use super::object::*;
+use super::scene::{Scene, SceneErr};
+use crate::asset::{AssetManager, AssetManagerFileErr};
+use crate::base::light::{Light, LightUnit};
+use crate::base::transform::Transform;
+use rmu::raw::{Mat4f, Vec3f};
+use serde_derive::{Serialize,Deserialize};
+use std::fs::{File,read_to_string};
+use std::io::Write;
+use std::path::Path;
+
+/// version of the scene file schema, increase it when the schema changes
+/// 
+/// * 1: first version
+/// * 2: lights with physical intensity, unit and range
+pub const SCENE_FILE_VERSION: u32 = 2;
+
+/// # Scene File
+/// objects are written in depth first order so that the order of sub objects survives a round trip,
+/// meshes are not inlined but referenced by their asset name, `meshes` has the paths of these assets
+#[derive(Serialize, Deserialize)]
+pub struct SceneFile {
+    pub version: u32,
+    pub name: String,
+    pub objects: Vec<ObjectData>,
+    pub lights: Vec<LightObject>,
+    pub cameras: Vec<CameraObject>,
+    #[serde(default)]
+    pub materials: Vec<MaterialObject>,
+    #[serde(default)]
+    pub material_instances: Vec<MaterialInstanceObject>,
+    #[serde(default)]
+    pub meshes: Vec<MeshAssetData>,
+}
+
+/// mesh asset of the `AssetManager` referenced by objects
+#[derive(Serialize, Deserialize)]
+pub struct MeshAssetData {
+    pub name: String,
+    pub path: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ObjectData {
+    pub name: String,
+    pub parent: Option<String>,
+    /// mesh asset name
+    pub mesh: Option<String>,
+    pub material: Option<String>,
+    pub tags: Vec<String>,
+    pub parent_inverse: Mat4f,
+    pub transform: Transform,
+}
+
+/// light of the version 1 schema, it has no intensity and the color is the radiance at one meter
+#[derive(Deserialize)]
+#[serde(tag = "type")]
+enum LightV1 {
+    #[serde(rename = "PointLight")]
+    Point { position: Vec3f, color: Vec3f },
+    #[serde(rename = "ParallelLight")]
+    Parallel { direction: Vec3f, color: Vec3f },
+    /// `theta` is the half angle of the cone
+    #[serde(rename = "SpotLight")]
+    Spot { position: Vec3f, direction: Vec3f, theta: f32, color: Vec3f },
+}
+
+#[derive(Deserialize)]
+struct LightObjectV1 {
+    name: String,
+    light: LightV1,
+}
+
+/// version 1 renderer faded spot lights out over this last fraction of the cone angle `theta`
+const SPOT_LIGHT_DIM_V1: f32 = 0.1;
+
+impl From<LightObjectV1> for LightObject {
+    fn from(light_object: LightObjectV1) -> Self {
+        let light = match light_object.light {
+            LightV1::Point { position, color } =>
+                Light::point(position, color, 1.0).with_intensity(1.0, LightUnit::Candela),
+            LightV1::Parallel { direction, color } =>
+                Light::parallel(direction, color, 1.0),
+            LightV1::Spot { position, direction, theta, color } =>
+                Light::spot(position, direction, theta * (1.0 - SPOT_LIGHT_DIM_V1), theta, color, 1.0)
+                    .with_intensity(1.0, LightUnit::Candela),
+        };
+
+        LightObject {
+            name: light_object.name,
+            light,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum SceneFileErr {
+    PathErr,
+    SrcErr,
+    /// the file is written by a newer schema, or by a schema older than the first version
+    VersionErr(u32),
+    AssetErr(String, AssetManagerFileErr),
+    SceneErr(SceneErr),
+}
+
+impl From<SceneErr> for SceneFileErr {
+    fn from(err: SceneErr) -> Self {
+        SceneFileErr::SceneErr(err)
+    }
+}
+
+impl SceneFile {
+    /// parse a scene file of any version up to `SCENE_FILE_VERSION`, older versions are migrated
+    pub fn parse(src: &str) -> Result<Self,SceneFileErr> {
+        let mut value: toml::Value = toml::from_str(src).map_err(|_| SceneFileErr::SrcErr)?;
+
+        let version = match value.get("version").and_then(|x| x.as_integer()) {
+            Some(version) if version >= 1 && version <= SCENE_FILE_VERSION as i64 => version as u32,
+            Some(version) => return Err(SceneFileErr::VersionErr(version.clamp(0, u32::MAX as i64) as u32)),
+            None => return Err(SceneFileErr::SrcErr),
+        };
+
+        /* version 1: lights are a tagged enum of point, parallel and spot light */
+        let lights_v1 = if version == 1 {
+            let table = value.as_table_mut().ok_or(SceneFileErr::SrcErr)?;
+            let lights: Vec<LightObjectV1> = match table.insert("lights".to_string(), toml::Value::Array(Vec::new())) {
+                Some(lights) => lights.try_into().map_err(|_| SceneFileErr::SrcErr)?,
+                None => Vec::new(),
+            };
+            Some(lights)
+        } else {
+            None
+        };
+
+        let mut scene_file: SceneFile = value.try_into().map_err(|_| SceneFileErr::SrcErr)?;
+
+        if let Some(lights) = lights_v1 {
+            scene_file.lights = lights.into_iter().map(LightObject::from).collect();
+        }
+
+        scene_file.version = SCENE_FILE_VERSION;
+        Ok(scene_file)
+    }
+}
+
+impl Scene {
+    /// save the scene as toml, a mesh with asset is referenced by its asset name and not written.
+    /// a mesh without asset is written as `<object name>.mesh` next to the scene file and
+    /// becomes the mesh asset of the object, `<object name>_<n>.mesh` if that asset name is taken.
+    /// write a changed asset with `AssetManager::save_mesh`
+    pub fn save(&mut self, path: &str, asset_manager: &mut AssetManager) -> Result<(),SceneFileErr> {
+        let directory = Path::new(path).parent().unwrap_or(Path::new(""));
+        let names: Vec<String> = self.depth_first().map(|object| object.name.clone()).collect();
+        let mut objects = Vec::new();
+        let mut meshes: Vec<MeshAssetData> = Vec::new();
+
+        for name in names {
+            let object = self.data.get_mut(&name).unwrap();
+
+            let mesh = match &object.sub_objects {
+                SubObject::Atomic(PrimitiveObject::Data(mesh)) => match &object.mesh_asset {
+                    Some(mesh_asset) if asset_manager.mesh_path(mesh_asset).is_some() => Some(mesh_asset.clone()),
+                    _ => {
+                        /* an asset of the same name may be shared by other objects, never write over it */
+                        let mut mesh_asset = object.name.clone();
+                        let mut n = 0;
+                        while asset_manager.mesh_path(&mesh_asset).is_some() {
+                            n += 1;
+                            mesh_asset = format!("{}_{}", object.name, n);
+                        }
+
+                        let mesh_path = directory.join(format!("{}.mesh", mesh_asset));
+                        asset_manager.add_mesh(mesh_asset.clone(), mesh_path.to_string_lossy().into_owned());
+
+                        asset_manager
+                            .save_mesh(&mesh_asset, mesh)
+                            .map_err(|err| SceneFileErr::AssetErr(mesh_asset.clone(), err))?;
+
+                        object.mesh_asset = Some(mesh_asset.clone());
+                        Some(mesh_asset)
+                    },
+                },
+                _ => None,
+            };
+
+            if let Some(mesh_asset) = &mesh {
+                if meshes.iter().all(|x| x.name != *mesh_asset) {
+                    meshes.push(MeshAssetData {
+                        name: mesh_asset.clone(),
+                        path: asset_manager.mesh_path(mesh_asset).unwrap().clone(),
+                    });
+                }
+            }
+
+            objects.push(ObjectData {
+                name: object.name.clone(),
+                parent: object.parent.clone(),
+                mesh,
+                material: object.material.clone(),
+                tags: object.tags.clone(),
+                parent_inverse: object.parent_inverse,
+                transform: object.transform,
+            });
+        }
+
+        let scene_file = SceneFile {
+            version: SCENE_FILE_VERSION,
+            name: self.name.clone(),
+            objects,
+            lights: self.lights.clone(),
+            cameras: self.cameras.clone(),
+            materials: self.materials.clone(),
+            material_instances: self.material_instances.clone(),
+            meshes,
+        };
+
+        /* through `toml::Value` so that empty arrays are written before the arrays of tables */
+        let src = toml::Value::try_from(&scene_file)
+            .and_then(|value| toml::to_string(&value))
+            .map_err(|_| SceneFileErr::SrcErr)?;
+
+        match File::create(path) {
+            Ok(mut file) => file.write_all(src.as_bytes()).map_err(|_| SceneFileErr::PathErr),
+            Err(_) => Err(SceneFileErr::PathErr),
+        }
+    }
+
+    /// load a scene saved by `Scene::save`, meshes are read through the asset manager.
+    /// mesh assets of the scene file the asset manager doesn't have are added to it
+    pub fn load(path: &str, asset_manager: &mut AssetManager) -> Result<Self,SceneFileErr> {
+        let src = read_to_string(path).map_err(|_| SceneFileErr::PathErr)?;
+        let scene_file = SceneFile::parse(src.as_str())?;
+
+        for mesh in scene_file.meshes {
+            if asset_manager.mesh_path(&mesh.name).is_none() {
+                asset_manager.add_mesh(mesh.name, mesh.path);
+            }
+        }
+
+        let mut scene = Scene::new(scene_file.name);
+
+        for object_data in scene_file.objects {
+            let primitive_object = match &object_data.mesh {
+                Some(mesh_name) => PrimitiveObject::Data(
+                    asset_manager
+                        .load_mesh(mesh_name)
+                        .map_err(|err| SceneFileErr::AssetErr(mesh_name.clone(), err))?
+                ),
+                None => PrimitiveObject::Empty,
+            };
+
+            let mut object = Object::from(object_data.name, primitive_object);
+            object.parent = object_data.parent;
+            object.material = object_data.material;
+            object.tags = object_data.tags;
+            object.parent_inverse = object_data.parent_inverse;
+            object.transform = object_data.transform;
+            object.mesh_asset = object_data.mesh;
+
+            scene.insert_object(object)?;
+        }
+
+        scene.lights = scene_file.lights;
+        scene.cameras = scene_file.cameras;
+        scene.materials = scene_file.materials;
+        scene.material_instances = scene_file.material_instances;
+
+        Ok(scene)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::base::light::Light;
+    use crate::model::generate::{cube, plane};
+    use std::fs::{create_dir_all, read, remove_dir_all, write};
+
+    #[test]
+    fn migrate_version_1() {
+        let src = r#"
+            version = 1
+            name = "old"
+            objects = []
+            cameras = []
+
+            [[lights]]
+            name = "point"
+            [lights.light]
+            type = "PointLight"
+            position = [1.0, 2.0, 3.0]
+            color = [1.0, 0.5, 0.25]
+
+            [[lights]]
+            name = "sun"
+            [lights.light]
+            type = "ParallelLight"
+            direction = [0.0, -1.0, 0.0]
+            color = [1.0, 1.0, 1.0]
+
+            [[lights]]
+            name = "spot"
+            [lights.light]
+            type = "SpotLight"
+            position = [0.0, 4.0, 0.0]
+            direction = [0.0, -1.0, 0.0]
+            theta = 0.5
+            color = [1.0, 1.0, 1.0]
+        "#;
+
+        let scene_file = SceneFile::parse(src).unwrap();
+
+        assert_eq!(scene_file.version, SCENE_FILE_VERSION);
+        assert_eq!(scene_file.lights.len(), 3);
+        assert_eq!(scene_file.lights[0].light, Light::point([1.0, 2.0, 3.0], [1.0, 0.5, 0.25], 1.0).with_intensity(1.0, LightUnit::Candela));
+        assert_eq!(scene_file.lights[1].light, Light::parallel([0.0, -1.0, 0.0], [1.0, 1.0, 1.0], 1.0));
+        assert_eq!(scene_file.lights[2].light.light_type, crate::base::light::LightType::Spot { inner_angle: 0.45, outer_angle: 0.5 });
+    }
+
+    #[test]
+    fn version_out_of_range() {
+        let src = |version: i64| format!("version = {}\nname = \"x\"\nobjects = []\nlights = []\ncameras = []\n", version);
+
+        assert!(SceneFile::parse(&src(SCENE_FILE_VERSION as i64)).is_ok());
+        assert!(matches!(SceneFile::parse(&src(SCENE_FILE_VERSION as i64 + 1)), Err(SceneFileErr::VersionErr(x)) if x == SCENE_FILE_VERSION + 1));
+        assert!(matches!(SceneFile::parse(&src(0)), Err(SceneFileErr::VersionErr(0))));
+        assert!(matches!(SceneFile::parse("name = \"x\""), Err(SceneFileErr::SrcErr)));
+    }
+
+    #[test]
+    fn save_load_save() {
+        let directory = std::env::temp_dir().join(format!("scene_file_{}", std::process::id()));
+        create_dir_all(&directory).unwrap();
+        let path_a = directory.join("a.toml").to_string_lossy().into_owned();
+        let path_b = directory.join("b.toml").to_string_lossy().into_owned();
+
+        let mut scene = Scene::new("scene".to_string());
+        scene.insert_object(Object::new("root".to_string()).with_tag("root")).unwrap();
+        scene.insert_object(Object::from("box".to_string(), PrimitiveObject::Data(cube(1.0))).with_parent("root").with_material("red")).unwrap();
+        scene.add_light("sun".to_string(), Light::parallel([0.0, -1.0, 0.0], [1.0, 1.0, 1.0], 10.0));
+
+        let mut asset_manager = AssetManager::new();
+        scene.save(&path_a, &mut asset_manager).unwrap();
+        assert_eq!(scene.get_object("box").unwrap().mesh_asset.as_deref(), Some("box"));
+
+        /* a mesh with asset is not written again */
+        let mesh_path = asset_manager.mesh_path("box").unwrap().clone();
+        let mesh_bytes = read(&mesh_path).unwrap();
+        let mut loaded = Scene::load(&path_a, &mut asset_manager).unwrap();
+        write(&mesh_path, plane(1.0).as_bytes()).unwrap();
+
+        loaded.save(&path_b, &mut asset_manager).unwrap();
+
+        assert_eq!(read(&path_a).unwrap(), read(&path_b).unwrap());
+        assert_eq!(read(&mesh_path).unwrap(), plane(1.0).as_bytes());
+        assert_ne!(mesh_bytes, plane(1.0).as_bytes());
+
+        remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn save_keeps_asset_named_after_object() {
+        let directory = std::env::temp_dir().join(format!("scene_file_shared_{}", std::process::id()));
+        create_dir_all(&directory).unwrap();
+        let path = directory.join("scene.toml").to_string_lossy().into_owned();
+
+        let mut asset_manager = AssetManager::new();
+        asset_manager.add_mesh("b".to_string(), directory.join("b.mesh").to_string_lossy().into_owned());
+        asset_manager.save_mesh("b", &cube(1.0)).unwrap();
+
+        /* `a` uses the asset `b`, the object `b` has its own mesh without asset */
+        let mut scene = Scene::new("scene".to_string());
+        scene.insert_object(Object::from("a".to_string(), PrimitiveObject::Data(cube(1.0))).with_mesh_asset("b")).unwrap();
+        scene.insert_object(Object::from("b".to_string(), PrimitiveObject::Data(plane(1.0)))).unwrap();
+
+        scene.save(&path, &mut asset_manager).unwrap();
+
+        assert_eq!(scene.get_object("a").unwrap().mesh_asset.as_deref(), Some("b"));
+        assert_eq!(scene.get_object("b").unwrap().mesh_asset.as_deref(), Some("b_1"));
+        assert_eq!(read(asset_manager.mesh_path("b").unwrap()).unwrap(), cube(1.0).as_bytes());
+        assert_eq!(read(asset_manager.mesh_path("b_1").unwrap()).unwrap(), plane(1.0).as_bytes());
+
+        remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn load_with_new_asset_manager() {
+        let directory = std::env::temp_dir().join(format!("scene_file_new_manager_{}", std::process::id()));
+        create_dir_all(&directory).unwrap();
+        let path_a = directory.join("a.toml").to_string_lossy().into_owned();
+        let path_b = directory.join("b.toml").to_string_lossy().into_owned();
+
+        let mut scene = Scene::new("scene".to_string());
+        scene.insert_object(Object::from("box".to_string(), PrimitiveObject::Data(cube(1.0)))).unwrap();
+        scene.insert_object(Object::from("floor".to_string(), PrimitiveObject::Data(plane(1.0)))).unwrap();
+        scene.save(&path_a, &mut AssetManager::new()).unwrap();
+
+        /* the paths of the meshes come from the scene file */
+        let mut asset_manager = AssetManager::new();
+        let mut loaded = Scene::load(&path_a, &mut asset_manager).unwrap();
+
+        assert_eq!(loaded.get_object("box").unwrap().mesh_asset.as_deref(), Some("box"));
+        assert!(asset_manager.mesh_path("floor").is_some());
+        match &loaded.get_object("floor").unwrap().sub_objects {
+            SubObject::Atomic(PrimitiveObject::Data(mesh)) => assert_eq!(mesh.as_bytes(), plane(1.0).as_bytes()),
+            _ => panic!("floor has no mesh"),
+        }
+
+        loaded.save(&path_b, &mut asset_manager).unwrap();
+        assert_eq!(read(&path_a).unwrap(), read(&path_b).unwrap());
+
+        remove_dir_all(&directory).unwrap();
+    }
+}
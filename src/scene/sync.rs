@@ -0,0 +1,325 @@
+use super::object::*;
+use super::scene::Scene;
+use crate::base::light::Light;
+use crate::base::material::Material;
+use crate::base::material_definition::MaterialErr;
+use crate::base::material_instance::MaterialInstance;
+use crate::base::mesh::Mesh;
+use crate::renderer::{RendererManager, DataUpdate, GMesh, GSkinnedMesh, Object as RenderObject};
+use crate::renderer::mesh_load::MeshLoad;
+use rmu::raw::Mat4f;
+use std::collections::HashMap;
+
+/// # Scene Sync
+/// keep what was pushed to a `RendererManager` and only send the difference to the scene.
+/// the mesh of a asset is uploaded once as `asset:<asset name>` and shared by the objects of the asset,
+/// other meshes and meshes with morph targets are uploaded as `object:<object name>`.
+/// a mesh is uploaded again when `Object::mesh_version` changed, see `Object::mesh_mut`,
+/// or when the object was replaced by a new object of the same name.
+/// use one `SceneSync` for each renderer.
+pub struct SceneSync {
+    /// how a `Mesh` is converted to a `GMesh`
+    pub mesh_load: fn(&Mesh) -> GMesh,
+    /// material of objects without material
+    pub default_material: String,
+    /// camera pushed by `update_camera`
+    pub camera: Option<String>,
+    /// uploaded meshes and the version of their data
+    meshes: HashMap<String,u64>,
+    morph_weights: HashMap<String,Vec<f32>>,
+    objects: HashMap<String,SyncedObject>,
+    materials: HashMap<String,Material>,
+    material_instances: HashMap<String,MaterialInstance>,
+    lights: HashMap<String,Light>,
+}
+
+struct SyncedObject {
+    id: u64,
+    mesh_name: String,
+    material_name: String,
+    transform: Mat4f,
+}
+
+impl SceneSync {
+    pub fn new() -> Self {
+        Self {
+            mesh_load: GMesh::flat,
+            default_material: String::new(),
+            camera: None,
+            meshes: HashMap::new(),
+            morph_weights: HashMap::new(),
+            objects: HashMap::new(),
+            materials: HashMap::new(),
+            material_instances: HashMap::new(),
+            lights: HashMap::new(),
+        }
+    }
+
+    /// push the changes of the scene since the last sync. a material rejected by the renderer
+    /// is pushed again next sync, the first error is returned after the rest of the scene is pushed
+    pub fn sync<R: RendererManager>(&mut self, scene: &mut Scene, renderer: &mut R) -> Result<(), MaterialErr> {
+        let result = self.sync_materials(scene, renderer);
+        self.sync_objects(scene, renderer);
+        self.sync_lights(scene, renderer);
+
+        if let Some(camera_name) = &self.camera {
+            if let Some(camera) = scene.get_camera(camera_name.clone()) {
+                renderer.update_camera(&camera);
+            }
+        }
+
+        result
+    }
+
+    /// forget what was pushed, the next sync push the whole scene again
+    pub fn reset(&mut self) {
+        self.meshes.clear();
+        self.morph_weights.clear();
+        self.objects.clear();
+        self.materials.clear();
+        self.material_instances.clear();
+        self.lights.clear();
+    }
+
+    /* removed names go first, a name can change from material to instance. parents are pushed before
+       instances, and instances are not pushed again when their parent changes */
+    fn sync_materials<R: RendererManager>(&mut self, scene: &Scene, renderer: &mut R) -> Result<(), MaterialErr> {
+        let removed: Vec<String> = self.materials
+            .keys()
+            .filter(|name| scene.materials.iter().all(|x| x.name != **name))
+            .chain(self.material_instances
+                .keys()
+                .filter(|name| scene.material_instances.iter().all(|x| x.name != **name)))
+            .cloned()
+            .collect();
+
+        for name in removed {
+            renderer.remove_material(&name);
+            self.materials.remove(&name);
+            self.material_instances.remove(&name);
+        }
+
+        let mut result = Ok(());
+
+        for material_object in scene.materials.iter() {
+            if self.materials.get(&material_object.name) != Some(&material_object.material) {
+                match renderer.update_material(&material_object.name, &material_object.material) {
+                    Ok(()) => {
+                        self.materials.insert(material_object.name.clone(), material_object.material.clone());
+                    },
+                    Err(err) => if result.is_ok() {
+                        result = Err(err);
+                    },
+                }
+            }
+        }
+
+        for instance_object in scene.material_instances.iter() {
+            if self.material_instances.get(&instance_object.name) != Some(&instance_object.instance) {
+                match renderer.update_material_instance(&instance_object.name, &instance_object.instance) {
+                    Ok(()) => {
+                        self.material_instances.insert(instance_object.name.clone(), instance_object.instance.clone());
+                    },
+                    Err(err) => if result.is_ok() {
+                        result = Err(err);
+                    },
+                }
+            }
+        }
+
+        result
+    }
+
+    fn sync_objects<R: RendererManager>(&mut self, scene: &mut Scene, renderer: &mut R) {
+        scene.update_world_transforms();
+
+        let mut alive: HashMap<String,()> = HashMap::new();
+        let mut alive_meshes: HashMap<String,()> = HashMap::new();
+
+        for object in scene.depth_first() {
+            let mesh = match &object.sub_objects {
+                SubObject::Atomic(PrimitiveObject::Data(mesh)) => mesh,
+                _ => continue,
+            };
+
+            alive.insert(object.name.clone(), ());
+
+            let object_mesh_name = format!("object:{}", object.name);
+
+            /* the object was removed and a new one inserted with its name, the old mesh is not its mesh */
+            if let Some(synced) = self.objects.get(&object.name) {
+                if synced.id != object.id {
+                    self.meshes.remove(&object_mesh_name);
+                    self.morph_weights.remove(&object_mesh_name);
+                }
+            }
+
+            /* the data of a asset never changes, the morph weights are own by each object */
+            let (mesh_name, mesh_version) = match &object.mesh_asset {
+                Some(asset) if mesh.morph_targets.is_empty() => (format!("asset:{}", asset), 0),
+                _ => (object_mesh_name, object.mesh_version),
+            };
+
+            if alive_meshes.insert(mesh_name.clone(), ()).is_none() {
+                let morph_weights = mesh.morph_weights();
+                if self.meshes.get(&mesh_name) != Some(&mesh_version) {
+                    if mesh.weights.is_empty() {
+                        renderer.update_mesh(&mesh_name, &GMesh::morph(mesh, self.mesh_load));
+                    } else {
+                        renderer.update_skinned_mesh(&mesh_name, &GSkinnedMesh::flat(&mesh.morphed()));
+                    }
+                    self.meshes.insert(mesh_name.clone(), mesh_version);
+                    self.morph_weights.insert(mesh_name.clone(), morph_weights);
+                } else if self.morph_weights.get(&mesh_name) != Some(&morph_weights) {
+                    /* only the weights changed, the topology and the indices are same */
+                    if mesh.weights.is_empty() {
+                        renderer.update_mesh_vertices(&mesh_name, &GMesh::morph(mesh, self.mesh_load).vertices);
+                    } else {
+                        renderer.update_skinned_mesh_vertices(&mesh_name, &GSkinnedMesh::flat(&mesh.morphed()).vertices);
+                    }
+                    self.morph_weights.insert(mesh_name.clone(), morph_weights);
+                }
+            }
+
+            let material_name = object.material.clone().unwrap_or(self.default_material.clone());
+
+            let update = match self.objects.get(&object.name) {
+                None => DataUpdate::ALL,
+                Some(synced) if synced.id != object.id || synced.mesh_name != mesh_name => DataUpdate::ALL,
+                Some(synced) => match (synced.material_name == material_name, synced.transform == object.world_transform) {
+                    (true, true) => DataUpdate::Not,
+                    (true, false) => DataUpdate::Transfrom,
+                    (false, true) => DataUpdate::Material,
+                    (false, false) => DataUpdate::ALL,
+                },
+            };
+
+            if update != DataUpdate::Not {
+                let mut render_object = RenderObject::new(mesh_name.clone(), material_name.clone(), object.world_transform);
+                render_object.update = update;
+                renderer.update_object(&object.name, &mut render_object);
+
+                self.objects.insert(object.name.clone(), SyncedObject {
+                    id: object.id,
+                    mesh_name,
+                    material_name,
+                    transform: object.world_transform,
+                });
+            }
+        }
+
+        let removed: Vec<String> = self.objects
+            .keys()
+            .filter(|name| !alive.contains_key(*name))
+            .cloned()
+            .collect();
+
+        for name in removed {
+            renderer.remove_object(&name);
+            self.objects.remove(&name);
+        }
+
+        let removed: Vec<String> = self.meshes
+            .keys()
+            .filter(|name| !alive_meshes.contains_key(*name))
+            .cloned()
+            .collect();
+
+        for name in removed {
+            renderer.remove_mesh(&name);
+            self.meshes.remove(&name);
+            self.morph_weights.remove(&name);
+        }
+    }
+
+    fn sync_lights<R: RendererManager>(&mut self, scene: &Scene, renderer: &mut R) {
+        for light_object in scene.lights.iter() {
+            if self.lights.get(&light_object.name) != Some(&light_object.light) {
+                renderer.update_light(&light_object.name, &light_object.light.into());
+                self.lights.insert(light_object.name.clone(), light_object.light);
+            }
+        }
+
+        let removed: Vec<String> = self.lights
+            .keys()
+            .filter(|name| scene.lights.iter().all(|x| x.name != **name))
+            .cloned()
+            .collect();
+
+        for name in removed {
+            renderer.remove_light(&name);
+            self.lights.remove(&name);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::Config;
+    use crate::backend::recording::{Command, RecordingRenderer};
+    use crate::model::generate::{cube, plane};
+
+    fn uploads(renderer: &mut RecordingRenderer) -> Vec<String> {
+        renderer.take_commands()
+            .into_iter()
+            .filter_map(|command| match command {
+                Command::UpdateMesh { name, .. } => Some(name),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn asset_and_object_meshes_do_not_collide() {
+        let mut scene = Scene::new("scene".to_string());
+        scene.insert_object(Object::from("cube".to_string(), PrimitiveObject::Data(plane(1.0)))).unwrap();
+        scene.insert_object(Object::from("box".to_string(), PrimitiveObject::Data(cube(1.0))).with_mesh_asset("cube")).unwrap();
+
+        let mut renderer = RecordingRenderer::new(Config::default());
+        SceneSync::new().sync(&mut scene, &mut renderer).unwrap();
+
+        let mut meshes = Vec::new();
+        for command in renderer.take_commands() {
+            match command {
+                Command::UpdateMesh { name, vertices, .. } => meshes.push((name, vertices)),
+                Command::UpdateObject { name, object } if name == "cube" => assert_eq!(object.mesh_name, "object:cube"),
+                Command::UpdateObject { name, object } if name == "box" => assert_eq!(object.mesh_name, "asset:cube"),
+                _ => (),
+            }
+        }
+
+        meshes.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(meshes.len(), 2);
+        assert_eq!(meshes[0], ("asset:cube".to_string(), GMesh::flat(&cube(1.0)).vertices));
+        assert_eq!(meshes[1], ("object:cube".to_string(), GMesh::flat(&plane(1.0)).vertices));
+    }
+
+    #[test]
+    fn replaced_object_uploads_its_mesh() {
+        let mut scene = Scene::new("scene".to_string());
+        scene.insert_object(Object::from("a".to_string(), PrimitiveObject::Data(cube(1.0)))).unwrap();
+
+        let mut renderer = RecordingRenderer::new(Config::default());
+        let mut sync = SceneSync::new();
+        sync.sync(&mut scene, &mut renderer).unwrap();
+        assert_eq!(uploads(&mut renderer), vec!["object:a"]);
+
+        sync.sync(&mut scene, &mut renderer).unwrap();
+        assert!(uploads(&mut renderer).is_empty());
+
+        /* removed and inserted again between two syncs, the mesh version is 0 again */
+        scene.remove_object("a", false).unwrap();
+        scene.insert_object(Object::from("a".to_string(), PrimitiveObject::Data(plane(1.0)))).unwrap();
+        sync.sync(&mut scene, &mut renderer).unwrap();
+        assert_eq!(uploads(&mut renderer), vec!["object:a"]);
+
+        /* the renamed object keeps its mesh under the new name */
+        scene.rename_object("a", "b").unwrap();
+        scene.insert_object(Object::from("a".to_string(), PrimitiveObject::Data(cube(1.0)))).unwrap();
+        sync.sync(&mut scene, &mut renderer).unwrap();
+        let mut names = uploads(&mut renderer);
+        names.sort();
+        assert_eq!(names, vec!["object:a", "object:b"]);
+    }
+}